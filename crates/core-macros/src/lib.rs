@@ -3,24 +3,30 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
 use syn::LitStr;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[proc_macro]
 pub fn sc(input: TokenStream) -> TokenStream {
   let input_lit = parse_macro_input!(input as LitStr);
   let s = input_lit.value();
 
-  // Check each character
-  for c in s.chars() {
-    if !c.is_ascii() {
+  let char_count = if s.is_ascii() {
+    s.len() as u32
+  } else {
+    // same as the runtime width of a string container
+    let width = s
+      .graphemes(true)
+      .map(|grapheme| unicode_width::UnicodeWidthStr::width(grapheme).min(2))
+      .sum::<usize>();
+    if unicode_width::UnicodeWidthStr::width(s.as_str()) != unicode_width::UnicodeWidthStr::width_cjk(s.as_str()) {
       let msg = format!(
-        "Unsupported character: '{}'. Only ASCII characters are known to have a Unicode width of 1. Don't use this macro in this case.",
-        c
+        "Unsupported text: '{}'. It contains East Asian ambiguous width characters, so its width depends on the print options. Don't use this macro in this case.",
+        s
       );
       return syn::Error::new(input_lit.span(), msg).to_compile_error().into();
     }
-  }
-
-  let char_count = s.len() as u32;
+    width as u32
+  };
 
   TokenStream::from(quote! {
     {
@@ -261,6 +261,12 @@ pub struct GlobalConfiguration {
   pub use_tabs: Option<bool>,
   pub indent_width: Option<u8>,
   pub new_line_kind: Option<NewLineKind>,
+  /// Whether to remove spaces and tabs at the end of lines.
+  pub trim_trailing_whitespace: Option<bool>,
+  /// Whether to end non-empty files with exactly one newline.
+  pub insert_final_new_line: Option<bool>,
+  /// The maximum number of consecutive blank lines to keep.
+  pub max_consecutive_blank_lines: Option<u32>,
 }
 
 pub const RECOMMENDED_GLOBAL_CONFIGURATION: RecommendedGlobalConfiguration = RecommendedGlobalConfiguration {
@@ -284,6 +290,9 @@ impl From<RecommendedGlobalConfiguration> for GlobalConfiguration {
       use_tabs: Some(config.use_tabs),
       indent_width: Some(config.indent_width),
       new_line_kind: Some(config.new_line_kind),
+      trim_trailing_whitespace: None,
+      insert_final_new_line: None,
+      max_consecutive_blank_lines: None,
     }
   }
 }
@@ -302,6 +311,30 @@ where
   pub config: T,
 }
 
+/// Policies for the whitespace in the printed text.
+///
+/// These are enforced when printing with `PrintOptions::with_whitespace`, so
+/// plugins don't need to post-process the formatted text themselves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WhitespaceOptions {
+  /// Remove spaces and tabs at the end of lines.
+  pub trim_trailing_whitespace: bool,
+  /// End non-empty text with exactly one newline.
+  pub insert_final_new_line: bool,
+  /// The maximum number of consecutive blank lines to keep. No limit when `None`.
+  pub max_consecutive_blank_lines: Option<u32>,
+}
+
+impl From<&GlobalConfiguration> for WhitespaceOptions {
+  fn from(config: &GlobalConfiguration) -> Self {
+    Self {
+      trim_trailing_whitespace: config.trim_trailing_whitespace.unwrap_or(false),
+      insert_final_new_line: config.insert_final_new_line.unwrap_or(false),
+      max_consecutive_blank_lines: config.max_consecutive_blank_lines,
+    }
+  }
+}
+
 /// Resolves a collection of key value pairs to a GlobalConfiguration.
 pub fn resolve_global_config(config: &mut ConfigKeyMap) -> ResolveConfigurationResult<GlobalConfiguration> {
   let mut diagnostics = Vec::new();
@@ -324,6 +357,9 @@ pub fn resolve_global_config(config: &mut ConfigKeyMap) -> ResolveConfigurationR
         }
       }
     }),
+    trim_trailing_whitespace: get_nullable_value(config, "trimTrailingWhitespace", &mut diagnostics),
+    insert_final_new_line: get_nullable_value(config, "insertFinalNewLine", &mut diagnostics),
+    max_consecutive_blank_lines: get_nullable_value(config, "maxConsecutiveBlankLines", &mut diagnostics),
   };
 
   ResolveConfigurationResult {
@@ -471,6 +507,9 @@ mod test {
     assert_eq!(config.indent_width, None);
     assert!(config.new_line_kind.is_none());
     assert_eq!(config.use_tabs, None);
+    assert_eq!(config.trim_trailing_whitespace, None);
+    assert_eq!(config.insert_final_new_line, None);
+    assert_eq!(config.max_consecutive_blank_lines, None);
   }
 
   #[test]
@@ -480,6 +519,9 @@ mod test {
       (String::from("indentWidth"), ConfigKeyValue::from_i32(8)),
       (String::from("newLineKind"), ConfigKeyValue::from_str("crlf")),
       (String::from("useTabs"), ConfigKeyValue::from_bool(true)),
+      (String::from("trimTrailingWhitespace"), ConfigKeyValue::from_bool(true)),
+      (String::from("insertFinalNewLine"), ConfigKeyValue::from_bool(false)),
+      (String::from("maxConsecutiveBlankLines"), ConfigKeyValue::from_i32(1)),
     ]);
     let config_result = resolve_global_config(&mut global_config);
     let config = config_result.config;
@@ -488,6 +530,9 @@ mod test {
     assert_eq!(config.indent_width, Some(8));
     assert_eq!(config.new_line_kind, Some(NewLineKind::CarriageReturnLineFeed));
     assert_eq!(config.use_tabs, Some(true));
+    assert_eq!(config.trim_trailing_whitespace, Some(true));
+    assert_eq!(config.insert_final_new_line, Some(false));
+    assert_eq!(config.max_consecutive_blank_lines, Some(1));
   }
 
   #[test]
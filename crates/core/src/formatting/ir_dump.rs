@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::*;
+
+impl PrintItems {
+  /// Gets an indented dump of the print items for debugging and snapshot tests.
+  ///
+  /// Each print item is output on its own line and the true and false paths of
+  /// conditions are indented below them. Paths that are referenced more than once
+  /// are labelled (ex. `path #1:`) the first time and afterwards only referenced
+  /// (ex. `path #1 (repeated)`).
+  ///
+  /// Only available in debug builds because that's where conditions and infos
+  /// keep their names, which keeps the output the same from run to run.
+  pub fn get_ir_dump(&self) -> String {
+    match self.first_node {
+      Some(first_node) => get_ir_dump(first_node),
+      None => String::new(),
+    }
+  }
+}
+
+enum DumpItem {
+  Path(PrintItemPath, usize),
+  /// A path referenced in multiple places.
+  SharedPath(PrintItemPath, usize),
+  Line(&'static str, usize),
+}
+
+fn get_ir_dump(first_node: PrintItemPath) -> String {
+  let path_ref_counts = get_path_ref_counts(first_node);
+  let mut path_labels = HashMap::new();
+  let mut text = String::new();
+  // do not use recursion as it will easily overflow the stack
+  let mut stack = vec![DumpItem::Path(first_node, 0)];
+  while let Some(dump_item) = stack.pop() {
+    let (node, indent) = match dump_item {
+      DumpItem::Path(node, indent) => (node, indent),
+      DumpItem::SharedPath(path, indent) => {
+        let next_label = path_labels.len() + 1;
+        match path_labels.get(&get_path_key(path)) {
+          Some(label) => write_line(&mut text, indent, &format!("path #{} (repeated)", label)),
+          None => {
+            path_labels.insert(get_path_key(path), next_label);
+            write_line(&mut text, indent, &format!("path #{}:", next_label));
+            stack.push(DumpItem::Path(path, indent + 1));
+          }
+        }
+        continue;
+      }
+      DumpItem::Line(line, indent) => {
+        write_line(&mut text, indent, line);
+        continue;
+      }
+    };
+
+    if let Some(next) = node.get_next() {
+      stack.push(DumpItem::Path(next, indent));
+    }
+
+    let line = match node.get_item() {
+      PrintItem::String(text) => format!("{:?}", text.text),
+      PrintItem::Signal(signal) => format!("signal: {:?}", signal),
+      PrintItem::Info(info) => {
+        let (kind, name) = match info {
+          Info::LineNumber(info) => ("line number", info.name()),
+          Info::ColumnNumber(info) => ("column number", info.name()),
+          Info::IsStartOfLine(info) => ("is start of line", info.name()),
+          Info::IndentLevel(info) => ("indent level", info.name()),
+          Info::LineStartColumnNumber(info) => ("line start column number", info.name()),
+          Info::LineStartIndentLevel(info) => ("line start indent level", info.name()),
+        };
+        format!("info: {} ({})", name, kind)
+      }
+      PrintItem::Anchor(Anchor::LineNumber(anchor)) => format!("anchor: {} (line number)", anchor.name()),
+      PrintItem::ConditionReevaluation(reevaluation) => format!("reevaluation: {}", reevaluation.name()),
+      PrintItem::SourcePosition(pos) => format!("source position: {}", pos),
+      PrintItem::Condition(condition) => {
+        if let Some(false_path) = condition.false_path() {
+          stack.push(get_path_dump_item(false_path, indent + 2, &path_ref_counts));
+          stack.push(DumpItem::Line("false:", indent + 1));
+        }
+        if let Some(true_path) = condition.true_path() {
+          stack.push(get_path_dump_item(true_path, indent + 2, &path_ref_counts));
+          stack.push(DumpItem::Line("true:", indent + 1));
+        }
+        format!("condition: {}", condition.name())
+      }
+      PrintItem::RcPath(path) => {
+        // inlined unless it's shared
+        stack.push(get_path_dump_item(path, indent, &path_ref_counts));
+        continue;
+      }
+    };
+    write_line(&mut text, indent, &line);
+  }
+  text
+}
+
+fn get_path_dump_item(path: PrintItemPath, indent: usize, path_ref_counts: &HashMap<*const PrintNodeCell, usize>) -> DumpItem {
+  if path_ref_counts.get(&get_path_key(path)).copied().unwrap_or(0) > 1 {
+    DumpItem::SharedPath(path, indent)
+  } else {
+    DumpItem::Path(path, indent)
+  }
+}
+
+fn get_path_ref_counts(first_node: PrintItemPath) -> HashMap<*const PrintNodeCell, usize> {
+  let mut ref_counts = HashMap::new();
+  let mut stack = vec![first_node];
+  while let Some(node) = stack.pop() {
+    if let Some(next) = node.get_next() {
+      stack.push(next);
+    }
+    let paths = match node.get_item() {
+      PrintItem::RcPath(path) => [Some(path), None],
+      PrintItem::Condition(condition) => [*condition.true_path(), *condition.false_path()],
+      _ => continue,
+    };
+    for path in paths.into_iter().flatten() {
+      let count = ref_counts.entry(get_path_key(path)).or_insert(0);
+      *count += 1;
+      // only walk a path the first time it's found
+      if *count == 1 {
+        stack.push(path);
+      }
+    }
+  }
+  ref_counts
+}
+
+fn get_path_key(path: PrintItemPath) -> *const PrintNodeCell {
+  path as *const PrintNodeCell
+}
+
+fn write_line(text: &mut String, indent: usize, line: &str) {
+  for _ in 0..indent {
+    text.push_str("  ");
+  }
+  writeln!(text, "{}", line).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn dumps_print_items() {
+    let mut dump = String::new();
+    format(
+      || {
+        let shared_path = {
+          let mut items = PrintItems::new();
+          items.push_str_runtime_width_computed("shared");
+          items.push_signal(Signal::NewLine);
+          items.into_rc_path()
+        };
+        let line_number = LineNumber::new("start");
+        let mut items = PrintItems::new();
+        items.push_info(line_number);
+        items.push_str_runtime_width_computed("a\"b");
+        items.push_condition(conditions::if_true_or("is_multi_line", std::rc::Rc::new(|_| Some(true)), shared_path.into(), {
+          let mut items = PrintItems::new();
+          items.push_signal(Signal::SpaceOrNewLine);
+          items.push_source_position(3);
+          items
+        }));
+        items.push_optional_path(shared_path);
+        items.push_anchor(LineNumberAnchor::new(line_number));
+        dump = items.get_ir_dump();
+        items
+      },
+      test_helpers::get_print_options(40),
+    );
+    assert_eq!(
+      dump,
+      concat!(
+        "info: start (line number)\n",
+        "\"a\\\"b\"\n",
+        "condition: is_multi_line\n",
+        "  true:\n",
+        "    path #1:\n",
+        "      \"shared\"\n",
+        "      signal: NewLine\n",
+        "  false:\n",
+        "    signal: SpaceOrNewLine\n",
+        "    source position: 3\n",
+        "path #1 (repeated)\n",
+        "anchor: start (line number)\n",
+      )
+    );
+  }
+}
@@ -0,0 +1,403 @@
+use super::super::print_items::*;
+use super::helpers::*;
+
+/// The kind of line comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCommentKind {
+  /// `// comment`
+  DoubleSlash,
+  /// `# comment`
+  Hash,
+  /// `-- comment`
+  DoubleDash,
+  /// `; comment`
+  Semicolon,
+}
+
+impl LineCommentKind {
+  /// Gets the text that starts the comment.
+  pub fn token(&self) -> &'static str {
+    match self {
+      LineCommentKind::DoubleSlash => "//",
+      LineCommentKind::Hash => "#",
+      LineCommentKind::DoubleDash => "--",
+      LineCommentKind::Semicolon => ";",
+    }
+  }
+}
+
+/// Generates IR from consecutive line comments (ex. `// some text\n// more text`) with
+/// the comment text reflowed to fit within the provided width.
+///
+/// The width includes the comment characters, but not the indentation, so it can be
+/// used for a `commentLineWidth`-style configuration option. Extra comment characters
+/// (ex. `///`, `//!` or `##`) are kept and consecutive lines that use different comment
+/// characters are reflowed separately. Doc comments (`///` and `//!`) are reflowed as
+/// markdown. See `reflow_comment_text` for what's preserved. Lines that aren't a comment
+/// of the provided kind are kept as is.
+pub fn gen_line_comments_reflowed(text: &str, kind: LineCommentKind, max_width: u32) -> PrintItems {
+  enum Run<'a> {
+    Comment(&'a str, Vec<&'a str>),
+    Other(&'a str),
+  }
+
+  let token = kind.token();
+  let mut runs: Vec<Run> = Vec::new();
+  for line in text.lines() {
+    let line = line.trim_start();
+    let Some(after_token) = line.strip_prefix(token) else {
+      runs.push(Run::Other(line));
+      continue;
+    };
+    let token_char = token.chars().next().unwrap();
+    let extra_len = after_token.len() - after_token.trim_start_matches([token_char, '!']).len();
+    let prefix = &line[..token.len() + extra_len];
+    let content = &after_token[extra_len..];
+    let content = content.strip_prefix(' ').unwrap_or(content);
+    match runs.last_mut() {
+      Some(Run::Comment(run_prefix, lines)) if *run_prefix == prefix => lines.push(content),
+      _ => runs.push(Run::Comment(prefix, vec![content])),
+    }
+  }
+
+  let mut items = PrintItems::new();
+  for run in runs {
+    let lines = match run {
+      Run::Comment(prefix, lines) => {
+        let is_doc_comment = kind == LineCommentKind::DoubleSlash && matches!(prefix, "///" | "//!");
+        let content_width = max_width.saturating_sub(get_text_width(prefix) + 1);
+        let mut reflowed_lines = reflow_comment_text(&lines.join("\n"), content_width, is_doc_comment);
+        // the joined text loses a trailing blank line
+        if lines.last() == Some(&"") {
+          reflowed_lines.push(String::new());
+        }
+        reflowed_lines.into_iter().map(|line| gen_comment_line(prefix, &line)).collect::<Vec<_>>()
+      }
+      Run::Other(line) => vec![with_no_new_lines(gen_from_raw_string(line))],
+    };
+    for line_items in lines {
+      if !items.is_empty() {
+        items.push_signal(Signal::NewLine);
+      }
+      items.extend(line_items);
+    }
+  }
+  items.push_signal(Signal::ExpectNewLine);
+  items
+}
+
+/// Generates IR from the text of a JS-like comment block (ex. `/** some text */`) with
+/// the comment text reflowed to fit within the provided width.
+///
+/// This only reflows doc comments (`/** */`), which are reflowed as markdown, and
+/// blocks where every line starts with a `*`. Other blocks are generated as is
+/// with `gen_js_like_comment_block`.
+pub fn gen_js_like_comment_block_reflowed(text: &str, max_width: u32) -> PrintItems {
+  let is_doc = text.starts_with('*') && text.len() > 1;
+  let is_multi_line = text.contains('\n');
+  let is_star_prefixed = is_multi_line && text.lines().skip(1).all(|line| line.trim().is_empty() || line.trim_start().starts_with('*'));
+  if !is_doc && !is_star_prefixed {
+    return gen_js_like_comment_block(text);
+  }
+
+  let opening = if is_doc { "/**" } else { "/*" };
+  let content_text = if is_doc { &text[1..] } else { text };
+  let mut content_lines = content_text
+    .lines()
+    .enumerate()
+    .map(|(i, line)| {
+      let line = if i == 0 {
+        line
+      } else {
+        let line = line.trim_start();
+        line.strip_prefix('*').unwrap_or(line)
+      };
+      let line = line.strip_prefix(' ').unwrap_or(line);
+      line.trim_end()
+    })
+    .collect::<Vec<_>>();
+  while content_lines.first().is_some_and(|line| line.is_empty()) {
+    content_lines.remove(0);
+  }
+  while content_lines.last().is_some_and(|line| line.is_empty()) {
+    content_lines.pop();
+  }
+
+  let lines = reflow_comment_text(&content_lines.join("\n"), max_width.saturating_sub(3), is_doc);
+  let mut items = PrintItems::new();
+  if !is_multi_line && lines.len() == 1 && get_text_width(&lines[0]) + get_text_width(opening) + 4 <= max_width {
+    items.extend(gen_from_raw_string(&format!("{} {} */", opening, lines[0])));
+    return items;
+  }
+
+  items.extend(gen_from_raw_string(opening));
+  for line in lines {
+    items.push_signal(Signal::NewLine);
+    items.extend(gen_comment_line(" *", &line));
+  }
+  items.push_signal(Signal::NewLine);
+  items.extend(gen_from_raw_string(" */"));
+  items
+}
+
+fn gen_comment_line(prefix: &str, line: &str) -> PrintItems {
+  with_no_new_lines(gen_from_raw_string(&if line.is_empty() {
+    prefix.to_string()
+  } else {
+    format!("{} {}", prefix, line)
+  }))
+}
+
+/// Reflows the text of a comment (without the comment characters) so each line
+/// fits within the provided width where possible.
+///
+/// Consecutive lines are joined into paragraphs and filled up to the width. The following
+/// are preserved:
+///
+/// * Blank lines.
+/// * Indented lines (ex. code).
+/// * List items (ex. `- item` or `1. item`), which get their continuation lines indented.
+///
+/// Doc comments (`is_doc_comment`) are treated as markdown with JSDoc tags, so the
+/// following are also preserved:
+///
+/// * Code fences (` ``` ` and `~~~`).
+/// * Headings, block quotes, tables and html (lines starting with `#`, `>`, `|` or `<`).
+/// * JSDoc tags (lines starting with `@`), which each start a new paragraph. The lines
+///   following an `@example` tag are kept as is.
+///
+/// Words longer than the width are not broken up.
+pub fn reflow_comment_text(text: &str, max_width: u32, is_doc_comment: bool) -> Vec<String> {
+  let mut lines = Vec::new();
+  let mut paragraph: Option<Paragraph> = None;
+  let mut fence: Option<&str> = None;
+  let mut is_in_example = false;
+  let is_fence_line = |trimmed_line: &str| is_doc_comment && (trimmed_line.starts_with("```") || trimmed_line.starts_with("~~~"));
+  let is_tag_line = |trimmed_line: &str| is_doc_comment && trimmed_line.starts_with('@');
+  let is_preserved_line = |trimmed_line: &str| is_doc_comment && trimmed_line.starts_with(['#', '>', '|', '<']);
+
+  for line in text.lines() {
+    let line = line.trim_end();
+    let trimmed_line = line.trim_start();
+    let indent = &line[..line.len() - trimmed_line.len()];
+
+    if let Some(fence_text) = fence {
+      lines.push(line.to_string());
+      if trimmed_line.starts_with(fence_text) {
+        fence = None;
+      }
+      continue;
+    }
+
+    let is_paragraph_continuation = paragraph.is_some()
+      && !trimmed_line.is_empty()
+      && !is_tag_line(trimmed_line)
+      && !is_in_example
+      && !is_fence_line(trimmed_line)
+      && !is_preserved_line(trimmed_line)
+      && get_list_marker(trimmed_line).is_none();
+    if is_paragraph_continuation {
+      paragraph.as_mut().unwrap().words.extend(trimmed_line.split_whitespace());
+      continue;
+    }
+    if let Some(paragraph) = paragraph.take() {
+      paragraph.reflow(max_width, &mut lines);
+    }
+
+    if is_fence_line(trimmed_line) {
+      fence = Some(&trimmed_line[..3]);
+      lines.push(line.to_string());
+    } else if is_tag_line(trimmed_line) {
+      is_in_example = trimmed_line.starts_with("@example");
+      if is_in_example {
+        lines.push(line.to_string());
+      } else {
+        paragraph = Some(Paragraph::new(indent.to_string(), indent.to_string(), trimmed_line));
+      }
+    } else if is_in_example || trimmed_line.is_empty() || indent.len() >= 4 || is_preserved_line(trimmed_line) {
+      lines.push(line.to_string());
+    } else if let Some(marker) = get_list_marker(trimmed_line) {
+      let first_line_prefix = format!("{}{} ", indent, marker);
+      let hanging_indent = " ".repeat(first_line_prefix.len());
+      paragraph = Some(Paragraph::new(first_line_prefix, hanging_indent, &trimmed_line[marker.len()..]));
+    } else {
+      paragraph = Some(Paragraph::new(indent.to_string(), indent.to_string(), trimmed_line));
+    }
+  }
+  if let Some(paragraph) = paragraph {
+    paragraph.reflow(max_width, &mut lines);
+  }
+
+  lines
+}
+
+struct Paragraph<'a> {
+  first_line_prefix: String,
+  hanging_indent: String,
+  words: Vec<&'a str>,
+}
+
+impl<'a> Paragraph<'a> {
+  fn new(first_line_prefix: String, hanging_indent: String, text: &'a str) -> Self {
+    Self {
+      first_line_prefix,
+      hanging_indent,
+      words: text.split_whitespace().collect(),
+    }
+  }
+
+  fn reflow(self, max_width: u32, lines: &mut Vec<String>) {
+    let mut line = self.first_line_prefix;
+    let mut line_width = get_text_width(&line);
+    let mut has_word = false;
+    for word in self.words {
+      let word_width = get_text_width(word);
+      if has_word && line_width + 1 + word_width > max_width {
+        lines.push(std::mem::replace(&mut line, self.hanging_indent.clone()));
+        line_width = get_text_width(&line);
+        has_word = false;
+      }
+      if has_word {
+        line.push(' ');
+        line_width += 1;
+      }
+      line.push_str(word);
+      line_width += word_width;
+      has_word = true;
+    }
+    lines.push(line);
+  }
+}
+
+/// Gets the list marker (ex. `-` or `1.`) when the line is a markdown list item.
+fn get_list_marker(trimmed_line: &str) -> Option<&str> {
+  let marker_len = if trimmed_line.starts_with(['-', '*', '+']) {
+    1
+  } else {
+    let digit_count = trimmed_line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digit_count == 0 || !trimmed_line[digit_count..].starts_with(['.', ')']) {
+      return None;
+    }
+    digit_count + 1
+  };
+  if trimmed_line[marker_len..].starts_with(' ') {
+    Some(&trimmed_line[..marker_len])
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::formatting::format;
+  use crate::formatting::test_helpers::get_print_options;
+
+  #[test]
+  fn reflows_paragraphs() {
+    assert_eq!(
+      reflow_comment_text("Lorem ipsum dolor sit amet,\nconsectetur adipiscing elit.\n\nSed do eiusmod.", 20, false),
+      vec!["Lorem ipsum dolor", "sit amet,", "consectetur", "adipiscing elit.", "", "Sed do eiusmod."]
+    );
+  }
+
+  #[test]
+  fn preserves_markdown() {
+    let text = concat!(
+      "Some text:\n",
+      "- first item that is long\n",
+      "  and continues\n",
+      "10. numbered\n",
+      "```rs\n",
+      "let   a = 5;\n",
+      "```\n",
+      "    indented code\n",
+      "# Heading\n",
+      "| a | b |\n",
+    );
+    assert_eq!(
+      reflow_comment_text(text, 16, true),
+      vec![
+        "Some text:",
+        "- first item",
+        "  that is long",
+        "  and continues",
+        "10. numbered",
+        "```rs",
+        "let   a = 5;",
+        "```",
+        "    indented code",
+        "# Heading",
+        "| a | b |",
+      ]
+    );
+  }
+
+  #[test]
+  fn preserves_jsdoc_tags() {
+    let text = "Description.\n@param a - The value\nthat's used.\n@example\nfoo(  1  );\n@returns The result.";
+    assert_eq!(
+      reflow_comment_text(text, 80, true),
+      vec![
+        "Description.",
+        "@param a - The value that's used.",
+        "@example",
+        "foo(  1  );",
+        "@returns The result."
+      ]
+    );
+  }
+
+  #[test]
+  fn only_preserves_markdown_in_doc_comments() {
+    let text = "Some text\n# not a heading\n<not html>\n@not_a_tag\n```\nnot a fence\n- item";
+    assert_eq!(
+      reflow_comment_text(text, 80, false),
+      vec!["Some text # not a heading <not html> @not_a_tag ``` not a fence", "- item"]
+    );
+  }
+
+  #[test]
+  fn gens_line_comments() {
+    let text = "// Lorem ipsum dolor sit amet,\n//   consectetur adipiscing\n//\n# not a comment\n/// Doc comment that is long";
+    assert_eq!(
+      format_items(|| gen_line_comments_reflowed(text, LineCommentKind::DoubleSlash, 20)),
+      "// Lorem ipsum dolor\n// sit amet,\n// consectetur\n// adipiscing\n//\n# not a comment\n/// Doc comment that\n/// is long"
+    );
+    assert_eq!(
+      format_items(|| gen_line_comments_reflowed("-- a b c\n--d", LineCommentKind::DoubleDash, 7)),
+      "-- a b\n-- c d"
+    );
+    assert_eq!(
+      format_items(|| gen_line_comments_reflowed(";; a b c", LineCommentKind::Semicolon, 80)),
+      ";; a b c"
+    );
+    assert_eq!(
+      format_items(|| gen_line_comments_reflowed("# a\n# <b> c\n# # d", LineCommentKind::Hash, 80)),
+      "# a <b> c # d"
+    );
+  }
+
+  #[test]
+  fn gens_comment_blocks() {
+    assert_eq!(format_items(|| gen_js_like_comment_block_reflowed("* Some text. ", 40)), "/** Some text. */");
+    assert_eq!(
+      format_items(|| gen_js_like_comment_block_reflowed("* Some text that is long. ", 20)),
+      "/**\n * Some text that is\n * long.\n */"
+    );
+    assert_eq!(
+      format_items(|| gen_js_like_comment_block_reflowed("*\n * Some\n * text.\n *\n * @param a\n ", 80)),
+      "/**\n * Some text.\n *\n * @param a\n */"
+    );
+    assert_eq!(
+      format_items(|| gen_js_like_comment_block_reflowed("\n * a b\n * c\n ", 80)),
+      "/*\n * a b c\n */"
+    );
+    // not reflowed
+    assert_eq!(format_items(|| gen_js_like_comment_block_reflowed(" a b\n c ", 2)), "/* a b\n c */");
+  }
+
+  fn format_items(get_items: impl FnOnce() -> PrintItems) -> String {
+    format(get_items, get_print_options(10))
+  }
+}
@@ -74,6 +74,35 @@ pub fn new_line_group(item: PrintItems) -> PrintItems {
   items
 }
 
+/// Fills the items onto each line up to the max width and moves the item
+/// that doesn't fit to the next line (ex. words in a paragraph).
+///
+/// This is `Signal::SpaceOrNewLine` between the items with each item in a
+/// `new_line_group` so the printer prefers breaking between the items rather
+/// than within them. It doesn't add any conditions, but like any possible
+/// newline, the printer may go back and print the text after a break again
+/// when a later item doesn't fit on the line.
+///
+/// Note: Any separator text (ex. a comma) should be included in the item.
+pub fn gen_fill(fill_items: impl IntoIterator<Item = PrintItems>) -> PrintItems {
+  let mut items = PrintItems::new();
+  for item in fill_items {
+    if item.is_empty() {
+      continue;
+    }
+    if !items.is_empty() {
+      items.push_signal(Signal::SpaceOrNewLine);
+    }
+    items.extend(new_line_group(item));
+  }
+  items
+}
+
+/// Fills the whitespace separated words of the text onto each line up to the max width.
+pub fn gen_fill_words(text: &str) -> PrintItems {
+  gen_fill(text.split_whitespace().map(gen_from_string_line))
+}
+
 /// Generates IR from a string as is and ignores its indent.
 pub fn gen_from_raw_string(text: &str) -> PrintItems {
   gen_from_raw_string_lines(text, gen_from_string_line)
@@ -277,3 +306,38 @@ pub fn text_has_dprint_ignore(text: &str, searching_text: &str) -> bool {
     false
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::formatting::format;
+  use crate::formatting::test_helpers::get_print_options;
+
+  #[test]
+  fn fills_words() {
+    let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor.";
+    assert_eq!(
+      format(|| gen_fill_words(text), get_print_options(20)),
+      "Lorem ipsum dolor\nsit amet,\nconsectetur\nadipiscing elit, sed\ndo eiusmod tempor."
+    );
+    // words longer than the line stay on their own line
+    assert_eq!(format(|| gen_fill_words("a bbbbbbbb c d"), get_print_options(5)), "a\nbbbbbbbb\nc d");
+  }
+
+  #[test]
+  fn fill_prefers_breaking_between_items() {
+    let get_items = || {
+      gen_fill(["aa", "bb cc", "dd"].into_iter().map(|text| {
+        let mut items = PrintItems::new();
+        for (i, word) in text.split(' ').enumerate() {
+          if i > 0 {
+            items.push_signal(Signal::SpaceOrNewLine);
+          }
+          items.push_string(word.to_string());
+        }
+        items
+      }))
+    };
+    assert_eq!(format(get_items, get_print_options(6)), "aa\nbb cc\ndd");
+  }
+}
@@ -1,5 +1,7 @@
+mod gen_comments;
 mod gen_separated_values;
 mod helpers;
 
+pub use gen_comments::*;
 pub use gen_separated_values::*;
 pub use helpers::*;
@@ -6,9 +6,14 @@ pub mod ir_helpers;
 
 mod collections;
 mod infinite_reevaluation_protection;
+#[cfg(debug_assertions)]
+mod ir_dump;
 mod print;
 mod print_items;
 mod printer;
+mod source_map;
+#[cfg(test)]
+mod test_helpers;
 mod thread_state;
 #[cfg(feature = "tracing")]
 mod tracing;
@@ -18,15 +23,24 @@ mod writer;
 pub mod tokens;
 pub mod utils;
 
+pub use crate::configuration::WhitespaceOptions;
 pub use print::format;
+pub use print::format_parallel;
+pub use print::format_with_result;
+pub use print::format_with_source_map;
+pub use print::get_arena_allocated_bytes;
 pub use print::print;
+pub use print::set_arena_retention_limit;
 #[cfg(feature = "tracing")]
 pub use print::trace_printing;
+pub use print::FormatResult;
 pub use print::PrintOptions;
+pub use print::TextWidthOptions;
 #[cfg(feature = "tracing")]
 pub use print::TracingResult;
 pub use print_items::*;
 use printer::*;
+pub use source_map::*;
 #[cfg(feature = "tracing")]
 use tracing::*;
 use write_items::*;
@@ -5,6 +5,11 @@ use self::thread_state::BumpAllocator;
 use super::*;
 
 /// Options for printing the print items.
+///
+/// Create this with `PrintOptions::new` and then the `with_*` methods,
+/// since new options may be added in the future.
+#[derive(Clone)]
+#[non_exhaustive]
 pub struct PrintOptions {
   /// The width the printer will attempt to keep the line under.
   pub max_width: u32,
@@ -14,36 +19,251 @@ pub struct PrintOptions {
   pub use_tabs: bool,
   /// The newline character to use when doing a new line.
   pub new_line_text: &'static str,
+  /// How to measure the width of text and tabs.
+  pub text_width: TextWidthOptions,
+  /// Whitespace policies to enforce on the printed text.
+  pub whitespace: WhitespaceOptions,
+  /// The maximum number of condition evaluations and look-ahead restores to do
+  /// before falling back to printing without looking ahead. No limit when `None`.
+  ///
+  /// The fallback only moves forward and breaks lines at possible newlines once
+  /// they're already too long, so the output is worse, but it won't take minutes on
+  /// pathological input (ex. minified code). Use `format_with_result` to find
+  /// out if this happened.
+  pub work_budget: Option<u64>,
+}
+
+impl Default for PrintOptions {
+  fn default() -> Self {
+    Self {
+      max_width: 120,
+      indent_width: 2,
+      use_tabs: false,
+      new_line_text: "\n",
+      text_width: Default::default(),
+      whitespace: Default::default(),
+      work_budget: None,
+    }
+  }
+}
+
+/// Options for measuring the width of printed text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextWidthOptions {
+  /// Count characters with an East Asian Width of "Ambiguous" (ex. `“`, `→`, `①`)
+  /// as two columns wide, which is how they're displayed in East Asian contexts.
+  pub ambiguous_as_wide: bool,
+  /// Count a tab as the columns up to the next tab stop (a multiple of the indent
+  /// width) instead of always counting it as the indent width.
+  pub expand_tabs: bool,
 }
 
 impl PrintOptions {
+  /// Creates the options with the defaults for any other options.
+  pub fn new(max_width: u32, indent_width: u8, use_tabs: bool, new_line_text: &'static str) -> Self {
+    Self {
+      max_width,
+      indent_width,
+      use_tabs,
+      new_line_text,
+      ..Default::default()
+    }
+  }
+
+  pub fn with_text_width(self, text_width: TextWidthOptions) -> Self {
+    Self { text_width, ..self }
+  }
+
+  pub fn with_whitespace(self, whitespace: WhitespaceOptions) -> Self {
+    Self { whitespace, ..self }
+  }
+
+  pub fn with_work_budget(self, work_budget: Option<u64>) -> Self {
+    Self { work_budget, ..self }
+  }
+
   pub(super) fn to_printer_options(&self) -> PrinterOptions {
     PrinterOptions {
       indent_width: self.indent_width,
       max_width: self.max_width,
+      expand_tabs: self.text_width.expand_tabs,
+      work_budget: self.work_budget,
       #[cfg(feature = "tracing")]
       enable_tracing: false,
     }
   }
 }
 
+/// The printed text along with information about how it was printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatResult {
+  pub text: String,
+  /// If the `work_budget` of the `PrintOptions` was exceeded and the
+  /// printer fell back to printing without looking ahead.
+  pub exceeded_work_budget: bool,
+}
+
 /// Function to create the provided print items and print them out as a string.
 ///
 /// Note: It is unsafe to use the print items created within `get_print_items`
 /// outside of the closure, since they are created with a thread local allocator
 /// that is reset once this function returns.
 pub fn format(get_print_items: impl FnOnce() -> PrintItems, options: PrintOptions) -> String {
+  format_with_result(get_print_items, options).text
+}
+
+/// Function to create the provided print items and print them out as a string
+/// along with whether the work budget was exceeded.
+///
+/// Note: It is unsafe to use the print items created within `get_print_items`
+/// outside of the closure, since they are created with a thread local allocator
+/// that is reset once this function returns.
+pub fn format_with_result(get_print_items: impl FnOnce() -> PrintItems, options: PrintOptions) -> FormatResult {
+  format_with(get_print_items, options, print_with_allocator)
+}
+
+/// Function to create the provided print items and print them out as a string
+/// along with a source map of the positions pushed with `PrintItems::push_source_position`.
+///
+/// Note: It is unsafe to use the print items created within `get_print_items`
+/// outside of the closure, since they are created with a thread local allocator
+/// that is reset once this function returns.
+pub fn format_with_source_map(get_print_items: impl FnOnce() -> PrintItems, options: PrintOptions) -> (String, SourceMap) {
+  format_with(get_print_items, options, print_with_allocator_and_source_map)
+}
+
+/// Formats independent segments of a file in parallel, then joins them with newlines.
+///
+/// This is useful for very large files where the top level statements don't affect
+/// each other's layout (ex. they're separated by hard newlines at indent level 0).
+/// The print items of each segment are created and printed on a worker thread, since
+/// print items can't be shared between threads, so `gen_segment` must be `Sync`.
+/// At most `max_threads` threads are used, including the current one.
+///
+/// The output is the same as formatting all the segments sequentially with a
+/// `Signal::NewLine` between each one, as long as each segment starts and ends at
+/// indent level 0 and none of its infos or conditions depend on another segment.
+pub fn format_parallel<T: Sync>(segments: &[T], gen_segment: impl Fn(&T) -> PrintItems + Sync, options: PrintOptions, max_threads: usize) -> FormatResult {
+  let chunk_size = segments.len().div_ceil(max_threads.max(1)).max(1);
+  // the final newline is only for the end of the text
+  let segment_options = PrintOptions {
+    whitespace: WhitespaceOptions {
+      insert_final_new_line: false,
+      ..options.whitespace
+    },
+    ..options.clone()
+  };
+  let format_chunk = |chunk: &[T]| {
+    let mut exceeded_work_budget = false;
+    let texts = chunk
+      .iter()
+      .map(|segment| {
+        let result = format_with(|| gen_segment(segment), segment_options.clone(), print_with_allocator);
+        exceeded_work_budget |= result.exceeded_work_budget;
+        result.text
+      })
+      .collect::<Vec<_>>();
+    (texts, exceeded_work_budget)
+  };
+
+  let mut chunks = segments.chunks(chunk_size);
+  let (segment_texts, exceeded_work_budget) = match chunks.next() {
+    Some(first_chunk) => std::thread::scope(|scope| {
+      let handles = chunks.map(|chunk| scope.spawn(|| format_chunk(chunk))).collect::<Vec<_>>();
+      // use this thread for the first chunk
+      let (mut segment_texts, mut exceeded_work_budget) = format_chunk(first_chunk);
+      for handle in handles {
+        match handle.join() {
+          Ok((texts, chunk_exceeded_work_budget)) => {
+            segment_texts.extend(texts);
+            exceeded_work_budget |= chunk_exceeded_work_budget;
+          }
+          Err(err) => std::panic::resume_unwind(err),
+        }
+      }
+      (segment_texts, exceeded_work_budget)
+    }),
+    None => (Vec::new(), false),
+  };
+
+  FormatResult {
+    text: join_segment_texts(segment_texts, &options),
+    exceeded_work_budget,
+  }
+}
+
+/// Joins the printed segments with newlines, applying the whitespace policies
+/// that span the joins and the end of the text.
+fn join_segment_texts(segment_texts: Vec<String>, options: &PrintOptions) -> String {
+  let newline = options.new_line_text;
+  let mut text = String::with_capacity(segment_texts.iter().map(|text| text.len() + newline.len()).sum());
+  for (i, segment_text) in segment_texts.iter().enumerate() {
+    let mut segment_text = segment_text.as_str();
+    if i > 0 {
+      match options.whitespace.max_consecutive_blank_lines {
+        Some(max) => {
+          // limit the newlines surrounding the join like printing sequentially would
+          let (text_without_newlines, trailing_count) = strip_newlines_end(&text, newline);
+          let text_len = text_without_newlines.len();
+          let leading_count = count_newlines_start(segment_text, newline);
+          segment_text = &segment_text[leading_count * newline.len()..];
+          let max_newlines = if text_len == 0 { max } else { max + 1 } as usize;
+          text.truncate(text_len);
+          text.push_str(&newline.repeat((trailing_count + 1 + leading_count).min(max_newlines)));
+        }
+        None => text.push_str(newline),
+      }
+    }
+    text.push_str(segment_text);
+  }
+  if options.whitespace.insert_final_new_line {
+    let len = strip_newlines_end(&text, newline).0.len();
+    text.truncate(len);
+    if !text.is_empty() {
+      text.push_str(newline);
+    }
+  }
+  text
+}
+
+fn strip_newlines_end<'a>(text: &'a str, newline: &str) -> (&'a str, usize) {
+  let mut text = text;
+  let mut count = 0;
+  while let Some(remaining) = text.strip_suffix(newline) {
+    text = remaining;
+    count += 1;
+  }
+  (text, count)
+}
+
+fn count_newlines_start(text: &str, newline: &str) -> usize {
+  let mut text = text;
+  let mut count = 0;
+  while let Some(remaining) = text.strip_prefix(newline) {
+    text = remaining;
+    count += 1;
+  }
+  count
+}
+
+fn format_with<T>(
+  get_print_items: impl FnOnce() -> PrintItems,
+  options: PrintOptions,
+  print: impl FnOnce(&mut BumpAllocator, &PrintItems, &PrintOptions) -> T,
+) -> T {
   increment_formatting_count();
   let old_counts = thread_state::take_counts();
+  let old_text_width_options = thread_state::set_text_width_options(options.text_width);
   let print_items = get_print_items();
 
   let result = thread_state::with_bump_allocator(|bump| {
-    let result = print_with_allocator(bump, &print_items, &options);
+    let result = print(bump, &print_items, &options);
     if decrement_formatting_count() {
       bump.reset();
     }
     result
   });
+  thread_state::set_text_width_options(old_text_width_options);
   thread_state::set_counts(old_counts);
   result
 }
@@ -60,16 +280,44 @@ pub fn print(print_items: PrintItems, options: PrintOptions) -> String {
   let old_counts = thread_state::take_counts();
   let result = thread_state::with_bump_allocator(|bump| print_with_allocator(bump, &print_items, &options));
   thread_state::set_counts(old_counts);
-  result
+  result.text
+}
+
+fn print_with_allocator(bump: &mut BumpAllocator, print_items: &PrintItems, options: &PrintOptions) -> FormatResult {
+  let result = Printer::new(bump, print_items.first_node, options.to_printer_options()).print();
+  FormatResult {
+    text: match result.write_items {
+      Some(write_items) => WriteItemsPrinter::from(options).print(write_items),
+      None => String::new(),
+    },
+    exceeded_work_budget: result.exceeded_work_budget,
+  }
 }
 
-fn print_with_allocator(bump: &mut BumpAllocator, print_items: &PrintItems, options: &PrintOptions) -> String {
-  match Printer::new(bump, print_items.first_node, options.to_printer_options()).print() {
-    Some(write_items) => WriteItemsPrinter::from(options).print(write_items),
-    None => String::new(),
+fn print_with_allocator_and_source_map(bump: &mut BumpAllocator, print_items: &PrintItems, options: &PrintOptions) -> (String, SourceMap) {
+  match Printer::new(bump, print_items.first_node, options.to_printer_options()).print().write_items {
+    Some(write_items) => WriteItemsPrinter::from(options).print_with_source_map(write_items),
+    None => (String::new(), SourceMap::default()),
   }
 }
 
+/// Sets the maximum number of bytes each thread's print item arena keeps
+/// allocated for reuse once formatting finishes.
+///
+/// The print items are allocated in a thread local arena that's reset and reused
+/// after each format, which is fast for long-lived plugins, but formatting a huge
+/// file will keep all that memory around. When above the limit, the arena's
+/// memory is released instead. This applies to every thread, including ones
+/// that are already running. Defaults to no limit.
+pub fn set_arena_retention_limit(max_bytes: Option<usize>) {
+  thread_state::set_arena_retention_limit(max_bytes);
+}
+
+/// Gets the number of bytes allocated by the current thread's print item arena.
+pub fn get_arena_allocated_bytes() -> usize {
+  thread_state::with_bump_allocator(|bump| bump.allocated_bytes())
+}
+
 #[cfg(feature = "tracing")]
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -85,7 +333,9 @@ pub fn trace_printing(get_print_items: impl FnOnce() -> PrintItems, options: Pri
   use std::iter;
 
   increment_formatting_count();
+  let old_text_width_options = thread_state::set_text_width_options(options.text_width);
   let print_items = get_print_items();
+  thread_state::set_text_width_options(old_text_width_options);
 
   thread_state::with_bump_allocator(|bump| {
     let tracing_result = Printer::new(bump, print_items.first_node, {
@@ -94,7 +344,8 @@ pub fn trace_printing(get_print_items: impl FnOnce() -> PrintItems, options: Pri
       printer_options
     })
     .print_for_tracing();
-    let writer_items_printer = WriteItemsPrinter::from(&options);
+    // the whitespace policies only apply to the whole text
+    let writer_items_printer = WriteItemsPrinter::new(options.indent_width, options.use_tabs, options.new_line_text);
 
     let result = TracingResult {
       traces: tracing_result.traces,
@@ -149,11 +400,23 @@ fn panic_if_not_formatting() {
 
 #[cfg(test)]
 mod test {
+  use crate::formatting::ir_helpers;
+  use crate::formatting::test_helpers;
   use crate::formatting::LineNumber;
 
+  use super::super::Condition;
   use super::super::PrintItems;
+  use super::super::Signal;
+  use super::super::SourceMapping;
   use super::format;
+  use super::format_parallel;
+  use super::format_with_result;
+  use super::format_with_source_map;
+  use super::get_arena_allocated_bytes;
+  use super::set_arena_retention_limit;
   use super::PrintOptions;
+  use super::TextWidthOptions;
+  use super::WhitespaceOptions;
 
   #[test]
   fn test_format_in_format() {
@@ -187,12 +450,474 @@ mod test {
     );
   }
 
-  fn get_print_options() -> PrintOptions {
-    PrintOptions {
-      max_width: 40,
-      indent_width: 2,
-      use_tabs: false,
-      new_line_text: "\n",
+  #[test]
+  fn test_format_with_source_map() {
+    // original text: "let  a=\n{b}"
+    let (text, source_map) = format_with_source_map(
+      || {
+        let mut items = PrintItems::new();
+        items.push_source_position(0);
+        items.push_str_runtime_width_computed("let");
+        items.push_signal(Signal::SpaceIfNotTrailing);
+        items.push_source_position(5);
+        items.push_signal(Signal::NewLine);
+        items.push_signal(Signal::StartIndent);
+        items.push_source_position(8);
+        items.push_str_runtime_width_computed("{");
+        items.push_signal(Signal::NewLine);
+        items.push_source_position(9);
+        items.push_str_runtime_width_computed("b");
+        items.push_signal(Signal::FinishIndent);
+        items.push_signal(Signal::NewLine);
+        items.push_source_position(10);
+        items.push_str_runtime_width_computed("}");
+        items
+      },
+      get_print_options(),
+    );
+    assert_eq!(text, "let\n  {\n  b\n}");
+    assert_eq!(
+      source_map.mappings(),
+      &[
+        SourceMapping { original: 0, formatted: 0 },
+        // the trailing space was removed
+        SourceMapping { original: 5, formatted: 3 },
+        // after the indentation
+        SourceMapping { original: 8, formatted: 6 },
+        SourceMapping { original: 9, formatted: 10 },
+        SourceMapping { original: 10, formatted: 12 },
+      ]
+    );
+    assert_eq!(source_map.get_formatted_position(9), 10);
+  }
+
+  #[test]
+  fn test_ambiguous_width() {
+    let get_items = || {
+      let mut items = PrintItems::new();
+      items.push_string("→→→".to_string());
+      items.push_signal(Signal::PossibleNewLine);
+      // emoji zwj sequence that's two columns wide
+      items.push_string("👨‍👩‍👧".to_string());
+      items
+    };
+    let get_options = |ambiguous_as_wide: bool| PrintOptions {
+      max_width: 5,
+      text_width: TextWidthOptions {
+        ambiguous_as_wide,
+        expand_tabs: false,
+      },
+      ..get_print_options()
+    };
+    assert_eq!(format(get_items, get_options(false)), "→→→👨‍👩‍👧");
+    assert_eq!(format(get_items, get_options(true)), "→→→\n👨‍👩‍👧");
+  }
+
+  #[test]
+  fn test_grapheme_width() {
+    let get_items = || {
+      let mut items = PrintItems::new();
+      items.push_string("abc".to_string());
+      items.push_signal(Signal::PossibleNewLine);
+      // malformed emoji modifier sequence that's a single grapheme cluster
+      items.push_string("😀🏽🏽".to_string());
+      items
+    };
+    let options = PrintOptions {
+      max_width: 5,
+      ..get_print_options()
+    };
+    assert_eq!(format(get_items, options), "abc😀🏽🏽");
+  }
+
+  #[test]
+  fn test_expand_tabs() {
+    let get_items = || {
+      let mut items = PrintItems::new();
+      items.push_str_runtime_width_computed("a");
+      items.push_signal(Signal::Tab);
+      items.push_signal(Signal::PossibleNewLine);
+      items.push_str_runtime_width_computed("bc");
+      items
+    };
+    let get_options = |expand_tabs: bool| PrintOptions {
+      max_width: 6,
+      indent_width: 4,
+      text_width: TextWidthOptions {
+        ambiguous_as_wide: false,
+        expand_tabs,
+      },
+      ..get_print_options()
+    };
+    assert_eq!(format(get_items, get_options(false)), "a\t\nbc");
+    assert_eq!(format(get_items, get_options(true)), "a\tbc");
+  }
+
+  #[test]
+  fn test_whitespace_options() {
+    let get_items = || {
+      let mut items = PrintItems::new();
+      items.push_signal(Signal::NewLine);
+      items.push_str_runtime_width_computed("a");
+      items.push_space();
+      items.push_signal(Signal::Tab);
+      items.push_signal(Signal::NewLine);
+      items.push_signal(Signal::NewLine);
+      items.push_str_runtime_width_computed("  ");
+      items.push_signal(Signal::NewLine);
+      items.push_signal(Signal::NewLine);
+      items.push_str_runtime_width_computed("b");
+      items.push_space();
+      items.push_signal(Signal::NewLine);
+      items.push_signal(Signal::NewLine);
+      items
+    };
+    let get_options = |whitespace: WhitespaceOptions| PrintOptions {
+      whitespace,
+      new_line_text: "\r\n",
+      ..get_print_options()
+    };
+    assert_eq!(format(get_items, get_options(Default::default())), "\r\na \t\r\n\r\n  \r\n\r\nb \r\n\r\n");
+    assert_eq!(
+      format(
+        get_items,
+        get_options(WhitespaceOptions {
+          trim_trailing_whitespace: true,
+          ..Default::default()
+        })
+      ),
+      "\r\na\r\n\r\n\r\n\r\nb\r\n\r\n"
+    );
+    assert_eq!(
+      format(
+        get_items,
+        get_options(WhitespaceOptions {
+          trim_trailing_whitespace: true,
+          insert_final_new_line: true,
+          max_consecutive_blank_lines: Some(1),
+        })
+      ),
+      "\r\na\r\n\r\nb\r\n"
+    );
+    assert_eq!(
+      format(
+        get_items,
+        get_options(WhitespaceOptions {
+          max_consecutive_blank_lines: Some(0),
+          ..Default::default()
+        })
+      ),
+      "a \t\r\n  \r\nb \r\n"
+    );
+    assert_eq!(
+      format(
+        PrintItems::new,
+        get_options(WhitespaceOptions {
+          insert_final_new_line: true,
+          ..Default::default()
+        })
+      ),
+      ""
+    );
+  }
+
+  #[test]
+  fn test_whitespace_options_keep_text_whitespace() {
+    let text = format(
+      || {
+        let mut items = PrintItems::new();
+        items.push_str_runtime_width_computed("hard break  ");
+        items.push_signal(Signal::NewLine);
+        items.push_str_runtime_width_computed("const a = `");
+        items.extend(ir_helpers::gen_from_raw_string("text \t\n\n\n\nend"));
+        items.push_str_runtime_width_computed("`;");
+        items.push_space();
+        items.push_signal(Signal::NewLine);
+        items.push_signal(Signal::NewLine);
+        items.push_signal(Signal::NewLine);
+        items.push_str_runtime_width_computed("b");
+        items
+      },
+      PrintOptions {
+        whitespace: WhitespaceOptions {
+          trim_trailing_whitespace: true,
+          insert_final_new_line: false,
+          max_consecutive_blank_lines: Some(1),
+        },
+        ..get_print_options()
+      },
+    );
+    assert_eq!(text, "hard break  \nconst a = `text \t\n\n\n\nend`;\n\nb");
+  }
+
+  #[test]
+  fn test_global_config_whitespace() {
+    let get_text = |whitespace: WhitespaceOptions| {
+      format(
+        || {
+          let mut items = PrintItems::new();
+          items.push_str_runtime_width_computed("a");
+          items.push_space();
+          items.push_signal(Signal::NewLine);
+          items.push_signal(Signal::NewLine);
+          items.push_signal(Signal::NewLine);
+          items.push_str_runtime_width_computed("b");
+          items
+        },
+        get_print_options().with_whitespace(whitespace),
+      )
+    };
+    assert_eq!(get_text(Default::default()), "a \n\n\nb");
+    let global_config = crate::configuration::GlobalConfiguration {
+      trim_trailing_whitespace: Some(true),
+      insert_final_new_line: Some(true),
+      max_consecutive_blank_lines: Some(1),
+      ..Default::default()
+    };
+    assert_eq!(get_text(WhitespaceOptions::from(&global_config)), "a\n\nb\n");
+  }
+
+  #[test]
+  fn test_whitespace_options_source_map() {
+    let (text, source_map) = format_with_source_map(
+      || {
+        let mut items = PrintItems::new();
+        items.push_str_runtime_width_computed("a");
+        items.push_space();
+        items.push_source_position(2);
+        items.push_space();
+        items.push_signal(Signal::NewLine);
+        items.push_signal(Signal::NewLine);
+        items.push_signal(Signal::NewLine);
+        items.push_source_position(6);
+        items.push_str_runtime_width_computed("b");
+        items.push_signal(Signal::NewLine);
+        items.push_source_position(8);
+        items
+      },
+      PrintOptions {
+        whitespace: WhitespaceOptions {
+          trim_trailing_whitespace: true,
+          insert_final_new_line: true,
+          max_consecutive_blank_lines: Some(1),
+        },
+        ..get_print_options()
+      },
+    );
+    assert_eq!(text, "a\n\nb\n");
+    assert_eq!(
+      source_map.mappings(),
+      &[
+        // moved back when the trailing whitespace was removed
+        SourceMapping { original: 2, formatted: 1 },
+        SourceMapping { original: 6, formatted: 3 },
+        SourceMapping { original: 8, formatted: 4 },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_arena_retention_limit() {
+    let format_large = || {
+      format(
+        || {
+          let mut items = PrintItems::new();
+          for i in 0..10_000 {
+            items.push_string(i.to_string());
+            items.push_signal(Signal::SpaceOrNewLine);
+          }
+          items
+        },
+        get_print_options(),
+      )
+    };
+    let max_bytes = 1024;
+    set_arena_retention_limit(Some(max_bytes));
+    format_large();
+    assert!(get_arena_allocated_bytes() <= max_bytes);
+    // applies to other threads
+    std::thread::spawn(move || {
+      format_large();
+      assert!(get_arena_allocated_bytes() <= max_bytes);
+    })
+    .join()
+    .unwrap();
+    set_arena_retention_limit(None);
+  }
+
+  #[test]
+  fn test_format_parallel() {
+    let segments = (0..500).collect::<Vec<u32>>();
+    let gen_segment = |i: &u32| {
+      let mut items = PrintItems::new();
+      for _ in 0..(i % 4) {
+        items.push_signal(Signal::NewLine);
+      }
+      items.push_string(format!("segment_{}", i));
+      items.push_signal(Signal::StartIndent);
+      for j in 0..(i % 7) {
+        items.push_signal(Signal::SpaceOrNewLine);
+        items.push_string(format!("value_{}", j));
+      }
+      items.push_signal(Signal::FinishIndent);
+      items.push_str_runtime_width_computed(" ");
+      items
+    };
+    let get_options = || PrintOptions {
+      whitespace: WhitespaceOptions {
+        trim_trailing_whitespace: true,
+        insert_final_new_line: true,
+        max_consecutive_blank_lines: Some(1),
+      },
+      ..get_print_options()
+    };
+    let sequential_text = format(
+      || {
+        let mut items = PrintItems::new();
+        for (i, segment) in segments.iter().enumerate() {
+          if i > 0 {
+            items.push_signal(Signal::NewLine);
+          }
+          items.extend(gen_segment(segment));
+        }
+        items
+      },
+      get_options(),
+    );
+    for max_threads in [1, 4] {
+      assert_eq!(format_parallel(&segments, gen_segment, get_options(), max_threads).text, sequential_text);
     }
+    assert!(sequential_text.contains("segment_6 value_0 value_1 value_2\n  value_3 value_4 value_5\n\nsegment_7\nsegment_8"));
+  }
+
+  #[test]
+  fn test_format_parallel_keeps_text_blank_lines() {
+    let segments = ["a", "`b\n\n\n\nc`", "d"];
+    let gen_segment = |text: &&str| {
+      let mut items = PrintItems::new();
+      items.push_signal(Signal::NewLine);
+      items.push_signal(Signal::NewLine);
+      items.extend(ir_helpers::gen_from_raw_string(text));
+      items.push_signal(Signal::NewLine);
+      items
+    };
+    let options = PrintOptions {
+      whitespace: WhitespaceOptions {
+        max_consecutive_blank_lines: Some(1),
+        ..Default::default()
+      },
+      ..get_print_options()
+    };
+    assert_eq!(format_parallel(&segments, gen_segment, options, 2).text, "\na\n\n`b\n\n\n\nc`\n\nd\n");
+  }
+
+  #[test]
+  fn test_work_budget() {
+    let get_items = || {
+      let mut items = PrintItems::new();
+      for i in 0..3 {
+        let mut inner_items = PrintItems::new();
+        inner_items.push_string(format!("value_{}", i));
+        for _ in 0..5 {
+          inner_items.push_signal(Signal::SpaceOrNewLine);
+          inner_items.push_str_runtime_width_computed("other_value");
+        }
+        items.push_str_runtime_width_computed("call(");
+        items.extend(ir_helpers::surround_with_newlines_indented_if_multi_line(inner_items, 2));
+        items.push_str_runtime_width_computed(");");
+        items.push_signal(Signal::NewLine);
+      }
+      items
+    };
+    let get_options = |work_budget: Option<u64>| PrintOptions {
+      work_budget,
+      ..get_print_options()
+    };
+    let result = format_with_result(get_items, get_options(None));
+    assert_eq!(
+      result.text,
+      concat!(
+        "call(\n  value_0 other_value other_value\n  other_value other_value other_value\n);\n",
+        "call(\n  value_1 other_value other_value\n  other_value other_value other_value\n);\n",
+        "call(\n  value_2 other_value other_value\n  other_value other_value other_value\n);\n",
+      )
+    );
+    assert!(!result.exceeded_work_budget);
+    // falls back to not going back to put the values on new lines
+    let result = format_with_result(get_items, get_options(Some(5)));
+    assert_eq!(
+      result.text,
+      concat!(
+        "call(value_0 other_value other_value\nother_value other_value other_value);\n",
+        "call(value_1 other_value other_value\nother_value other_value other_value);\n",
+        "call(value_2 other_value other_value\nother_value other_value other_value);\n",
+      )
+    );
+    assert!(result.exceeded_work_budget);
+
+    let segments = [0, 1, 2];
+    let gen_segment = |_: &u32| get_items();
+    assert!(!format_parallel(&segments, gen_segment, get_options(None), 2).exceeded_work_budget);
+    assert!(format_parallel(&segments, gen_segment, get_options(Some(5)), 2).exceeded_work_budget);
+  }
+
+  #[test]
+  fn test_work_budget_fallback_new_lines() {
+    let options = PrintOptions {
+      work_budget: Some(0),
+      ..get_print_options()
+    };
+    let format_over_budget = |gen_items: fn(&mut PrintItems)| {
+      let result = format_with_result(
+        || {
+          let mut items = PrintItems::new();
+          items.push_condition(Condition::new_true());
+          gen_items(&mut items);
+          items
+        },
+        options.clone(),
+      );
+      assert!(result.exceeded_work_budget);
+      result.text
+    };
+
+    // doesn't break in a deeper new line group once an outer possible newline was passed
+    assert_eq!(
+      format_over_budget(|items| {
+        items.push_str_runtime_width_computed("call_with_long_name");
+        items.push_signal(Signal::SpaceOrNewLine);
+        items.push_signal(Signal::StartNewLineGroup);
+        items.push_str_runtime_width_computed("(argument_one,");
+        items.push_signal(Signal::PossibleNewLine);
+        items.push_str_runtime_width_computed("argument_two)");
+        items.push_signal(Signal::FinishNewLineGroup);
+      }),
+      "call_with_long_name (argument_one,argument_two)",
+    );
+    // but still breaks at a possible newline in the same group
+    assert_eq!(
+      format_over_budget(|items| {
+        items.push_str_runtime_width_computed("call_with_long_name");
+        items.push_signal(Signal::SpaceOrNewLine);
+        items.push_str_runtime_width_computed("(argument_one,");
+        items.push_signal(Signal::PossibleNewLine);
+        items.push_str_runtime_width_computed("argument_two)");
+      }),
+      "call_with_long_name (argument_one,\nargument_two)",
+    );
+    // doesn't break once something else was written after the possible newline
+    assert_eq!(
+      format_over_budget(|items| {
+        items.push_str_runtime_width_computed("call_with_long_name");
+        items.push_signal(Signal::PossibleNewLine);
+        items.push_signal(Signal::Tab);
+        items.push_str_runtime_width_computed("argument_one_argument_two");
+      }),
+      "call_with_long_name\targument_one_argument_two",
+    );
+  }
+
+  fn get_print_options() -> PrintOptions {
+    test_helpers::get_print_options(40)
   }
 }
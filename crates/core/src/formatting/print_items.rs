@@ -3,6 +3,8 @@ use std::cell::UnsafeCell;
 use std::mem;
 use std::rc::Rc;
 
+use unicode_segmentation::UnicodeSegmentation;
+
 use super::condition_resolvers;
 use super::printer::Printer;
 use super::thread_state;
@@ -115,6 +117,12 @@ impl PrintItems {
     self.push_item_internal(PrintItem::ConditionReevaluation(condition_reevaluation));
   }
 
+  /// Marks that the next printed text corresponds to this byte position in the
+  /// original text. Used to create the source map in `format_with_source_map`.
+  pub fn push_source_position(&mut self, pos: u32) {
+    self.push_item_internal(PrintItem::SourcePosition(pos));
+  }
+
   pub fn push_signal(&mut self, signal: Signal) {
     self.push_item_internal(PrintItem::Signal(signal));
   }
@@ -175,6 +183,7 @@ impl PrintItems {
             text.push_str(&get_line(format!("{}: {}", desc, name), &indent_text))
           }
           PrintItem::ConditionReevaluation(reevaluation) => text.push_str(&get_line(format!("Condition reevaluation: {}", reevaluation.name()), &indent_text)),
+          PrintItem::SourcePosition(pos) => text.push_str(&get_line(format!("Source position: {}", pos), &indent_text)),
         }
       }
 
@@ -277,6 +286,15 @@ pub struct Trace {
   pub print_node_id: u32,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub writer_node_id: Option<u32>,
+  /// The writer state after handling the print node.
+  pub writer_info: WriterInfo,
+  /// The resolved value when the print node is a condition (`null` when it couldn't be resolved yet).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub condition_value: Option<Option<bool>>,
+  /// If the printer went back to a save point when handling the print node
+  /// (ex. to use a possible newline because the line width was exceeded).
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  pub restored_save_point: bool,
 }
 
 #[cfg(feature = "tracing")]
@@ -311,6 +329,7 @@ pub enum TracePrintItem {
   RcPath(u32),
   Anchor(TraceLineNumberAnchor),
   ConditionReevaluation(TraceConditionReevaluation),
+  SourcePosition(u32),
 }
 
 #[cfg(feature = "tracing")]
@@ -490,6 +509,8 @@ pub enum PrintItem {
   Anchor(Anchor),
   Info(Info),
   ConditionReevaluation(ConditionReevaluation),
+  /// Byte position in the original text that the text printed next corresponds to.
+  SourcePosition(u32),
 }
 
 #[derive(Clone, PartialEq, Eq, Copy, Debug, serde::Serialize)]
@@ -1081,9 +1102,16 @@ pub struct StringContainer {
 
 impl StringContainer {
   /// Creates a new string container.
+  ///
+  /// The width is the sum of the widths of the text's grapheme clusters according to
+  /// the `TextWidthOptions` of the `PrintOptions` that are currently being used to format.
+  /// Each cluster is its `unicode-width` (ex. a letter with combining marks is one column),
+  /// but no more than two columns, so any emoji ZWJ sequence is two columns.
   pub fn new(text: UnsafePrintLifetime<str>) -> Self {
-    let char_count = unicode_width::UnicodeWidthStr::width(text) as u32;
-    Self { text, char_count }
+    Self {
+      text,
+      char_count: get_text_width(text),
+    }
   }
 
   /// This is used by the sc! proc macro and should not be used otherwise
@@ -1093,8 +1121,26 @@ impl StringContainer {
   }
 }
 
+/// Gets the width of the text according to the `TextWidthOptions` currently being used to format.
+pub(crate) fn get_text_width(text: &str) -> u32 {
+  let ambiguous_as_wide = thread_state::text_width_options().ambiguous_as_wide;
+  let get_width = |text: &str| {
+    if ambiguous_as_wide {
+      unicode_width::UnicodeWidthStr::width_cjk(text)
+    } else {
+      unicode_width::UnicodeWidthStr::width(text)
+    }
+  };
+  if text.is_ascii() {
+    // every character is its own grapheme cluster
+    return get_width(text) as u32;
+  }
+  text.graphemes(true).map(|grapheme| get_width(grapheme).min(2) as u32).sum()
+}
+
 /// Information about a certain location being printed.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "tracing", derive(serde::Serialize), serde(rename_all = "camelCase"))]
 pub struct WriterInfo {
   pub line_number: u32,
   pub column_number: u32,
@@ -29,6 +29,12 @@ pub struct SavePoint<'a> {
   pub next_node_stack: NodeStack<'a>,
 }
 
+pub struct PrinterResult<TWriteItems> {
+  pub write_items: Option<TWriteItems>,
+  /// If the work budget was exceeded and the printer fell back to only moving forward.
+  pub exceeded_work_budget: bool,
+}
+
 #[cfg(feature = "tracing")]
 pub struct PrintTracingResult<'a> {
   pub traces: Vec<Trace>,
@@ -41,6 +47,11 @@ pub struct PrinterOptions {
   pub max_width: u32,
   /// The number of columns to count when indenting or using a tab.
   pub indent_width: u8,
+  /// Whether to count a tab as the columns up to the next tab stop.
+  pub expand_tabs: bool,
+  /// The number of condition evaluations and restored save points allowed
+  /// before falling back to printing without looking ahead.
+  pub work_budget: Option<u64>,
   #[cfg(feature = "tracing")]
   pub enable_tracing: bool,
 }
@@ -78,10 +89,22 @@ pub struct Printer<'a> {
   max_width: u32,
   skip_moving_next: bool,
   resolving_save_point: Option<&'a SavePoint<'a>>,
+  work_budget: Option<u64>,
+  work_count: u64,
+  is_over_work_budget: bool,
+  /// If the next text may go on a new line when over the work budget.
+  is_at_fallback_possible_new_line: bool,
+  /// The lowest new line group depth of the possible newlines on the current
+  /// line when over the work budget.
+  fallback_new_line_group_depth: Option<u16>,
   #[cfg(feature = "tracing")]
   traces: Option<Vec<Trace>>,
   #[cfg(feature = "tracing")]
   start_time: std::time::Instant,
+  #[cfg(feature = "tracing")]
+  trace_condition_value: Option<Option<bool>>,
+  #[cfg(feature = "tracing")]
+  trace_restored_save_point: bool,
 }
 
 impl<'a> Printer<'a> {
@@ -96,6 +119,7 @@ impl<'a> Printer<'a> {
         bump,
         WriterOptions {
           indent_width: options.indent_width,
+          expand_tabs: options.expand_tabs,
           #[cfg(feature = "tracing")]
           enable_tracing: options.enable_tracing,
         },
@@ -121,17 +145,29 @@ impl<'a> Printer<'a> {
       max_width: options.max_width,
       skip_moving_next: false,
       resolving_save_point: None,
+      work_budget: options.work_budget,
+      work_count: 0,
+      is_over_work_budget: false,
+      is_at_fallback_possible_new_line: false,
+      fallback_new_line_group_depth: None,
       #[cfg(feature = "tracing")]
       traces: if options.enable_tracing { Some(Vec::new()) } else { None },
       #[cfg(feature = "tracing")]
       start_time: std::time::Instant::now(),
+      #[cfg(feature = "tracing")]
+      trace_condition_value: None,
+      #[cfg(feature = "tracing")]
+      trace_restored_save_point: false,
     }
   }
 
   /// Turns the print items into a collection of writer items according to the options.
-  pub fn print(mut self) -> Option<impl Iterator<Item = WriteItem<'a>>> {
+  pub fn print(mut self) -> PrinterResult<impl Iterator<Item = WriteItem<'a>>> {
     self.inner_print();
-    self.writer.items()
+    PrinterResult {
+      exceeded_work_budget: self.is_over_work_budget,
+      write_items: self.writer.items(),
+    }
   }
 
   /// Turns the print items into a collection of writer items according to the options along with traces.
@@ -179,6 +215,9 @@ impl<'a> Printer<'a> {
         nanos: (std::time::Instant::now() - self.start_time).as_nanos(),
         print_node_id: current_node.print_node_id,
         writer_node_id: self.writer.current_node_id(),
+        writer_info: self.writer.writer_info(),
+        condition_value: self.trace_condition_value.take(),
+        restored_save_point: std::mem::take(&mut self.trace_restored_save_point),
       });
     }
   }
@@ -190,7 +229,7 @@ impl<'a> Printer<'a> {
 
   pub fn resolved_line_number(&mut self, line_number: LineNumber) -> Option<u32> {
     let resolved_number = self.resolved_line_numbers.get(line_number.unique_id());
-    if resolved_number.is_none() && !self.look_ahead_line_number_save_points.contains_key(&line_number.unique_id()) {
+    if resolved_number.is_none() && !self.is_over_work_budget && !self.look_ahead_line_number_save_points.contains_key(&line_number.unique_id()) {
       let save_point = self.get_save_point_for_restoring_condition(line_number.name());
       self.look_ahead_line_number_save_points.insert(line_number.unique_id(), save_point);
     }
@@ -200,7 +239,7 @@ impl<'a> Printer<'a> {
 
   pub fn resolved_column_number(&mut self, column_number: ColumnNumber) -> Option<u32> {
     let resolved_number = self.resolved_column_numbers.get(column_number.unique_id());
-    if resolved_number.is_none() && !self.look_ahead_column_number_save_points.contains_key(&column_number.unique_id()) {
+    if resolved_number.is_none() && !self.is_over_work_budget && !self.look_ahead_column_number_save_points.contains_key(&column_number.unique_id()) {
       let save_point = self.get_save_point_for_restoring_condition(column_number.name());
       self.look_ahead_column_number_save_points.insert(column_number.unique_id(), save_point);
     }
@@ -210,7 +249,10 @@ impl<'a> Printer<'a> {
 
   pub fn resolved_is_start_of_line(&mut self, is_start_of_line: IsStartOfLine) -> Option<bool> {
     let resolved_is_start_of_line = self.resolved_is_start_of_lines.get(is_start_of_line.unique_id());
-    if resolved_is_start_of_line.is_none() && !self.look_ahead_is_start_of_line_save_points.contains_key(&is_start_of_line.unique_id()) {
+    if resolved_is_start_of_line.is_none()
+      && !self.is_over_work_budget
+      && !self.look_ahead_is_start_of_line_save_points.contains_key(&is_start_of_line.unique_id())
+    {
       let save_point = self.get_save_point_for_restoring_condition(is_start_of_line.name());
       self.look_ahead_is_start_of_line_save_points.insert(is_start_of_line.unique_id(), save_point);
     }
@@ -220,7 +262,7 @@ impl<'a> Printer<'a> {
 
   pub fn resolved_indent_level(&mut self, indent_level: IndentLevel) -> Option<u8> {
     let resolved_indent_level = self.resolved_indent_levels.get(indent_level.unique_id());
-    if resolved_indent_level.is_none() && !self.look_ahead_indent_level_save_points.contains_key(&indent_level.unique_id()) {
+    if resolved_indent_level.is_none() && !self.is_over_work_budget && !self.look_ahead_indent_level_save_points.contains_key(&indent_level.unique_id()) {
       let save_point = self.get_save_point_for_restoring_condition(indent_level.name());
       self.look_ahead_indent_level_save_points.insert(indent_level.unique_id(), save_point);
     }
@@ -231,6 +273,7 @@ impl<'a> Printer<'a> {
   pub fn resolved_line_start_column_number(&mut self, line_start_column_number: LineStartColumnNumber) -> Option<u32> {
     let resolved_line_start_column_number = self.resolved_line_start_column_numbers.get(line_start_column_number.unique_id());
     if resolved_line_start_column_number.is_none()
+      && !self.is_over_work_budget
       && !self
         .look_ahead_line_start_column_number_save_points
         .contains_key(&line_start_column_number.unique_id())
@@ -247,6 +290,7 @@ impl<'a> Printer<'a> {
   pub fn resolved_line_start_indent_level(&mut self, line_start_indent_level: LineStartIndentLevel) -> Option<u8> {
     let resolved_line_start_indent_level = self.resolved_line_start_indent_levels.get(line_start_indent_level.unique_id());
     if resolved_line_start_indent_level.is_none()
+      && !self.is_over_work_budget
       && !self
         .look_ahead_line_start_indent_level_save_points
         .contains_key(&line_start_indent_level.unique_id())
@@ -272,7 +316,10 @@ impl<'a> Printer<'a> {
   }
 
   pub fn resolved_condition(&mut self, condition_reference: &ConditionReference) -> Option<bool> {
-    if !self.resolved_conditions.contains_key(&condition_reference.id) && !self.look_ahead_condition_save_points.contains_key(&condition_reference.id) {
+    if !self.is_over_work_budget
+      && !self.resolved_conditions.contains_key(&condition_reference.id)
+      && !self.look_ahead_condition_save_points.contains_key(&condition_reference.id)
+    {
       let save_point = self.get_save_point_for_restoring_condition(condition_reference.name());
       self.look_ahead_condition_save_points.insert(condition_reference.id, save_point);
     }
@@ -295,12 +342,18 @@ impl<'a> Printer<'a> {
       PrintItem::Anchor(anchor) => self.handle_anchor(anchor),
       PrintItem::Info(info) => self.handle_targeted_info(info),
       PrintItem::ConditionReevaluation(reevaluation) => self.handle_condition_reevaluation(reevaluation),
+      PrintItem::SourcePosition(pos) => {
+        self.is_at_fallback_possible_new_line = false;
+        self.writer.source_position(*pos)
+      }
     }
   }
 
   fn write_new_line(&mut self) {
     self.writer.new_line();
     self.possible_new_line_save_point = None;
+    self.is_at_fallback_possible_new_line = false;
+    self.fallback_new_line_group_depth = None;
   }
 
   fn create_save_point(&self, _name: &'static str, next_node: Option<PrintItemPath>) -> &'a SavePoint<'a> {
@@ -333,6 +386,18 @@ impl<'a> Printer<'a> {
   }
 
   fn mark_possible_new_line_if_able(&mut self) {
+    if self.is_over_work_budget {
+      // like the save point below, prefer a possible newline in an outer group
+      // on this line, which means not breaking the line in a deeper group
+      if let Some(depth) = self.fallback_new_line_group_depth {
+        if self.new_line_group_depth > depth {
+          return;
+        }
+      }
+      self.fallback_new_line_group_depth = Some(self.new_line_group_depth);
+      self.is_at_fallback_possible_new_line = true;
+      return;
+    }
     if let Some(new_line_save_point) = &self.possible_new_line_save_point {
       if self.new_line_group_depth > new_line_save_point.new_line_group_depth {
         return;
@@ -348,7 +413,29 @@ impl<'a> Printer<'a> {
     self.writer.column_number() + offset > self.max_width
   }
 
+  /// Counts a condition evaluation or restored save point towards the work budget.
+  fn count_work(&mut self) {
+    self.work_count += 1;
+    if !self.is_over_work_budget && self.work_budget.is_some_and(|budget| self.work_count > budget) {
+      // fall back to only moving forward, which breaks lines at the last
+      // possible newline instead of going back to find a better place
+      self.is_over_work_budget = true;
+      self.possible_new_line_save_point = None;
+    }
+  }
+
+  fn restore_look_ahead_save_point(&mut self, save_point: &'a SavePoint<'a>) {
+    if !self.is_over_work_budget {
+      self.update_state_to_save_point(save_point, false);
+    }
+  }
+
   fn update_state_to_save_point(&mut self, save_point: &'a SavePoint<'a>, is_for_new_line: bool) {
+    self.count_work();
+    #[cfg(feature = "tracing")]
+    {
+      self.trace_restored_save_point = true;
+    }
     self.writer.set_state(save_point.writer_state.clone());
     self.possible_new_line_save_point = if is_for_new_line { None } else { save_point.possible_new_line_save_point };
     self.current_node = save_point.node;
@@ -390,11 +477,16 @@ impl<'a> Printer<'a> {
           self.write_new_line()
         }
       }
-      Signal::Tab => self.writer.tab(),
+      Signal::Tab => {
+        self.is_at_fallback_possible_new_line = false;
+        self.writer.tab()
+      }
       Signal::ExpectNewLine => {
         // just always allow this for now since it's most likely a comment...
         self.writer.mark_expect_new_line();
         self.possible_new_line_save_point = None;
+        self.is_at_fallback_possible_new_line = false;
+        self.fallback_new_line_group_depth = None;
       }
       Signal::PossibleNewLine => {
         if self.allow_new_lines() {
@@ -427,12 +519,18 @@ impl<'a> Printer<'a> {
       Signal::FinishIndent => self.writer.finish_indent(),
       Signal::StartNewLineGroup => self.new_line_group_depth += 1,
       Signal::FinishNewLineGroup => self.new_line_group_depth -= 1,
-      Signal::SingleIndent => self.writer.single_indent(),
+      Signal::SingleIndent => {
+        self.is_at_fallback_possible_new_line = false;
+        self.writer.single_indent()
+      }
       Signal::StartIgnoringIndent => self.writer.start_ignoring_indent(),
       Signal::FinishIgnoringIndent => self.writer.finish_ignoring_indent(),
       Signal::StartForceNoNewLines => self.force_no_newlines_depth += 1,
       Signal::FinishForceNoNewLines => self.force_no_newlines_depth -= 1,
-      Signal::SpaceIfNotTrailing => self.writer.space_if_not_trailing(),
+      Signal::SpaceIfNotTrailing => {
+        self.is_at_fallback_possible_new_line = false;
+        self.writer.space_if_not_trailing()
+      }
     }
   }
 
@@ -459,13 +557,15 @@ impl<'a> Printer<'a> {
 
   #[inline]
   fn handle_targeted_info(&mut self, info: &Info) {
+    // the resolved position would be wrong if the next text went on a new line
+    self.is_at_fallback_possible_new_line = false;
     match info {
       Info::LineNumber(line_number) => {
         let line_number_id = line_number.unique_id();
         self.resolved_line_numbers.insert(line_number_id, self.writer.line_number());
         let option_save_point = self.look_ahead_line_number_save_points.remove(&line_number_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
       Info::ColumnNumber(column_number) => {
@@ -473,7 +573,7 @@ impl<'a> Printer<'a> {
         self.resolved_column_numbers.insert(column_number_id, self.writer.column_number());
         let option_save_point = self.look_ahead_column_number_save_points.remove(&column_number_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
       Info::IsStartOfLine(is_start_of_line) => {
@@ -481,7 +581,7 @@ impl<'a> Printer<'a> {
         self.resolved_is_start_of_lines.insert(is_start_of_line_id, self.writer.is_start_of_line());
         let option_save_point = self.look_ahead_is_start_of_line_save_points.remove(&is_start_of_line_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
       Info::IndentLevel(indent_level) => {
@@ -489,7 +589,7 @@ impl<'a> Printer<'a> {
         self.resolved_indent_levels.insert(indent_level_id, self.writer.indent_level());
         let option_save_point = self.look_ahead_indent_level_save_points.remove(&indent_level_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
       Info::LineStartColumnNumber(line_start_column_number) => {
@@ -499,7 +599,7 @@ impl<'a> Printer<'a> {
           .insert(line_start_column_number_id, self.writer.line_start_column_number());
         let option_save_point = self.look_ahead_line_start_column_number_save_points.remove(&line_start_column_number_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
       Info::LineStartIndentLevel(line_start_indent_level) => {
@@ -509,7 +609,7 @@ impl<'a> Printer<'a> {
           .insert(line_start_indent_level_id, self.writer.line_start_indent_level());
         let option_save_point = self.look_ahead_line_start_indent_level_save_points.remove(&line_start_indent_level_id);
         if let Some(save_point) = option_save_point {
-          self.update_state_to_save_point(save_point, false);
+          self.restore_look_ahead_save_point(save_point);
         }
       }
     }
@@ -524,6 +624,7 @@ impl<'a> Printer<'a> {
         let mut context = ConditionResolverContext::new(self, save_point.writer_state.writer_info(self.writer.indent_width()));
         let latest_condition_value = condition.resolve(&mut context);
         self.resolving_save_point.take();
+        self.count_work();
 
         // Do not re-evaluate the condition if it's flipped back and forth a decent number of times.
         // If it hits the max number of times it can flip then an error will be logged.
@@ -535,7 +636,7 @@ impl<'a> Printer<'a> {
         if should_reevaluate {
           if let Some(latest_condition_value) = latest_condition_value {
             if latest_condition_value != past_condition_value {
-              self.update_state_to_save_point(save_point, false);
+              self.restore_look_ahead_save_point(save_point);
             }
           } else {
             self.resolved_conditions.remove(&condition_id);
@@ -549,21 +650,28 @@ impl<'a> Printer<'a> {
   fn handle_condition(&mut self, condition: &'a Condition, next_node: &Option<PrintItemPath>) {
     let condition_id = condition.unique_id();
 
-    if condition.store_save_point {
+    if condition.store_save_point && !self.is_over_work_budget {
       let save_point = self.get_save_point_for_restoring_condition(condition.name());
       self.stored_condition_save_points.insert(condition.unique_id(), (condition, save_point));
     }
 
     let condition_value = condition.resolve(&mut ConditionResolverContext::new(self, self.get_writer_info()));
+    self.count_work();
+    #[cfg(feature = "tracing")]
+    {
+      self.trace_condition_value = Some(condition_value);
+    }
     if condition.is_stored {
       self.resolved_conditions.insert(condition_id, condition_value);
     }
 
-    let save_point = self.look_ahead_condition_save_points.get(&condition_id);
-    if condition_value.is_some() && save_point.is_some() {
-      let save_point = self.look_ahead_condition_save_points.remove(&condition_id);
-      self.update_state_to_save_point(save_point.unwrap(), false);
-      return;
+    if condition_value.is_some() {
+      if let Some(save_point) = self.look_ahead_condition_save_points.remove(&condition_id) {
+        if !self.is_over_work_budget {
+          self.update_state_to_save_point(save_point, false);
+          return;
+        }
+      }
     }
 
     if condition_value.is_some() && condition_value.unwrap() {
@@ -601,6 +709,11 @@ impl<'a> Printer<'a> {
       let save_point = self.possible_new_line_save_point.take();
       self.update_state_to_save_point(save_point.unwrap(), true);
     } else {
+      if self.is_at_fallback_possible_new_line && self.is_above_max_width(text.char_count) && self.allow_new_lines() {
+        // this will remove the space of a space or newline
+        self.write_new_line();
+      }
+      self.is_at_fallback_possible_new_line = false;
       self.writer.write(text);
     }
   }
@@ -0,0 +1,89 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A byte position in the original text and where it ended up in the formatted text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMapping {
+  pub original: usize,
+  pub formatted: usize,
+}
+
+/// Mapping between byte positions in the original text and the formatted text.
+///
+/// Created by `dprint_core::formatting::format_with_source_map` from the
+/// positions pushed with `PrintItems::push_source_position`. This isn't sent
+/// through the plugin protocols, so it's only available within a plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SourceMap {
+  /// Mappings ordered by their original position.
+  mappings: Vec<SourceMapping>,
+}
+
+impl SourceMap {
+  pub fn new(mut mappings: Vec<SourceMapping>) -> Self {
+    mappings.sort_by_key(|m| (m.original, m.formatted));
+    mappings.dedup();
+    Self { mappings }
+  }
+
+  /// Gets the mappings ordered by their original position.
+  pub fn mappings(&self) -> &[SourceMapping] {
+    &self.mappings
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.mappings.is_empty()
+  }
+
+  /// Gets the position in the formatted text for a position in
+  /// the original text (ex. to relocate a cursor after formatting).
+  ///
+  /// Positions between mappings keep their offset from the closest
+  /// preceding mapping, but won't go past the following mapping.
+  pub fn get_formatted_position(&self, original: usize) -> usize {
+    let index = self.mappings.partition_point(|m| m.original <= original);
+    if index == 0 {
+      return match self.mappings.first() {
+        Some(next) => original.min(next.formatted),
+        None => original,
+      };
+    }
+    let mapping = &self.mappings[index - 1];
+    let position = mapping.formatted + (original - mapping.original);
+    match self.mappings.get(index) {
+      Some(next) if next.formatted >= mapping.formatted => position.min(next.formatted),
+      _ => position,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn gets_formatted_position() {
+    let source_map = SourceMap::new(vec![
+      SourceMapping { original: 10, formatted: 4 },
+      SourceMapping { original: 0, formatted: 0 },
+      SourceMapping { original: 20, formatted: 8 },
+    ]);
+    assert_eq!(
+      source_map.mappings(),
+      &[
+        SourceMapping { original: 0, formatted: 0 },
+        SourceMapping { original: 10, formatted: 4 },
+        SourceMapping { original: 20, formatted: 8 },
+      ]
+    );
+    assert_eq!(source_map.get_formatted_position(0), 0);
+    assert_eq!(source_map.get_formatted_position(2), 2);
+    // clamped to the next mapping
+    assert_eq!(source_map.get_formatted_position(8), 4);
+    assert_eq!(source_map.get_formatted_position(10), 4);
+    assert_eq!(source_map.get_formatted_position(11), 5);
+    assert_eq!(source_map.get_formatted_position(25), 13);
+    assert_eq!(SourceMap::default().get_formatted_position(5), 5);
+  }
+}
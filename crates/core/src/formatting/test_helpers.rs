@@ -0,0 +1,12 @@
+use super::PrintOptions;
+
+/// Gets the print options used by the formatting tests.
+pub fn get_print_options(max_width: u32) -> PrintOptions {
+  PrintOptions {
+    max_width,
+    indent_width: 2,
+    use_tabs: false,
+    new_line_text: "\n",
+    ..Default::default()
+  }
+}
@@ -1,6 +1,9 @@
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::UnsafeCell;
 use std::rc::Rc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use super::collections::GraphNode;
 use super::collections::NodeStackNode;
@@ -9,6 +12,7 @@ use super::ConditionResolver;
 use super::PrintNodeCell;
 use super::SavePoint;
 use super::StringContainer;
+use super::TextWidthOptions;
 use super::UnsafePrintLifetime;
 use super::WriteItem;
 
@@ -70,8 +74,8 @@ impl BumpAllocator {
     let string = match item {
       Cow::Borrowed(item) => item,
       Cow::Owned(item) => {
-        let string = self.bump.alloc(bumpalo::collections::String::from_str_in(&item, &self.bump));
-        unsafe { std::mem::transmute::<&bumpalo::collections::String, UnsafePrintLifetime<bumpalo::collections::String>>(string) }
+        let string = self.bump.alloc_str(&item);
+        unsafe { std::mem::transmute::<&str, UnsafePrintLifetime<str>>(string) }
       }
     };
     let string = StringContainer::new(string);
@@ -96,15 +100,38 @@ impl BumpAllocator {
     unsafe { std::mem::transmute::<&PrintNodeCell, UnsafePrintLifetime<PrintNodeCell>>(result) }
   }
 
+  pub fn allocated_bytes(&self) -> usize {
+    self.bump.allocated_bytes()
+  }
+
   pub fn reset(&mut self) {
     self.bump.reset();
     self.condition_resolvers.clear();
+    // the memory is reused for the next format unless it grew too large (ex. a huge file)
+    if self.bump.allocated_bytes() > ARENA_RETENTION_LIMIT.load(Ordering::Relaxed) {
+      self.bump = bumpalo::Bump::new();
+      self.condition_resolvers = Default::default();
+    }
   }
 }
 
+/// The maximum number of bytes the arena of each thread keeps allocated when reset.
+static ARENA_RETENTION_LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Sets the maximum number of bytes the arena of every thread keeps allocated when reset.
+pub fn set_arena_retention_limit(max_bytes: Option<usize>) {
+  ARENA_RETENTION_LIMIT.store(max_bytes.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
 thread_local! {
   static BUMP_ALLOCATOR: UnsafeCell<BumpAllocator> = UnsafeCell::new(BumpAllocator::new());
   static COUNTS: UnsafeCell<Counts> = UnsafeCell::new(Default::default());
+  static TEXT_WIDTH_OPTIONS: Cell<TextWidthOptions> = const {
+    Cell::new(TextWidthOptions {
+      ambiguous_as_wide: false,
+      expand_tabs: false,
+    })
+  };
 }
 
 pub fn with_bump_allocator<TReturn>(action: impl FnOnce(&mut BumpAllocator) -> TReturn) -> TReturn {
@@ -124,6 +151,16 @@ pub fn set_counts(counts: Counts) {
   })
 }
 
+/// Gets the options used to compute the width of strings created while formatting.
+pub fn text_width_options() -> TextWidthOptions {
+  TEXT_WIDTH_OPTIONS.with(|cell| cell.get())
+}
+
+/// Sets the options used to compute the width of strings, returning the previous options.
+pub fn set_text_width_options(options: TextWidthOptions) -> TextWidthOptions {
+  TEXT_WIDTH_OPTIONS.with(|cell| cell.replace(options))
+}
+
 pub fn next_line_number_anchor_id() -> u32 {
   COUNTS.with(|cell| unsafe {
     let counts = &mut *cell.get();
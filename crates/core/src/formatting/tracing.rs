@@ -61,6 +61,7 @@ pub fn get_trace_print_nodes(start_node: Option<PrintItemPath>) -> Vec<TracePrin
         condition_id: reevaluation.condition_id,
         name: reevaluation.name().to_string(),
       }),
+      PrintItem::SourcePosition(pos) => TracePrintItem::SourcePosition(pos),
     };
 
     // create and store the trace print node
@@ -1,13 +1,21 @@
 use super::PrintOptions;
+use super::SourceMap;
+use super::SourceMapping;
 use super::StringContainer;
+use super::WhitespaceOptions;
 
 #[derive(Clone, Copy)]
 pub enum WriteItem<'a> {
   String(&'a StringContainer),
   Indent(u8),
   NewLine,
+  /// A newline written while ignoring indentation (ex. within a template literal),
+  /// which is part of the text, so the whitespace policies leave it alone.
+  VerbatimNewLine,
   Tab,
   Space,
+  /// Byte position in the original text. This has no output.
+  SourcePosition(u32),
 }
 
 pub enum Indentation {
@@ -22,6 +30,8 @@ pub struct WriteItemsPrinter {
   pub indent: Indentation,
   /// Character to use for a newline.
   pub newline: &'static str,
+  /// Whitespace policies to enforce on the output.
+  pub whitespace: WhitespaceOptions,
 }
 
 impl WriteItemsPrinter {
@@ -32,25 +42,140 @@ impl WriteItemsPrinter {
         false => Indentation::Spaces(indent_width as usize),
       },
       newline,
+      whitespace: Default::default(),
     }
   }
 
   pub fn print<'a>(&self, items: impl Iterator<Item = WriteItem<'a>>) -> String {
-    items.fold(String::new(), |acc, item| match item {
+    self.print_inner(items, &mut None)
+  }
+
+  /// Prints the items along with a source map of the source positions found in the items.
+  pub fn print_with_source_map<'a>(&self, items: impl Iterator<Item = WriteItem<'a>>) -> (String, SourceMap) {
+    let mut mappings = Some(Vec::new());
+    let text = self.print_inner(items, &mut mappings);
+    (text, SourceMap::new(mappings.unwrap()))
+  }
+
+  fn print_inner<'a>(&self, items: impl Iterator<Item = WriteItem<'a>>, mappings: &mut Option<Vec<SourceMapping>>) -> String {
+    let mut text = String::new();
+    let mut pending_positions = Vec::new();
+    let mut line_start = 0;
+    // only trim the whitespace after the last text on a line because trailing
+    // whitespace within text is part of it (ex. a markdown hard break)
+    let mut trim_start = 0;
+    let mut blank_line_count = 0;
+    for item in items {
+      match item {
+        WriteItem::SourcePosition(pos) => {
+          if mappings.is_some() {
+            pending_positions.push(pos);
+          }
+          continue;
+        }
+        // a position at the start of a line maps to after the indentation
+        WriteItem::Indent(_) => {}
+        _ => flush_positions(&mut pending_positions, mappings, text.len()),
+      }
+      match item {
+        WriteItem::NewLine => {
+          if self.whitespace.trim_trailing_whitespace {
+            trim_whitespace_end(&mut text, trim_start, mappings);
+          }
+          if text.len() == line_start {
+            blank_line_count += 1;
+            if self.whitespace.max_consecutive_blank_lines.is_some_and(|max| blank_line_count > max) {
+              continue;
+            }
+          } else {
+            blank_line_count = 0;
+          }
+          text.push_str(self.newline);
+          line_start = text.len();
+          trim_start = line_start;
+        }
+        WriteItem::VerbatimNewLine => {
+          blank_line_count = 0;
+          text.push_str(self.newline);
+          line_start = text.len();
+          trim_start = line_start;
+        }
+        WriteItem::String(string) => {
+          self.push_item(&mut text, item);
+          // whitespace only strings are separators (ex. `PrintItems::push_space`)
+          if !string.text.trim_start_matches([' ', '\t']).is_empty() {
+            trim_start = text.len();
+          }
+        }
+        _ => self.push_item(&mut text, item),
+      }
+    }
+    flush_positions(&mut pending_positions, mappings, text.len());
+
+    if self.whitespace.trim_trailing_whitespace {
+      trim_whitespace_end(&mut text, trim_start, mappings);
+    }
+    if self.whitespace.insert_final_new_line {
+      let mut end = text.len();
+      while let Some(remaining) = text[..end].strip_suffix(self.newline) {
+        end = remaining.len();
+      }
+      truncate(&mut text, end, mappings);
+      if !text.is_empty() {
+        text.push_str(self.newline);
+      }
+    }
+    text
+  }
+
+  fn push_item(&self, text: &mut String, item: WriteItem) {
+    match item {
       WriteItem::Indent(n) => match self.indent {
-        Indentation::Tabs => acc + &str::repeat("\t", n as usize),
-        Indentation::Spaces(width) => acc + &str::repeat(" ", width * n as usize),
+        Indentation::Tabs => text.push_str(&str::repeat("\t", n as usize)),
+        Indentation::Spaces(width) => text.push_str(&str::repeat(" ", width * n as usize)),
       },
-      WriteItem::NewLine => acc + self.newline,
-      WriteItem::Tab => acc + "\t",
-      WriteItem::Space => acc + " ",
-      WriteItem::String(StringContainer { text, .. }) => acc + text,
-    })
+      WriteItem::NewLine | WriteItem::VerbatimNewLine => text.push_str(self.newline),
+      WriteItem::Tab => text.push('\t'),
+      WriteItem::Space => text.push(' '),
+      WriteItem::String(StringContainer { text: item_text, .. }) => text.push_str(item_text),
+      WriteItem::SourcePosition(_) => {}
+    }
   }
 }
 
 impl From<&PrintOptions> for WriteItemsPrinter {
   fn from(value: &PrintOptions) -> Self {
-    Self::new(value.indent_width, value.use_tabs, value.new_line_text)
+    Self {
+      whitespace: value.whitespace,
+      ..Self::new(value.indent_width, value.use_tabs, value.new_line_text)
+    }
+  }
+}
+
+fn flush_positions(pending_positions: &mut Vec<u32>, mappings: &mut Option<Vec<SourceMapping>>, formatted: usize) {
+  if let Some(mappings) = mappings {
+    mappings.extend(pending_positions.drain(..).map(|pos| SourceMapping {
+      original: pos as usize,
+      formatted,
+    }));
+  }
+}
+
+/// Removes the trailing spaces and tabs found after the provided start.
+fn trim_whitespace_end(text: &mut String, start: usize, mappings: &mut Option<Vec<SourceMapping>>) {
+  let len = start + text[start..].trim_end_matches([' ', '\t']).len();
+  truncate(text, len, mappings);
+}
+
+/// Truncates the text, moving any mappings that pointed past the end back to it.
+fn truncate(text: &mut String, len: usize, mappings: &mut Option<Vec<SourceMapping>>) {
+  text.truncate(len);
+  if let Some(mappings) = mappings {
+    for mapping in mappings.iter_mut().rev() {
+      if mapping.formatted <= len {
+        break;
+      }
+      mapping.formatted = len;
+    }
   }
 }
@@ -41,6 +41,7 @@ impl WriterState<'_> {
 
 pub struct WriterOptions {
   pub indent_width: u8,
+  pub expand_tabs: bool,
   #[cfg(feature = "tracing")]
   pub enable_tracing: bool,
 }
@@ -49,6 +50,7 @@ pub struct Writer<'a> {
   bump: &'a BumpAllocator,
   state: WriterState<'a>,
   indent_width: u8,
+  expand_tabs: bool,
   #[cfg(feature = "tracing")]
   nodes: Option<Vec<&'a GraphNode<'a, WriteItem<'a>>>>,
 }
@@ -58,6 +60,7 @@ impl<'a> Writer<'a> {
     Writer {
       bump,
       indent_width: options.indent_width,
+      expand_tabs: options.expand_tabs,
       state: WriterState {
         current_line_column: 0,
         current_line_number: 0,
@@ -183,7 +186,7 @@ impl<'a> Writer<'a> {
 
   pub fn new_line(&mut self) {
     if self.state.last_was_not_trailing_space {
-      self.pop_item();
+      self.pop_not_trailing_space();
       self.state.last_was_not_trailing_space = false;
     }
 
@@ -191,7 +194,11 @@ impl<'a> Writer<'a> {
     self.state.current_line_number += 1;
     self.state.last_line_indent_level = self.state.indent_level;
     self.state.expect_newline_next = false;
-    self.push_item(WriteItem::NewLine);
+    self.push_item(if self.state.ignore_indent_count > 0 {
+      WriteItem::VerbatimNewLine
+    } else {
+      WriteItem::NewLine
+    });
   }
 
   pub fn single_indent(&mut self) {
@@ -202,7 +209,13 @@ impl<'a> Writer<'a> {
 
   pub fn tab(&mut self) {
     self.handle_first_column();
-    self.state.current_line_column += self.indent_width as u32;
+    let indent_width = self.indent_width as u32;
+    if self.expand_tabs && indent_width > 0 {
+      // move to the next tab stop
+      self.state.current_line_column = (self.state.current_line_column / indent_width + 1) * indent_width;
+    } else {
+      self.state.current_line_column += indent_width;
+    }
     self.push_item(WriteItem::Tab);
   }
 
@@ -212,6 +225,12 @@ impl<'a> Writer<'a> {
     self.push_item(WriteItem::Space);
   }
 
+  /// Marks the byte position in the original text that the next written text corresponds to.
+  pub fn source_position(&mut self, pos: u32) {
+    // this has no width, so don't handle the first column or queued indentation
+    self.push_node(WriteItem::SourcePosition(pos));
+  }
+
   pub fn write(&mut self, text: &'a StringContainer) {
     self.handle_first_column();
     self.state.current_line_column += text.char_count;
@@ -242,6 +261,16 @@ impl<'a> Writer<'a> {
   }
 
   fn push_item(&mut self, item: WriteItem<'a>) {
+    self.push_node(item);
+
+    if self.state.indent_queue_count > 0 {
+      let indent_count = self.state.indent_queue_count;
+      self.state.indent_queue_count = 0;
+      self.state.indent_level += indent_count;
+    }
+  }
+
+  fn push_node(&mut self, item: WriteItem<'a>) {
     let previous = self.state.items.take();
     let graph_node = self.bump.alloc_write_item_graph_node(GraphNode::new(item, previous));
     self.state.items = Some(graph_node);
@@ -250,17 +279,20 @@ impl<'a> Writer<'a> {
     if let Some(nodes) = self.nodes.as_mut() {
       nodes.push(graph_node);
     }
-
-    if self.state.indent_queue_count > 0 {
-      let indent_count = self.state.indent_queue_count;
-      self.state.indent_queue_count = 0;
-      self.state.indent_level += indent_count;
-    }
   }
 
-  fn pop_item(&mut self) {
-    if let Some(previous) = &self.state.items {
-      self.state.items = previous.previous;
+  fn pop_not_trailing_space(&mut self) {
+    // keep any source positions written after the space
+    let mut source_positions = Vec::new();
+    while let Some(node) = self.state.items {
+      self.state.items = node.previous;
+      match node.item {
+        WriteItem::SourcePosition(pos) => source_positions.push(pos),
+        _ => break,
+      }
+    }
+    for pos in source_positions.into_iter().rev() {
+      self.push_node(WriteItem::SourcePosition(pos));
     }
   }
 
@@ -376,6 +408,7 @@ mod test {
     let p = WriteItemsPrinter {
       indent: Indentation::Spaces(2),
       newline: "\n",
+      whitespace: Default::default(),
     };
     assert_eq!(p.print(writer.items().unwrap()), String::from(text));
   }
@@ -390,6 +423,7 @@ mod test {
       bump,
       WriterOptions {
         indent_width: 2,
+        expand_tabs: false,
         #[cfg(feature = "tracing")]
         enable_tracing: false,
       },
@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FormatDiagnosticSeverity {
+  #[default]
+  Error,
+  Warning,
+}
+
+/// A structured error a plugin may return when it fails formatting
+/// a file (ex. a parse error).
+///
+/// Return this as the error of a `FormatResult` (ex. `Err(diagnostic.into())`)
+/// and the dprint CLI and editors will be able to show the location of the error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatDiagnostic {
+  pub message: String,
+  /// Byte range within the file text the diagnostic applies to.
+  #[serde(default)]
+  pub range: Option<std::ops::Range<usize>>,
+  #[serde(default)]
+  pub severity: FormatDiagnosticSeverity,
+  /// Plugin specific code of the diagnostic.
+  #[serde(default)]
+  pub code: Option<String>,
+}
+
+impl FormatDiagnostic {
+  pub fn new(message: impl Into<String>) -> Self {
+    Self {
+      message: message.into(),
+      range: None,
+      severity: Default::default(),
+      code: None,
+    }
+  }
+
+  /// Gets the format diagnostic from the provided error if it is one.
+  pub fn from_error(err: &anyhow::Error) -> Option<&FormatDiagnostic> {
+    err.downcast_ref::<FormatDiagnostic>()
+  }
+
+  /// Renders the diagnostic with the line and column along with
+  /// a snippet of the file text that highlights the range.
+  #[cfg(feature = "formatting")]
+  pub fn render(&self, file_text: &str) -> String {
+    let range = self
+      .range
+      .as_ref()
+      .filter(|r| r.start <= r.end && file_text.is_char_boundary(r.start) && file_text.is_char_boundary(r.end));
+    crate::formatting::utils::string_utils::format_diagnostic(range.map(|r| (r.start, r.end)), &self.to_string(), file_text)
+  }
+}
+
+impl std::fmt::Display for FormatDiagnostic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.code {
+      Some(code) => write!(f, "{} ({})", self.message, code),
+      None => write!(f, "{}", self.message),
+    }
+  }
+}
+
+impl std::error::Error for FormatDiagnostic {}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn should_get_from_error() {
+    let diagnostic = FormatDiagnostic {
+      message: "Expected a semi-colon.".to_string(),
+      range: Some(4..5),
+      severity: FormatDiagnosticSeverity::Error,
+      code: Some("E1".to_string()),
+    };
+    let err: anyhow::Error = diagnostic.clone().into();
+    assert_eq!(FormatDiagnostic::from_error(&err), Some(&diagnostic));
+    assert_eq!(err.to_string(), "Expected a semi-colon. (E1)");
+    assert_eq!(FormatDiagnostic::from_error(&anyhow::anyhow!("Other.")), None);
+  }
+
+  #[test]
+  fn should_serialize_and_deserialize() {
+    let diagnostic = FormatDiagnostic {
+      message: "Message.".to_string(),
+      range: Some(1..3),
+      severity: FormatDiagnosticSeverity::Warning,
+      code: None,
+    };
+    let json = serde_json::to_string(&diagnostic).unwrap();
+    assert_eq!(json, r#"{"message":"Message.","range":{"start":1,"end":3},"severity":"warning","code":null}"#);
+    assert_eq!(serde_json::from_str::<FormatDiagnostic>(&json).unwrap(), diagnostic);
+    assert_eq!(
+      serde_json::from_str::<FormatDiagnostic>(r#"{"message":"Message."}"#).unwrap(),
+      FormatDiagnostic::new("Message.")
+    );
+  }
+
+  #[cfg(feature = "formatting")]
+  #[test]
+  fn should_render() {
+    let mut diagnostic = FormatDiagnostic::new("Unexpected token.");
+    diagnostic.range = Some(5..8);
+    assert_eq!(diagnostic.render("let\nabc def;"), "Line 2, column 2: Unexpected token.\n\n  abc def;\n   ~~~");
+    // out of range, so ignore the range
+    diagnostic.range = Some(5..100);
+    assert_eq!(diagnostic.render("let\nabc def;"), "Unexpected token.");
+  }
+}
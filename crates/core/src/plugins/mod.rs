@@ -1,3 +1,4 @@
+mod format_diagnostic;
 mod plugin_handler;
 mod plugin_info;
 
@@ -6,5 +7,6 @@ pub mod process;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+pub use format_diagnostic::*;
 pub use plugin_handler::*;
 pub use plugin_info::*;
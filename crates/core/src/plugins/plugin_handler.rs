@@ -11,6 +11,8 @@ use crate::configuration::ConfigKeyMap;
 use crate::configuration::ConfigKeyValue;
 use crate::configuration::ConfigurationDiagnostic;
 use crate::configuration::GlobalConfiguration;
+use crate::configuration::WhitespaceOptions;
+use crate::plugins::FormatDiagnostic;
 use crate::plugins::PluginInfo;
 
 use super::FileMatchingInfo;
@@ -79,6 +81,50 @@ pub struct CheckConfigUpdatesMessage {
   pub config: ConfigKeyMap,
 }
 
+/// Information about text being formatted by the host, such as
+/// a code block embedded in another language.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostFormatHints {
+  /// Language identifier (ex. `sql`) or media type (ex. `text/css`) of the
+  /// text. When set, the host uses this to pick the plugin instead of the
+  /// file path's extension.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub language: Option<String>,
+  /// Line width inherited from the host document.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub line_width: Option<u32>,
+  /// Indent width inherited from the host document.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub indent_width: Option<u8>,
+  /// Whether tabs are used in the host document.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub use_tabs: Option<bool>,
+}
+
+impl HostFormatHints {
+  pub fn is_empty(&self) -> bool {
+    self.language.is_none() && self.line_width.is_none() && self.indent_width.is_none() && self.use_tabs.is_none()
+  }
+
+  /// Adds the inherited values to the override configuration unless
+  /// they're already set there or in the provided plugin configuration.
+  pub fn add_inherited_to_override_config(&self, override_config: &mut ConfigKeyMap, plugin_config: &ConfigKeyMap) {
+    let values = [
+      ("lineWidth", self.line_width.map(|v| ConfigKeyValue::from_i32(v as i32))),
+      ("indentWidth", self.indent_width.map(|v| ConfigKeyValue::from_i32(v as i32))),
+      ("useTabs", self.use_tabs.map(ConfigKeyValue::from_bool)),
+    ];
+    for (key, value) in values {
+      if let Some(value) = value {
+        if !override_config.contains_key(key) && !plugin_config.contains_key(key) {
+          override_config.insert(key.to_string(), value);
+        }
+      }
+    }
+  }
+}
+
 #[cfg(feature = "process")]
 #[derive(Debug)]
 pub struct HostFormatRequest {
@@ -87,6 +133,7 @@ pub struct HostFormatRequest {
   /// Range to format.
   pub range: FormatRange,
   pub override_config: ConfigKeyMap,
+  pub hints: HostFormatHints,
   pub token: std::sync::Arc<dyn CancellationToken>,
 }
 
@@ -98,6 +145,7 @@ pub struct SyncHostFormatRequest<'a> {
   /// Range to format.
   pub range: FormatRange,
   pub override_config: &'a ConfigKeyMap,
+  pub hints: HostFormatHints,
 }
 
 /// `Ok(Some(text))` - Changes due to the format.
@@ -105,6 +153,9 @@ pub struct SyncHostFormatRequest<'a> {
 /// `Err(err)` - Error formatting. Use a `CriticalError` to signal that the plugin can't recover.
 pub type FormatResult = Result<Option<Vec<u8>>>;
 
+/// Problems found in the file that the plugin won't fix when formatting.
+pub type CheckResult = Result<Vec<FormatDiagnostic>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawFormatConfig {
   pub plugin: ConfigKeyMap,
@@ -143,6 +194,9 @@ pub struct FormatRequest<TConfiguration> {
   pub config: std::sync::Arc<TConfiguration>,
   /// Range to format.
   pub range: FormatRange,
+  /// Whitespace policies from the global configuration (ex. `trimTrailingWhitespace`).
+  /// Provide these to `PrintOptions::with_whitespace` to have the printer enforce them.
+  pub whitespace: WhitespaceOptions,
   pub token: std::sync::Arc<dyn CancellationToken>,
 }
 
@@ -154,6 +208,27 @@ pub struct SyncFormatRequest<'a, TConfiguration> {
   pub config: &'a TConfiguration,
   /// Range to format.
   pub range: FormatRange,
+  /// Whitespace policies from the global configuration (ex. `trimTrailingWhitespace`).
+  /// Provide these to `PrintOptions::with_whitespace` to have the printer enforce them.
+  pub whitespace: WhitespaceOptions,
+  pub token: &'a dyn CancellationToken,
+}
+
+#[cfg(feature = "process")]
+pub struct CheckRequest<TConfiguration> {
+  pub file_path: std::path::PathBuf,
+  pub file_bytes: Vec<u8>,
+  pub config_id: FormatConfigId,
+  pub config: std::sync::Arc<TConfiguration>,
+  pub token: std::sync::Arc<dyn CancellationToken>,
+}
+
+#[cfg(feature = "wasm")]
+pub struct SyncCheckRequest<'a, TConfiguration> {
+  pub file_path: &'a std::path::Path,
+  pub file_bytes: Vec<u8>,
+  pub config_id: FormatConfigId,
+  pub config: &'a TConfiguration,
   pub token: &'a dyn CancellationToken,
 }
 
@@ -233,11 +308,19 @@ pub trait AsyncPluginHandler: 'static {
     Ok(Vec::new())
   }
   /// Formats the provided file text based on the provided file path and configuration.
+  ///
+  /// Return `Ok(None)` when the text doesn't change so that large files
+  /// aren't sent back to the CLI.
   async fn format(
     &self,
     request: FormatRequest<Self::Configuration>,
     format_with_host: impl FnMut(HostFormatRequest) -> LocalBoxFuture<'static, FormatResult> + 'static,
   ) -> FormatResult;
+  /// Checks the provided file text for problems the plugin won't fix when
+  /// formatting (ex. lint-style warnings). These don't fail formatting.
+  async fn check(&self, _request: CheckRequest<Self::Configuration>) -> CheckResult {
+    Ok(Vec::new())
+  }
 }
 
 /// Trait for implementing a Wasm plugin.
@@ -254,4 +337,36 @@ pub trait SyncPluginHandler<TConfiguration: Clone + serde::Serialize> {
   fn check_config_updates(&self, message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>>;
   /// Formats the provided file text based on the provided file path and configuration.
   fn format(&mut self, request: SyncFormatRequest<TConfiguration>, format_with_host: impl FnMut(SyncHostFormatRequest) -> FormatResult) -> FormatResult;
+  /// Checks the provided file text for problems the plugin won't fix when
+  /// formatting (ex. lint-style warnings). These don't fail formatting.
+  fn check(&mut self, _request: SyncCheckRequest<TConfiguration>) -> CheckResult {
+    Ok(Vec::new())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn adds_inherited_values_not_otherwise_configured() {
+    let hints = HostFormatHints {
+      language: Some("ts".to_string()),
+      line_width: Some(40),
+      indent_width: Some(4),
+      use_tabs: Some(true),
+    };
+    let mut override_config = ConfigKeyMap::new();
+    override_config.insert("indentWidth".to_string(), ConfigKeyValue::from_i32(2));
+    let mut plugin_config = ConfigKeyMap::new();
+    plugin_config.insert("useTabs".to_string(), ConfigKeyValue::from_bool(false));
+    hints.add_inherited_to_override_config(&mut override_config, &plugin_config);
+
+    let mut expected = ConfigKeyMap::new();
+    expected.insert("indentWidth".to_string(), ConfigKeyValue::from_i32(2));
+    expected.insert("lineWidth".to_string(), ConfigKeyValue::from_i32(40));
+    assert_eq!(override_config, expected);
+    assert!(!hints.is_empty());
+    assert!(HostFormatHints::default().is_empty());
+  }
 }
@@ -20,14 +20,22 @@ use std::time::Duration;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 
+use super::messages::create_format_chunk_messages;
+use super::messages::CheckChunkedMessageBody;
 use super::messages::CheckConfigUpdatesMessageBody;
 use super::messages::CheckConfigUpdatesResponseBody;
+use super::messages::CheckMessageBody;
+use super::messages::ChunkedMessageAssembler;
+use super::messages::FormatChunkedMessageBody;
 use super::messages::FormatMessageBody;
 use super::messages::HostFormatMessageBody;
 use super::messages::MessageBody;
+use super::messages::PluginInfoCapabilities;
+use super::messages::ProcessPluginCapabilities;
 use super::messages::ProcessPluginMessage;
 use super::messages::RegisterConfigMessageBody;
 use super::messages::ResponseBody;
+use super::messages::FORMAT_CHUNK_SIZE;
 use super::PLUGIN_SCHEMA_VERSION;
 use crate::async_runtime::DropGuardAction;
 use crate::async_runtime::LocalBoxFuture;
@@ -40,10 +48,12 @@ use crate::communication::SingleThreadMessageWriter;
 use crate::configuration::ConfigKeyMap;
 use crate::configuration::ConfigurationDiagnostic;
 use crate::configuration::GlobalConfiguration;
+use crate::plugins::CheckResult;
 use crate::plugins::ConfigChange;
 use crate::plugins::CriticalFormatError;
 use crate::plugins::FileMatchingInfo;
 use crate::plugins::FormatConfigId;
+use crate::plugins::FormatDiagnostic;
 use crate::plugins::FormatRange;
 use crate::plugins::FormatResult;
 use crate::plugins::HostFormatRequest;
@@ -64,6 +74,13 @@ pub struct ProcessPluginCommunicatorFormatRequest {
   pub token: DprintCancellationToken,
 }
 
+pub struct ProcessPluginCommunicatorCheckRequest {
+  pub file_path: PathBuf,
+  pub file_bytes: Vec<u8>,
+  pub config_id: FormatConfigId,
+  pub token: DprintCancellationToken,
+}
+
 enum MessageResponseChannel {
   Acknowledgement(oneshot::Sender<Result<()>>),
   Data(oneshot::Sender<Result<Vec<u8>>>),
@@ -77,6 +94,7 @@ struct Context {
   messages: RcIdStore<MessageResponseChannel>,
   format_request_tokens: RcIdStore<Arc<CancellationToken>>,
   host_format_callbacks: RcIdStore<HostFormatCallback>,
+  capabilities: tokio::sync::OnceCell<ProcessPluginCapabilities>,
 }
 
 /// Communicates with a process plugin.
@@ -163,6 +181,7 @@ impl ProcessPluginCommunicator {
       messages: Default::default(),
       format_request_tokens: Default::default(),
       host_format_callbacks: Default::default(),
+      capabilities: Default::default(),
     });
 
     // read from stdout
@@ -171,13 +190,17 @@ impl ProcessPluginCommunicator {
       let shutdown_flag = context.shutdown_flag.clone();
       let on_std_err = on_std_err.clone();
       move || {
+        let mut chunked_message_assembler = ChunkedMessageAssembler::default();
         loop {
-          match ProcessPluginMessage::read(&mut stdout_reader) {
-            Ok(message) => {
+          match ProcessPluginMessage::read(&mut stdout_reader).and_then(|message| chunked_message_assembler.process(message)) {
+            Ok(Some(message)) => {
               if tx.send(message).is_err() {
                 break; // closed
               }
             }
+            Ok(None) => {
+              // waiting on more chunks
+            }
             Err(err) if err.kind() == ErrorKind::BrokenPipe => {
               break;
             }
@@ -289,18 +312,37 @@ impl ProcessPluginCommunicator {
   pub async fn format_text(&self, request: ProcessPluginCommunicatorFormatRequest) -> FormatResult {
     let (tx, rx) = oneshot::channel::<Result<Option<Vec<u8>>>>();
 
+    let capabilities = self.capabilities().await;
+    let is_chunked = request.file_bytes.len() > FORMAT_CHUNK_SIZE && capabilities.chunked_format;
     let message_id = self.context.id_generator.next();
     let store_guard = self.context.host_format_callbacks.store_with_guard(message_id, request.on_host_format);
+    let override_config = serde_json::to_vec(&request.override_config).unwrap();
+    let (body, chunk_messages) = if is_chunked {
+      let body = MessageBody::FormatChunked(FormatChunkedMessageBody {
+        file_path: request.file_path,
+        range: request.range,
+        config_id: request.config_id,
+        override_config,
+        file_bytes_len: request.file_bytes.len() as u32,
+      });
+      let chunk_messages = create_format_chunk_messages(&self.context.id_generator, message_id, Arc::new(request.file_bytes));
+      (body, Some(chunk_messages))
+    } else {
+      let body = MessageBody::Format(FormatMessageBody {
+        file_path: request.file_path,
+        file_bytes: request.file_bytes,
+        range: request.range,
+        config_id: request.config_id,
+        override_config,
+        chunked: false,
+      });
+      (body, None)
+    };
     let maybe_result = self
       .send_message_with_id(
         message_id,
-        MessageBody::Format(FormatMessageBody {
-          file_path: request.file_path,
-          file_bytes: request.file_bytes,
-          range: request.range,
-          config_id: request.config_id,
-          override_config: serde_json::to_vec(&request.override_config).unwrap(),
-        }),
+        body,
+        chunk_messages.into_iter().flatten(),
         MessageResponseChannel::Format(tx),
         rx,
         request.token.clone(),
@@ -319,6 +361,73 @@ impl ProcessPluginCommunicator {
     }
   }
 
+  /// Checks the file with the plugin. Plugins that don't
+  /// support checking will always return no diagnostics.
+  pub async fn check_text(&self, request: ProcessPluginCommunicatorCheckRequest) -> CheckResult {
+    let capabilities = self.capabilities().await;
+    if !capabilities.check {
+      return Ok(Vec::new());
+    }
+    let (tx, rx) = oneshot::channel::<Result<Vec<u8>>>();
+    let is_chunked = request.file_bytes.len() > FORMAT_CHUNK_SIZE && capabilities.chunked_format;
+    let message_id = self.context.id_generator.next();
+    let (body, chunk_messages) = if is_chunked {
+      let body = MessageBody::CheckChunked(CheckChunkedMessageBody {
+        file_path: request.file_path,
+        config_id: request.config_id,
+        file_bytes_len: request.file_bytes.len() as u32,
+      });
+      let chunk_messages = create_format_chunk_messages(&self.context.id_generator, message_id, Arc::new(request.file_bytes));
+      (body, Some(chunk_messages))
+    } else {
+      let body = MessageBody::Check(CheckMessageBody {
+        file_path: request.file_path,
+        config_id: request.config_id,
+        file_bytes: request.file_bytes,
+      });
+      (body, None)
+    };
+    let data = self
+      .send_message_with_id(
+        message_id,
+        body,
+        chunk_messages.into_iter().flatten(),
+        MessageResponseChannel::Data(tx),
+        rx,
+        request.token.clone(),
+      )
+      .await??;
+    if request.token.is_cancelled() {
+      Ok(Vec::new())
+    } else {
+      Ok(serde_json::from_slice(&data)?)
+    }
+  }
+
+  /// Gets the protocol capabilities of the plugin, which are requested
+  /// once on first use and shared by any concurrent requests.
+  async fn capabilities(&self) -> ProcessPluginCapabilities {
+    self
+      .context
+      .capabilities
+      .get_or_init(|| async {
+        let mut capabilities = self
+          .send_receiving_data::<PluginInfoCapabilities>(MessageBody::GetPluginInfo)
+          .await
+          .map(|info| info.capabilities)
+          .unwrap_or_default();
+        if capabilities.format_diagnostics {
+          capabilities.format_diagnostics = self.send_with_acknowledgement(MessageBody::EnableFormatDiagnostics).await.is_ok();
+        }
+        if capabilities.host_format_hints {
+          capabilities.host_format_hints = self.send_with_acknowledgement(MessageBody::EnableHostFormatHints).await.is_ok();
+        }
+        capabilities
+      })
+      .await
+      .clone()
+  }
+
   /// Checks if the process is functioning.
   pub async fn is_process_alive(&self) -> bool {
     if self.context.shutdown_flag.is_raised() {
@@ -360,13 +469,16 @@ impl ProcessPluginCommunicator {
     token: Arc<dyn super::super::CancellationToken>,
   ) -> Result<Result<T>> {
     let message_id = self.context.id_generator.next();
-    self.send_message_with_id(message_id, body, response_channel, receiver, token).await
+    self
+      .send_message_with_id(message_id, body, std::iter::empty(), response_channel, receiver, token)
+      .await
   }
 
   async fn send_message_with_id<T: Default>(
     &self,
     message_id: u32,
     body: MessageBody,
+    additional_messages: impl IntoIterator<Item = ProcessPluginMessage>,
     response_channel: MessageResponseChannel,
     receiver: oneshot::Receiver<Result<T>>,
     token: Arc<dyn super::super::CancellationToken>,
@@ -383,6 +495,9 @@ impl ProcessPluginCommunicator {
 
     self.context.messages.store(message_id, response_channel);
     self.context.stdin_writer.send(ProcessPluginMessage { id: message_id, body })?;
+    for message in additional_messages {
+      self.context.stdin_writer.send(message)?;
+    }
     tokio::select! {
       _ = token.wait_cancellation() => {
         drop(drop_guard); // explicit
@@ -469,6 +584,24 @@ fn handle_stdout_message(message: ProcessPluginMessage, context: &Rc<Context>) -
         None => {}
       }
     }
+    MessageBody::FormatDiagnostic(response) => {
+      let err = match serde_json::from_slice::<FormatDiagnostic>(&response.data) {
+        Ok(diagnostic) => diagnostic.into(),
+        Err(err) => anyhow!("Failed deserializing format diagnostic. {:#}", err),
+      };
+      match context.messages.take(response.message_id) {
+        Some(MessageResponseChannel::Acknowledgement(channel)) => {
+          let _ignore = channel.send(Err(err));
+        }
+        Some(MessageResponseChannel::Data(channel)) => {
+          let _ignore = channel.send(Err(err));
+        }
+        Some(MessageResponseChannel::Format(channel)) => {
+          let _ignore = channel.send(Err(err));
+        }
+        None => {}
+      }
+    }
     MessageBody::FormatResponse(response) => match context.messages.take(response.message_id) {
       Some(MessageResponseChannel::Acknowledgement(channel)) => {
         let _ignore = channel.send(Err(anyhow!("Unexpected success channel for format response: {}", response.message_id)));
@@ -504,10 +637,19 @@ fn handle_stdout_message(message: ProcessPluginMessage, context: &Rc<Context>) -
               message_id: message.id,
               data: result,
             }),
-            Err(err) => MessageBody::Error(ResponseBody {
-              message_id: message.id,
-              data: format!("{:#}", err).into_bytes(),
-            }),
+            Err(err) => {
+              let supports_diagnostics = context.capabilities.get().map(|c| c.format_diagnostics).unwrap_or(false);
+              match FormatDiagnostic::from_error(&err).filter(|_| supports_diagnostics) {
+                Some(diagnostic) => MessageBody::FormatDiagnostic(ResponseBody {
+                  message_id: message.id,
+                  data: serde_json::to_vec(diagnostic).unwrap(),
+                }),
+                None => MessageBody::Error(ResponseBody {
+                  message_id: message.id,
+                  data: format!("{:#}", err).into_bytes(),
+                }),
+              }
+            }
           },
         });
       });
@@ -520,6 +662,13 @@ fn handle_stdout_message(message: ProcessPluginMessage, context: &Rc<Context>) -
       });
     }
     MessageBody::Format(_)
+    | MessageBody::FormatChunked(_)
+    | MessageBody::FormatChunk(_)
+    | MessageBody::ChunkedFormatResponse(_)
+    | MessageBody::EnableFormatDiagnostics
+    | MessageBody::Check(_)
+    | MessageBody::CheckChunked(_)
+    | MessageBody::EnableHostFormatHints
     | MessageBody::Close
     | MessageBody::GetPluginInfo
     | MessageBody::GetLicenseText
@@ -559,6 +708,7 @@ async fn host_format(context: Rc<Context>, message_id: u32, body: HostFormatMess
     file_bytes: body.file_text,
     range: body.range,
     override_config: serde_json::from_slice(&body.override_config).unwrap(),
+    hints: body.hints.unwrap_or_default(),
     token,
   })
   .await;
@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -31,6 +32,10 @@ pub struct ProcessContext<TConfiguration: Serialize + Clone> {
   pub cancellation_tokens: RcIdStore<Arc<CancellationToken>>,
   pub format_host_senders: RcIdStore<FormatHostSender>,
   pub stdout_writer: Rc<SingleThreadMessageWriter<ProcessPluginMessage>>,
+  /// If the CLI supports receiving format diagnostics.
+  pub format_diagnostics_enabled: Cell<bool>,
+  /// If the CLI supports receiving host format requests with hints.
+  pub host_format_hints_enabled: Cell<bool>,
 }
 
 impl<TConfiguration: Serialize + Clone> ProcessContext<TConfiguration> {
@@ -41,6 +46,8 @@ impl<TConfiguration: Serialize + Clone> ProcessContext<TConfiguration> {
       cancellation_tokens: Default::default(),
       format_host_senders: Default::default(),
       stdout_writer: Rc::new(stdout_writer),
+      format_diagnostics_enabled: Default::default(),
+      host_format_hints_enabled: Default::default(),
     }
   }
 }
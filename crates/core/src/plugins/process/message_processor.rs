@@ -11,12 +11,17 @@ use tokio_util::sync::CancellationToken;
 
 use super::context::ProcessContext;
 use super::context::StoredConfig;
+use super::messages::create_format_chunk_messages;
 use super::messages::CheckConfigUpdatesMessageBody;
 use super::messages::CheckConfigUpdatesResponseBody;
+use super::messages::ChunkedMessageAssembler;
 use super::messages::HostFormatMessageBody;
 use super::messages::MessageBody;
+use super::messages::PluginInfoWithCapabilities;
+use super::messages::ProcessPluginCapabilities;
 use super::messages::ProcessPluginMessage;
 use super::messages::ResponseBody;
+use super::messages::FORMAT_CHUNK_SIZE;
 use super::utils::setup_exit_process_panic_hook;
 use super::PLUGIN_SCHEMA_VERSION;
 
@@ -27,7 +32,10 @@ use crate::communication::MessageWriter;
 use crate::communication::SingleThreadMessageWriter;
 use crate::configuration::ConfigKeyMap;
 use crate::configuration::GlobalConfiguration;
+use crate::configuration::WhitespaceOptions;
 use crate::plugins::AsyncPluginHandler;
+use crate::plugins::CheckRequest;
+use crate::plugins::FormatDiagnostic;
 use crate::plugins::FormatRequest;
 use crate::plugins::FormatResult;
 use crate::plugins::HostFormatRequest;
@@ -49,14 +57,22 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
 
   // now start reading messages
   let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<std::io::Result<ProcessPluginMessage>>();
-  crate::async_runtime::spawn_blocking(move || loop {
-    let message_result = ProcessPluginMessage::read(&mut stdin_reader);
-    let is_err = message_result.is_err();
-    if tx.send(message_result).is_err() {
-      return; // disconnected
-    }
-    if is_err {
-      return; // shut down
+  crate::async_runtime::spawn_blocking(move || {
+    let mut chunked_message_assembler = ChunkedMessageAssembler::default();
+    loop {
+      let message_result = ProcessPluginMessage::read(&mut stdin_reader).and_then(|message| chunked_message_assembler.process(message));
+      let message_result = match message_result {
+        Ok(Some(message)) => Ok(message),
+        Ok(None) => continue, // waiting on more chunks
+        Err(err) => Err(err),
+      };
+      let is_err = message_result.is_err();
+      if tx.send(message_result).is_err() {
+        return; // disconnected
+      }
+      if is_err {
+        return; // shut down
+      }
     }
   });
 
@@ -83,7 +99,15 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
         MessageBody::GetPluginInfo => {
           handle_message(&context, message.id, || {
             let plugin_info = handler.plugin_info();
-            let data = serde_json::to_vec(&plugin_info)?;
+            let data = serde_json::to_vec(&PluginInfoWithCapabilities {
+              info: &plugin_info,
+              capabilities: ProcessPluginCapabilities {
+                chunked_format: true,
+                format_diagnostics: true,
+                check: true,
+                host_format_hints: true,
+              },
+            })?;
             Ok(MessageBody::DataResponse(ResponseBody { message_id: message.id, data }))
           });
         }
@@ -169,32 +193,31 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
           .await;
         }
         MessageBody::Format(body) => {
+          let chunked = body.chunked;
           // now parse
           let token = Arc::new(CancellationToken::new());
+          let Some(stored_config) = context.configs.get_cloned(body.config_id.as_raw()) else {
+            send_error_response(&context, message.id, anyhow!("Did not find configuration for id: {}", body.config_id));
+            continue;
+          };
+          let global_config = stored_config.global_config.clone();
           let request = FormatRequest {
             file_path: body.file_path,
             range: body.range,
             config_id: body.config_id,
-            config: match context.configs.get_cloned(body.config_id.as_raw()) {
-              Some(config) => {
-                if body.override_config.is_empty() {
-                  config.config.clone()
-                } else {
-                  let mut config_map = config.config_map.clone();
-                  let override_config_map: ConfigKeyMap = serde_json::from_slice(&body.override_config)?;
-                  for (key, value) in override_config_map {
-                    config_map.insert(key, value);
-                  }
-                  let result = handler.resolve_config(config_map, config.global_config.clone()).await;
-                  Arc::new(result.config)
-                }
-              }
-              None => {
-                send_error_response(&context, message.id, anyhow!("Did not find configuration for id: {}", body.config_id));
-                continue;
+            config: if body.override_config.is_empty() {
+              stored_config.config.clone()
+            } else {
+              let mut config_map = stored_config.config_map.clone();
+              let override_config_map: ConfigKeyMap = serde_json::from_slice(&body.override_config)?;
+              for (key, value) in override_config_map {
+                config_map.insert(key, value);
               }
+              let result = handler.resolve_config(config_map, global_config.clone()).await;
+              Arc::new(result.config)
             },
             file_bytes: body.file_bytes,
+            whitespace: WhitespaceOptions::from(&global_config),
             token: token.clone(),
           };
 
@@ -212,17 +235,51 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
               .await;
             drop(token_storage_guard);
             if !token.is_cancelled() {
-              let body = match result {
-                Ok(text) => MessageBody::FormatResponse(ResponseBody {
-                  message_id: message.id,
-                  data: text,
-                }),
-                Err(err) => MessageBody::Error(ResponseBody {
-                  message_id: message.id,
-                  data: format!("{:#}", err).into_bytes(),
-                }),
-              };
-              send_response_body(&context, body)
+              match result {
+                Ok(Some(text)) if chunked && text.len() > FORMAT_CHUNK_SIZE => {
+                  send_chunked_format_response(&context, message.id, text);
+                }
+                Ok(text) => send_response_body(
+                  &context,
+                  MessageBody::FormatResponse(ResponseBody {
+                    message_id: message.id,
+                    data: text,
+                  }),
+                ),
+                Err(err) => send_format_error_response(&context, message.id, err),
+              }
+            }
+          });
+        }
+        MessageBody::Check(body) => {
+          let config = match context.configs.get_cloned(body.config_id.as_raw()) {
+            Some(config) => config.config.clone(),
+            None => {
+              send_error_response(&context, message.id, anyhow!("Did not find configuration for id: {}", body.config_id));
+              continue;
+            }
+          };
+          let token = Arc::new(CancellationToken::new());
+          let request = CheckRequest {
+            file_path: body.file_path,
+            file_bytes: body.file_bytes,
+            config_id: body.config_id,
+            config,
+            token: token.clone(),
+          };
+
+          // start the task
+          let context = context.clone();
+          let handler = handler.clone();
+          let token_storage_guard = context.cancellation_tokens.store_with_owned_guard(message.id, token.clone());
+          crate::async_runtime::spawn(async move {
+            let result = handler.check(request).await;
+            drop(token_storage_guard);
+            if !token.is_cancelled() {
+              handle_message(&context, message.id, || {
+                let data = serde_json::to_vec(&result?)?;
+                Ok(MessageBody::DataResponse(ResponseBody { message_id: message.id, data }))
+              });
             }
           });
         }
@@ -242,6 +299,23 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
             }
           }
         }
+        MessageBody::FormatDiagnostic(body) => {
+          if let Some(sender) = context.format_host_senders.take(body.message_id) {
+            let err = match serde_json::from_slice::<FormatDiagnostic>(&body.data) {
+              Ok(diagnostic) => diagnostic.into(),
+              Err(err) => anyhow!("Failed deserializing format diagnostic. {:#}", err),
+            };
+            sender.send(Err(err)).unwrap();
+          }
+        }
+        MessageBody::EnableFormatDiagnostics => {
+          context.format_diagnostics_enabled.set(true);
+          send_response_body(&context, MessageBody::Success(message.id));
+        }
+        MessageBody::EnableHostFormatHints => {
+          context.host_format_hints_enabled.set(true);
+          send_response_body(&context, MessageBody::Success(message.id));
+        }
         MessageBody::FormatResponse(body) => {
           if let Some(sender) = context.format_host_senders.take(body.message_id) {
             sender.send(Ok(body.data)).unwrap();
@@ -253,6 +327,9 @@ pub async fn handle_process_stdio_messages<THandler: AsyncPluginHandler>(handler
         MessageBody::HostFormat(_) => {
           send_error_response(&context, message.id, anyhow!("Cannot host format with a plugin."));
         }
+        MessageBody::FormatChunked(_) | MessageBody::FormatChunk(_) | MessageBody::ChunkedFormatResponse(_) | MessageBody::CheckChunked(_) => {
+          unreachable!("chunked messages are reassembled when read")
+        }
         MessageBody::Unknown(message_kind) => panic!("Received unknown message kind: {}", message_kind),
       }
     }
@@ -270,6 +347,17 @@ fn host_format<TConfiguration: Serialize + Clone + Send + Sync>(
   let id = context.id_generator.next();
   context.format_host_senders.store(id, tx);
 
+  let mut override_config = request.override_config;
+  let hints = if request.hints.is_empty() {
+    None
+  } else if context.host_format_hints_enabled.get() {
+    Some(request.hints)
+  } else {
+    // older CLI, so fall back to providing the inherited values as configuration
+    request.hints.add_inherited_to_override_config(&mut override_config, &Default::default());
+    None
+  };
+
   context
     .stdout_writer
     .send(ProcessPluginMessage {
@@ -279,7 +367,8 @@ fn host_format<TConfiguration: Serialize + Clone + Send + Sync>(
         file_path: request.file_path,
         file_text: request.file_bytes,
         range: request.range,
-        override_config: serde_json::to_vec(&request.override_config).unwrap(),
+        override_config: serde_json::to_vec(&override_config).unwrap(),
+        hints,
       }),
     })
     .unwrap_or_else(|err| panic!("Error sending host format response: {:#}", err));
@@ -349,6 +438,51 @@ fn send_error_response<TConfiguration: Serialize + Clone + Send + Sync>(
   send_response_body(context, body)
 }
 
+/// Sends a format diagnostic when the error is one and the CLI
+/// supports it, otherwise sends the error's text.
+fn send_format_error_response<TConfiguration: Serialize + Clone + Send + Sync>(
+  context: &ProcessContext<TConfiguration>,
+  original_message_id: u32,
+  err: anyhow::Error,
+) {
+  let diagnostic = if context.format_diagnostics_enabled.get() {
+    FormatDiagnostic::from_error(&err)
+  } else {
+    None
+  };
+  match diagnostic {
+    Some(diagnostic) => send_response_body(
+      context,
+      MessageBody::FormatDiagnostic(ResponseBody {
+        message_id: original_message_id,
+        data: serde_json::to_vec(diagnostic).unwrap(),
+      }),
+    ),
+    None => send_error_response(context, original_message_id, err),
+  }
+}
+
+fn send_chunked_format_response<TConfiguration: Serialize + Clone + Send + Sync>(
+  context: &ProcessContext<TConfiguration>,
+  original_message_id: u32,
+  data: Vec<u8>,
+) {
+  let id = context.id_generator.next();
+  let header = ProcessPluginMessage {
+    id,
+    body: MessageBody::ChunkedFormatResponse(ResponseBody {
+      message_id: original_message_id,
+      data: data.len() as u32,
+    }),
+  };
+  let chunk_messages = create_format_chunk_messages(&context.id_generator, id, Arc::new(data));
+  for message in std::iter::once(header).chain(chunk_messages) {
+    if let Err(err) = context.stdout_writer.send(message) {
+      panic!("Receiver dropped. {:#}", err);
+    }
+  }
+}
+
 fn send_response_body<TConfiguration: Serialize + Clone + Send + Sync>(context: &ProcessContext<TConfiguration>, body: MessageBody) {
   let message = ProcessPluginMessage {
     id: context.id_generator.next(),
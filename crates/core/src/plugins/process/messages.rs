@@ -1,16 +1,21 @@
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::io::Read;
 use std::io::Result;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::communication::IdGenerator;
 use crate::communication::Message;
 use crate::plugins::ConfigChange;
 use crate::plugins::FormatConfigId;
 use crate::plugins::FormatRange;
+use crate::plugins::HostFormatHints;
+use crate::plugins::PluginInfo;
 
 use crate::communication::MessageReader;
 use crate::communication::MessageWriter;
@@ -37,8 +42,19 @@ mod message_ids {
   pub const FORMAT_RESPONSE_ID: MessageId = 14;
   pub const CANCEL_FORMAT_ID: MessageId = 15;
   pub const HOST_FORMAT_ID: MessageId = 16;
+  pub const FORMAT_CHUNKED_ID: MessageId = 17;
+  pub const FORMAT_CHUNK_ID: MessageId = 18;
+  pub const ENABLE_FORMAT_DIAGNOSTICS_ID: MessageId = 19;
+  pub const FORMAT_DIAGNOSTIC_ID: MessageId = 20;
+  pub const CHECK_ID: MessageId = 21;
+  pub const ENABLE_HOST_FORMAT_HINTS_ID: MessageId = 22;
+  pub const HOST_FORMAT_WITH_HINTS_ID: MessageId = 23;
+  pub const CHECK_CHUNKED_ID: MessageId = 24;
 }
 
+/// The size of each chunk when sending file bytes in chunks.
+pub const FORMAT_CHUNK_SIZE: usize = 1024 * 1024;
+
 #[derive(Debug)]
 pub struct ProcessPluginMessage {
   pub id: MessageId,
@@ -92,55 +108,102 @@ impl ProcessPluginMessage {
         let file_text = reader.read_sized_bytes()?;
         MessageBody::Format(FormatMessageBody {
           file_path: PathBuf::from(String::from_utf8_lossy(&file_path).to_string()),
-          range: if start_byte_index == 0 && end_byte_index == file_text.len() as u32 {
-            None
-          } else {
-            Some(std::ops::Range {
-              start: start_byte_index as usize,
-              end: end_byte_index as usize,
-            })
-          },
+          range: get_format_range(start_byte_index, end_byte_index, file_text.len()),
           config_id,
           file_bytes: file_text,
           override_config,
+          chunked: false,
         })
       }
       message_ids::FORMAT_RESPONSE_ID => {
         let message_id = reader.read_u32()?;
         let response_kind = reader.read_u32()?;
-        let data = match response_kind {
-          0 => None,
-          1 => Some(reader.read_sized_bytes()?),
+        match response_kind {
+          0 => MessageBody::FormatResponse(ResponseBody { message_id, data: None }),
+          1 => MessageBody::FormatResponse(ResponseBody {
+            message_id,
+            data: Some(reader.read_sized_bytes()?),
+          }),
+          2 => MessageBody::ChunkedFormatResponse(ResponseBody {
+            message_id,
+            data: reader.read_u32()?,
+          }),
           _ => {
             return Err(std::io::Error::new(
               ErrorKind::InvalidData,
               format!("Unknown format response kind: {}", response_kind),
             ))
           }
-        };
-        MessageBody::FormatResponse(ResponseBody { message_id, data })
+        }
       }
       message_ids::CANCEL_FORMAT_ID => MessageBody::CancelFormat(reader.read_u32()?),
-      message_ids::HOST_FORMAT_ID => {
+      message_ids::HOST_FORMAT_ID | message_ids::HOST_FORMAT_WITH_HINTS_ID => {
         let original_message_id = reader.read_u32()?;
         let file_path = reader.read_sized_bytes()?;
         let start_byte_index = reader.read_u32()?;
         let end_byte_index = reader.read_u32()?;
         let override_config = reader.read_sized_bytes()?;
+        let hints = if message_kind == message_ids::HOST_FORMAT_WITH_HINTS_ID {
+          let hints = reader.read_sized_bytes()?;
+          Some(serde_json::from_slice(&hints).map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))?)
+        } else {
+          None
+        };
         let file_text = reader.read_sized_bytes()?;
         MessageBody::HostFormat(HostFormatMessageBody {
           original_message_id,
           file_path: PathBuf::from(String::from_utf8_lossy(&file_path).to_string()),
-          range: if start_byte_index == 0 && end_byte_index == file_text.len() as u32 {
-            None
-          } else {
-            Some(std::ops::Range {
-              start: start_byte_index as usize,
-              end: end_byte_index as usize,
-            })
-          },
+          range: get_format_range(start_byte_index, end_byte_index, file_text.len()),
           file_text,
           override_config,
+          hints,
+        })
+      }
+      message_ids::ENABLE_HOST_FORMAT_HINTS_ID => MessageBody::EnableHostFormatHints,
+      message_ids::FORMAT_CHUNKED_ID => {
+        let file_path = reader.read_sized_bytes()?;
+        let start_byte_index = reader.read_u32()?;
+        let end_byte_index = reader.read_u32()?;
+        let config_id = FormatConfigId::from_raw(reader.read_u32()?);
+        let override_config = reader.read_sized_bytes()?;
+        let file_bytes_len = reader.read_u32()?;
+        MessageBody::FormatChunked(FormatChunkedMessageBody {
+          file_path: PathBuf::from(String::from_utf8_lossy(&file_path).to_string()),
+          range: get_format_range(start_byte_index, end_byte_index, file_bytes_len as usize),
+          config_id,
+          override_config,
+          file_bytes_len,
+        })
+      }
+      message_ids::FORMAT_CHUNK_ID => {
+        let message_id = reader.read_u32()?;
+        let data = FormatChunkBytes::from(reader.read_sized_bytes()?);
+        MessageBody::FormatChunk(ResponseBody { message_id, data })
+      }
+      message_ids::ENABLE_FORMAT_DIAGNOSTICS_ID => MessageBody::EnableFormatDiagnostics,
+      message_ids::FORMAT_DIAGNOSTIC_ID => {
+        let message_id = reader.read_u32()?;
+        let data = reader.read_sized_bytes()?;
+        MessageBody::FormatDiagnostic(ResponseBody { message_id, data })
+      }
+      message_ids::CHECK_ID => {
+        let file_path = reader.read_sized_bytes()?;
+        let config_id = FormatConfigId::from_raw(reader.read_u32()?);
+        let file_bytes = reader.read_sized_bytes()?;
+        MessageBody::Check(CheckMessageBody {
+          file_path: PathBuf::from(String::from_utf8_lossy(&file_path).to_string()),
+          config_id,
+          file_bytes,
+        })
+      }
+      message_ids::CHECK_CHUNKED_ID => {
+        let file_path = reader.read_sized_bytes()?;
+        let config_id = FormatConfigId::from_raw(reader.read_u32()?);
+        let file_bytes_len = reader.read_u32()?;
+        MessageBody::CheckChunked(CheckChunkedMessageBody {
+          file_path: PathBuf::from(String::from_utf8_lossy(&file_path).to_string()),
+          config_id,
+          file_bytes_len,
         })
       }
       _ => {
@@ -240,15 +303,64 @@ impl Message for ProcessPluginMessage {
         writer.send_u32(message_ids::CANCEL_FORMAT_ID)?;
         writer.send_u32(*message_id)?;
       }
+      MessageBody::ChunkedFormatResponse(response) => {
+        writer.send_u32(message_ids::FORMAT_RESPONSE_ID)?;
+        writer.send_u32(response.message_id)?;
+        writer.send_u32(2)?;
+        writer.send_u32(response.data)?;
+      }
+      MessageBody::FormatChunked(body) => {
+        writer.send_u32(message_ids::FORMAT_CHUNKED_ID)?;
+        writer.send_sized_bytes(body.file_path.to_string_lossy().as_bytes())?;
+        writer.send_u32(body.range.as_ref().map(|r| r.start).unwrap_or(0) as u32)?;
+        writer.send_u32(body.range.as_ref().map(|r| r.end as u32).unwrap_or(body.file_bytes_len))?;
+        writer.send_u32(body.config_id.as_raw())?;
+        writer.send_sized_bytes(&body.override_config)?;
+        writer.send_u32(body.file_bytes_len)?;
+      }
+      MessageBody::FormatChunk(body) => {
+        writer.send_u32(message_ids::FORMAT_CHUNK_ID)?;
+        writer.send_u32(body.message_id)?;
+        writer.send_sized_bytes(body.data.as_slice())?;
+      }
+      MessageBody::EnableFormatDiagnostics => {
+        writer.send_u32(message_ids::ENABLE_FORMAT_DIAGNOSTICS_ID)?;
+      }
+      MessageBody::FormatDiagnostic(response) => {
+        writer.send_u32(message_ids::FORMAT_DIAGNOSTIC_ID)?;
+        writer.send_u32(response.message_id)?;
+        writer.send_sized_bytes(&response.data)?;
+      }
+      MessageBody::Check(body) => {
+        writer.send_u32(message_ids::CHECK_ID)?;
+        writer.send_sized_bytes(body.file_path.to_string_lossy().as_bytes())?;
+        writer.send_u32(body.config_id.as_raw())?;
+        writer.send_sized_bytes(&body.file_bytes)?;
+      }
+      MessageBody::CheckChunked(body) => {
+        writer.send_u32(message_ids::CHECK_CHUNKED_ID)?;
+        writer.send_sized_bytes(body.file_path.to_string_lossy().as_bytes())?;
+        writer.send_u32(body.config_id.as_raw())?;
+        writer.send_u32(body.file_bytes_len)?;
+      }
       MessageBody::HostFormat(body) => {
-        writer.send_u32(message_ids::HOST_FORMAT_ID)?;
+        writer.send_u32(match body.hints {
+          Some(_) => message_ids::HOST_FORMAT_WITH_HINTS_ID,
+          None => message_ids::HOST_FORMAT_ID,
+        })?;
         writer.send_u32(body.original_message_id)?;
         writer.send_sized_bytes(body.file_path.to_string_lossy().as_bytes())?;
         writer.send_u32(body.range.as_ref().map(|r| r.start).unwrap_or(0) as u32)?;
         writer.send_u32(body.range.as_ref().map(|r| r.end).unwrap_or(body.file_text.len()) as u32)?;
         writer.send_sized_bytes(&body.override_config)?;
+        if let Some(hints) = &body.hints {
+          writer.send_sized_bytes(&serde_json::to_vec(hints)?)?;
+        }
         writer.send_sized_bytes(&body.file_text)?;
       }
+      MessageBody::EnableHostFormatHints => {
+        writer.send_u32(message_ids::ENABLE_HOST_FORMAT_HINTS_ID)?;
+      }
       MessageBody::Unknown(_) => unreachable!(), // should never be written
     }
     writer.send_success_bytes()?;
@@ -273,8 +385,31 @@ pub enum MessageBody {
   CheckConfigUpdates(Vec<u8>),
   Format(FormatMessageBody),
   FormatResponse(ResponseBody<Option<Vec<u8>>>),
+  /// A format response whose formatted bytes (of the provided length)
+  /// will be sent in subsequent `FormatChunk` messages.
+  ChunkedFormatResponse(ResponseBody<u32>),
   CancelFormat(MessageId),
   HostFormat(HostFormatMessageBody),
+  /// A format request whose file bytes will be sent in
+  /// subsequent `FormatChunk` messages.
+  FormatChunked(FormatChunkedMessageBody),
+  /// A chunk of the bytes for the chunked format request
+  /// or response with the provided message id.
+  FormatChunk(ResponseBody<FormatChunkBytes>),
+  /// Tells the plugin it may respond to format requests
+  /// with `FormatDiagnostic` messages.
+  EnableFormatDiagnostics,
+  /// An error response to a format request containing a
+  /// JSON serialized `FormatDiagnostic`.
+  FormatDiagnostic(ResponseBody<Vec<u8>>),
+  /// Requests the plugin check a file. Responded to with a data
+  /// response containing a JSON serialized `Vec<FormatDiagnostic>`.
+  Check(CheckMessageBody),
+  /// Same as `Check`, but with the file bytes following
+  /// in `FormatChunk` messages.
+  CheckChunked(CheckChunkedMessageBody),
+  /// Tells the plugin it may send host format requests with hints.
+  EnableHostFormatHints,
   /// If encountered, process plugin should panic and
   /// the CLI should kill the process plugin.
   Unknown(u32),
@@ -308,6 +443,32 @@ pub struct FormatMessageBody {
   pub config_id: FormatConfigId,
   pub override_config: Vec<u8>,
   pub file_bytes: Vec<u8>,
+  /// If the file bytes were sent in chunks, which means the
+  /// sender also supports receiving a chunked response.
+  pub chunked: bool,
+}
+
+#[derive(Debug)]
+pub struct FormatChunkedMessageBody {
+  pub file_path: PathBuf,
+  pub range: FormatRange,
+  pub config_id: FormatConfigId,
+  pub override_config: Vec<u8>,
+  pub file_bytes_len: u32,
+}
+
+#[derive(Debug)]
+pub struct CheckMessageBody {
+  pub file_path: PathBuf,
+  pub config_id: FormatConfigId,
+  pub file_bytes: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct CheckChunkedMessageBody {
+  pub file_path: PathBuf,
+  pub config_id: FormatConfigId,
+  pub file_bytes_len: u32,
 }
 
 #[derive(Debug)]
@@ -317,4 +478,347 @@ pub struct HostFormatMessageBody {
   pub range: FormatRange,
   pub override_config: Vec<u8>,
   pub file_text: Vec<u8>,
+  /// Sent as a host format with hints message when set.
+  pub hints: Option<HostFormatHints>,
+}
+
+/// Protocol features that aren't supported by every plugin or
+/// CLI version. A plugin reports these along with its plugin info.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessPluginCapabilities {
+  /// If the plugin supports receiving format requests and
+  /// sending format responses in chunks.
+  #[serde(default)]
+  pub chunked_format: bool,
+  /// If the plugin supports the `EnableFormatDiagnostics` message
+  /// and sending and receiving format diagnostic responses.
+  #[serde(default)]
+  pub format_diagnostics: bool,
+  /// If the plugin supports the `Check` message.
+  #[serde(default)]
+  pub check: bool,
+  /// If the plugin supports the `EnableHostFormatHints` message
+  /// and sending host format requests with hints.
+  #[serde(default)]
+  pub host_format_hints: bool,
+}
+
+#[derive(Serialize)]
+pub struct PluginInfoWithCapabilities<'a> {
+  #[serde(flatten)]
+  pub info: &'a PluginInfo,
+  pub capabilities: ProcessPluginCapabilities,
+}
+
+#[derive(Deserialize)]
+pub struct PluginInfoCapabilities {
+  /// Older plugins won't have this set.
+  #[serde(default)]
+  pub capabilities: ProcessPluginCapabilities,
+}
+
+/// A chunk of file bytes, which when sending refers to a
+/// range of the buffer shared with the other chunks.
+#[derive(Debug, Clone)]
+pub struct FormatChunkBytes {
+  bytes: Arc<Vec<u8>>,
+  range: std::ops::Range<usize>,
+}
+
+impl FormatChunkBytes {
+  pub fn as_slice(&self) -> &[u8] {
+    &self.bytes[self.range.clone()]
+  }
+}
+
+impl From<Vec<u8>> for FormatChunkBytes {
+  fn from(bytes: Vec<u8>) -> Self {
+    let range = 0..bytes.len();
+    Self { bytes: Arc::new(bytes), range }
+  }
+}
+
+/// Lazily creates the messages that send the provided bytes in chunks for
+/// the chunked format request or response with the provided message id.
+pub fn create_format_chunk_messages(id_generator: &IdGenerator, message_id: MessageId, bytes: Arc<Vec<u8>>) -> impl Iterator<Item = ProcessPluginMessage> + '_ {
+  let len = bytes.len();
+  (0..len).step_by(FORMAT_CHUNK_SIZE).map(move |start| ProcessPluginMessage {
+    id: id_generator.next(),
+    body: MessageBody::FormatChunk(ResponseBody {
+      message_id,
+      data: FormatChunkBytes {
+        bytes: bytes.clone(),
+        range: start..std::cmp::min(start + FORMAT_CHUNK_SIZE, len),
+      },
+    }),
+  })
+}
+
+/// Reassembles chunked format requests and responses into regular
+/// `Format` and `FormatResponse` messages as they're read.
+#[derive(Default)]
+pub struct ChunkedMessageAssembler {
+  pending: HashMap<MessageId, PendingChunkedMessage>,
+}
+
+struct PendingChunkedMessage {
+  kind: PendingChunkedMessageKind,
+  total_len: usize,
+  data: Vec<u8>,
+}
+
+enum PendingChunkedMessageKind {
+  Format(FormatChunkedMessageBody),
+  Check(CheckChunkedMessageBody),
+  FormatResponse(MessageId),
+}
+
+impl ChunkedMessageAssembler {
+  /// Returns the message once it's complete.
+  pub fn process(&mut self, message: ProcessPluginMessage) -> Result<Option<ProcessPluginMessage>> {
+    match message.body {
+      MessageBody::FormatChunked(body) => {
+        let total_len = body.file_bytes_len as usize;
+        Ok(self.start(message.id, PendingChunkedMessageKind::Format(body), total_len))
+      }
+      MessageBody::CheckChunked(body) => {
+        let total_len = body.file_bytes_len as usize;
+        Ok(self.start(message.id, PendingChunkedMessageKind::Check(body), total_len))
+      }
+      MessageBody::ChunkedFormatResponse(body) => Ok(self.start(message.id, PendingChunkedMessageKind::FormatResponse(body.message_id), body.data as usize)),
+      MessageBody::FormatChunk(body) => {
+        let Some(pending) = self.pending.get_mut(&body.message_id) else {
+          // the format was cancelled
+          return Ok(None);
+        };
+        let data = body.data.as_slice();
+        if pending.data.len() + data.len() > pending.total_len {
+          return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Received more bytes than expected for chunked message: {}", body.message_id),
+          ));
+        }
+        pending.data.extend_from_slice(data);
+        if pending.data.len() == pending.total_len {
+          let pending = self.pending.remove(&body.message_id).unwrap();
+          Ok(Some(pending.into_message(body.message_id)))
+        } else {
+          Ok(None)
+        }
+      }
+      MessageBody::CancelFormat(message_id) => {
+        if matches!(
+          self.pending.get(&message_id),
+          Some(PendingChunkedMessage {
+            kind: PendingChunkedMessageKind::Format(_) | PendingChunkedMessageKind::Check(_),
+            ..
+          })
+        ) {
+          self.pending.remove(&message_id);
+        }
+        Ok(Some(message))
+      }
+      _ => Ok(Some(message)),
+    }
+  }
+
+  fn start(&mut self, id: MessageId, kind: PendingChunkedMessageKind, total_len: usize) -> Option<ProcessPluginMessage> {
+    let pending = PendingChunkedMessage {
+      kind,
+      total_len,
+      // the length comes from the other process, so grow the buffer as chunks arrive
+      data: Vec::with_capacity(std::cmp::min(total_len, FORMAT_CHUNK_SIZE)),
+    };
+    if total_len == 0 {
+      Some(pending.into_message(id))
+    } else {
+      self.pending.insert(id, pending);
+      None
+    }
+  }
+}
+
+impl PendingChunkedMessage {
+  fn into_message(self, id: MessageId) -> ProcessPluginMessage {
+    ProcessPluginMessage {
+      id,
+      body: match self.kind {
+        PendingChunkedMessageKind::Format(body) => MessageBody::Format(FormatMessageBody {
+          file_path: body.file_path,
+          range: body.range,
+          config_id: body.config_id,
+          override_config: body.override_config,
+          file_bytes: self.data,
+          chunked: true,
+        }),
+        PendingChunkedMessageKind::Check(body) => MessageBody::Check(CheckMessageBody {
+          file_path: body.file_path,
+          config_id: body.config_id,
+          file_bytes: self.data,
+        }),
+        PendingChunkedMessageKind::FormatResponse(message_id) => MessageBody::FormatResponse(ResponseBody {
+          message_id,
+          data: Some(self.data),
+        }),
+      },
+    }
+  }
+}
+
+fn get_format_range(start_byte_index: u32, end_byte_index: u32, file_bytes_len: usize) -> FormatRange {
+  if start_byte_index == 0 && end_byte_index == file_bytes_len as u32 {
+    None
+  } else {
+    Some(std::ops::Range {
+      start: start_byte_index as usize,
+      end: end_byte_index as usize,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn format_chunked_message(id: MessageId, file_bytes_len: u32) -> ProcessPluginMessage {
+    ProcessPluginMessage {
+      id,
+      body: MessageBody::FormatChunked(FormatChunkedMessageBody {
+        file_path: PathBuf::from("file.txt"),
+        range: None,
+        config_id: FormatConfigId::from_raw(1),
+        override_config: Vec::new(),
+        file_bytes_len,
+      }),
+    }
+  }
+
+  fn format_chunk_message(id: MessageId, message_id: MessageId, data: &[u8]) -> ProcessPluginMessage {
+    ProcessPluginMessage {
+      id,
+      body: MessageBody::FormatChunk(ResponseBody {
+        message_id,
+        data: FormatChunkBytes::from(data.to_vec()),
+      }),
+    }
+  }
+
+  fn get_format_bytes(message: Option<ProcessPluginMessage>) -> Vec<u8> {
+    match message.unwrap().body {
+      MessageBody::Format(body) => {
+        assert!(body.chunked);
+        body.file_bytes
+      }
+      body => panic!("Unexpected message: {:?}", body),
+    }
+  }
+
+  #[test]
+  fn assembles_chunks() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    assert!(assembler.process(format_chunked_message(1, 5)).unwrap().is_none());
+    assert!(assembler.process(format_chunk_message(2, 1, b"abc")).unwrap().is_none());
+    let message = assembler.process(format_chunk_message(3, 1, b"de")).unwrap();
+    assert_eq!(get_format_bytes(message), b"abcde");
+    assert!(assembler.pending.is_empty());
+  }
+
+  #[test]
+  fn errors_for_oversize_chunk() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    assert!(assembler.process(format_chunked_message(1, 4)).unwrap().is_none());
+    assert!(assembler.process(format_chunk_message(2, 1, b"abc")).unwrap().is_none());
+    let err = assembler.process(format_chunk_message(3, 1, b"de")).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "Received more bytes than expected for chunked message: 1");
+  }
+
+  #[test]
+  fn does_not_allocate_advertised_length_up_front() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    assert!(assembler.process(format_chunked_message(1, u32::MAX)).unwrap().is_none());
+    assert!(assembler.pending[&1].data.capacity() <= FORMAT_CHUNK_SIZE);
+  }
+
+  #[test]
+  fn drops_chunks_after_cancel() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    assert!(assembler.process(format_chunked_message(1, 5)).unwrap().is_none());
+    assert!(assembler.process(format_chunk_message(2, 1, b"abc")).unwrap().is_none());
+    let message = assembler
+      .process(ProcessPluginMessage {
+        id: 3,
+        body: MessageBody::CancelFormat(1),
+      })
+      .unwrap();
+    assert!(matches!(message.unwrap().body, MessageBody::CancelFormat(1)));
+    assert!(assembler.pending.is_empty());
+    // the remaining chunk was already in flight
+    assert!(assembler.process(format_chunk_message(4, 1, b"de")).unwrap().is_none());
+    assert!(assembler.pending.is_empty());
+  }
+
+  #[test]
+  fn assembles_check_chunks() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    let message = ProcessPluginMessage {
+      id: 1,
+      body: MessageBody::CheckChunked(CheckChunkedMessageBody {
+        file_path: PathBuf::from("file.txt"),
+        config_id: FormatConfigId::from_raw(1),
+        file_bytes_len: 4,
+      }),
+    };
+    assert!(assembler.process(message).unwrap().is_none());
+    let message = assembler.process(format_chunk_message(2, 1, b"text")).unwrap();
+    match message.unwrap().body {
+      MessageBody::Check(body) => {
+        assert_eq!(body.file_path, PathBuf::from("file.txt"));
+        assert_eq!(body.file_bytes, b"text");
+      }
+      body => panic!("Unexpected message: {:?}", body),
+    }
+  }
+
+  #[test]
+  fn completes_zero_length_message_immediately() {
+    let mut assembler = ChunkedMessageAssembler::default();
+    let message = assembler.process(format_chunked_message(1, 0)).unwrap();
+    assert_eq!(get_format_bytes(message), Vec::<u8>::new());
+    assert!(assembler.pending.is_empty());
+
+    let message = assembler
+      .process(ProcessPluginMessage {
+        id: 2,
+        body: MessageBody::ChunkedFormatResponse(ResponseBody { message_id: 7, data: 0 }),
+      })
+      .unwrap();
+    match message.unwrap().body {
+      MessageBody::FormatResponse(body) => {
+        assert_eq!(body.message_id, 7);
+        assert_eq!(body.data, Some(Vec::new()));
+      }
+      body => panic!("Unexpected message: {:?}", body),
+    }
+  }
+
+  #[test]
+  fn creates_chunk_messages_from_slices() {
+    let id_generator = IdGenerator::default();
+    let bytes = Arc::new(vec![1; FORMAT_CHUNK_SIZE * 2 + 10]);
+    let messages = create_format_chunk_messages(&id_generator, 5, bytes.clone()).collect::<Vec<_>>();
+    let lens = messages
+      .iter()
+      .map(|message| match &message.body {
+        MessageBody::FormatChunk(body) => {
+          assert_eq!(body.message_id, 5);
+          assert!(Arc::ptr_eq(&body.data.bytes, &bytes));
+          body.data.as_slice().len()
+        }
+        body => panic!("Unexpected message: {:?}", body),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(lens, vec![FORMAT_CHUNK_SIZE, FORMAT_CHUNK_SIZE, 10]);
+  }
 }
@@ -16,6 +16,17 @@ pub enum JsonResponse {
   Err(String),
 }
 
+/// The override configuration sent to the host by plugins when
+/// the host has enabled host format hints.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostFormatConfigWithHints {
+  #[serde(default)]
+  pub config: crate::configuration::ConfigKeyMap,
+  #[serde(default)]
+  pub hints: crate::plugins::HostFormatHints,
+}
+
 pub struct WasiPrintFd(pub i32);
 
 impl std::io::Write for WasiPrintFd {
@@ -117,6 +128,20 @@ pub mod macros {
 
       // HOST FORMATTING
 
+      static HOST_FORMAT_HINTS_ENABLED: StaticCell<bool> = StaticCell::new(false);
+
+      /// Called by the CLI when it supports receiving host format hints.
+      #[no_mangle]
+      pub fn enable_host_format_hints() {
+        unsafe { *HOST_FORMAT_HINTS_ENABLED.get() = true };
+      }
+
+      /// Called by the CLI when it supports providing host format errors
+      /// as format diagnostics, which it then does by returning `3` from
+      /// `host_format` and the JSON diagnostic from `host_get_error_text`.
+      #[no_mangle]
+      pub fn enable_host_format_diagnostics() {}
+
       #[link(wasm_import_module = "dprint")]
       extern "C" {
         fn host_has_cancelled() -> i32;
@@ -143,10 +168,21 @@ pub mod macros {
         }
 
         let file_path = request.file_path.to_string_lossy();
-        let override_config = if !request.override_config.is_empty() {
-          Cow::Owned(serde_json::to_string(request.override_config).unwrap())
-        } else {
+        let override_config = if request.override_config.is_empty() && request.hints.is_empty() {
           Cow::Borrowed("")
+        } else if unsafe { *HOST_FORMAT_HINTS_ENABLED.get() } {
+          Cow::Owned(
+            serde_json::to_string(&dprint_core::plugins::wasm::HostFormatConfigWithHints {
+              config: request.override_config.clone(),
+              hints: request.hints,
+            })
+            .unwrap(),
+          )
+        } else {
+          // older CLI, so fall back to providing the inherited values as configuration
+          let mut override_config = request.override_config.clone();
+          request.hints.add_inherited_to_override_config(&mut override_config, &Default::default());
+          Cow::Owned(serde_json::to_string(&override_config).unwrap())
         };
         let range = request.range.unwrap_or(0..request.file_bytes.len());
 
@@ -178,6 +214,15 @@ pub mod macros {
             let error_text = get_string_from_host(length);
             Err(anyhow::anyhow!("{}", error_text))
           }
+          3 => {
+            // error with a format diagnostic
+            let length = unsafe { host_get_error_text() };
+            let diagnostic_bytes = get_bytes_from_host(length);
+            match serde_json::from_slice::<dprint_core::plugins::FormatDiagnostic>(&diagnostic_bytes) {
+              Ok(diagnostic) => Err(diagnostic.into()),
+              Err(err) => Err(anyhow::anyhow!("Failed deserializing host format diagnostic. {:#}", err)),
+            }
+          }
           value => panic!("unknown host format value: {}", value),
         };
 
@@ -201,6 +246,8 @@ pub mod macros {
       static FILE_PATH: StaticCell<Option<std::path::PathBuf>> = StaticCell::new(None);
       static FORMATTED_TEXT: StaticCell<Option<Vec<u8>>> = StaticCell::new(None);
       static ERROR_TEXT: StaticCell<Option<String>> = StaticCell::new(None);
+      static ERROR_DIAGNOSTIC: StaticCell<Option<dprint_core::plugins::FormatDiagnostic>> = StaticCell::new(None);
+      static CHECK_DIAGNOSTICS: StaticCell<Option<Vec<dprint_core::plugins::FormatDiagnostic>>> = StaticCell::new(None);
 
       #[no_mangle]
       pub fn set_override_config() {
@@ -226,16 +273,16 @@ pub mod macros {
         format_inner(config_id, Some(range_start as usize..range_end as usize))
       }
 
-      fn format_inner(config_id: u32, range: dprint_core::plugins::FormatRange) -> u8 {
-        #[derive(Debug)]
-        struct HostCancellationToken;
+      #[derive(Debug)]
+      struct HostCancellationToken;
 
-        impl dprint_core::plugins::CancellationToken for HostCancellationToken {
-          fn is_cancelled(&self) -> bool {
-            unsafe { host_has_cancelled() == 1 }
-          }
+      impl dprint_core::plugins::CancellationToken for HostCancellationToken {
+        fn is_cancelled(&self) -> bool {
+          unsafe { host_has_cancelled() == 1 }
         }
+      }
 
+      fn format_inner(config_id: u32, range: dprint_core::plugins::FormatRange) -> u8 {
         let config_id = dprint_core::plugins::FormatConfigId::from_raw(config_id);
         ensure_initialized(config_id);
         let config = unsafe {
@@ -248,12 +295,21 @@ pub mod macros {
         let file_path = unsafe { FILE_PATH.get().take().expect("Expected the file path to be set.") };
         let file_bytes = take_from_shared_bytes();
 
+        let whitespace = unsafe {
+          UNRESOLVED_CONFIG
+            .get()
+            .get(&config_id)
+            .map(|config| dprint_core::configuration::WhitespaceOptions::from(&config.global))
+            .unwrap_or_default()
+        };
+
         let request = dprint_core::plugins::SyncFormatRequest::<$wasm_plugin_config> {
           file_path: &file_path,
           file_bytes,
           config: &config,
           config_id,
           range,
+          whitespace,
           token: &HostCancellationToken,
         };
         let formatted_text = unsafe { WASM_PLUGIN.get().format(request, format_with_host) };
@@ -265,8 +321,10 @@ pub mod macros {
             unsafe { FORMATTED_TEXT.get().replace(formatted_text) };
             1 // change
           }
-          Err(err_text) => {
-            unsafe { ERROR_TEXT.get().replace(err_text.to_string()) };
+          Err(err) => {
+            let diagnostic = dprint_core::plugins::FormatDiagnostic::from_error(&err).cloned();
+            ERROR_DIAGNOSTIC.replace(diagnostic);
+            unsafe { ERROR_TEXT.get().replace(err.to_string()) };
             2 // error
           }
         }
@@ -284,6 +342,53 @@ pub mod macros {
         set_shared_bytes_str(error_text)
       }
 
+      /// Gets the JSON serialized format diagnostic of the last error or
+      /// zero length when the error was not a format diagnostic.
+      #[no_mangle]
+      pub fn get_error_diagnostic() -> usize {
+        match ERROR_DIAGNOSTIC.replace(None) {
+          Some(diagnostic) => set_shared_bytes(serde_json::to_vec(&diagnostic).unwrap()),
+          None => set_shared_bytes(Vec::new()),
+        }
+      }
+
+      #[no_mangle]
+      pub fn check(config_id: u32) -> u8 {
+        let config_id = dprint_core::plugins::FormatConfigId::from_raw(config_id);
+        ensure_initialized(config_id);
+        let config = &get_resolved_config_result(config_id).config;
+        let file_path = unsafe { FILE_PATH.get().take().expect("Expected the file path to be set.") };
+        let file_bytes = take_from_shared_bytes();
+
+        let request = dprint_core::plugins::SyncCheckRequest::<$wasm_plugin_config> {
+          file_path: &file_path,
+          file_bytes,
+          config,
+          config_id,
+          token: &HostCancellationToken,
+        };
+        match unsafe { WASM_PLUGIN.get().check(request) } {
+          Ok(diagnostics) if diagnostics.is_empty() => {
+            0 // no diagnostics
+          }
+          Ok(diagnostics) => {
+            CHECK_DIAGNOSTICS.replace(Some(diagnostics));
+            1 // diagnostics
+          }
+          Err(err) => {
+            unsafe { ERROR_TEXT.get().replace(err.to_string()) };
+            2 // error
+          }
+        }
+      }
+
+      /// Gets the JSON serialized diagnostics of the last check.
+      #[no_mangle]
+      pub fn get_check_diagnostics() -> usize {
+        let diagnostics = CHECK_DIAGNOSTICS.replace(None).expect("Expected to have check diagnostics.");
+        set_shared_bytes(serde_json::to_vec(&diagnostics).unwrap())
+      }
+
       // INFORMATION & CONFIGURATION
 
       static RESOLVE_CONFIGURATION_RESULT: RefStaticCell<
@@ -28,12 +28,7 @@ fn should_stabilize_after_reevaluation_flipping() {
       items.push_reevaluation(reevaluation);
       items
     },
-    PrintOptions {
-      indent_width: 2,
-      max_width: 40,
-      use_tabs: false,
-      new_line_text: "\n",
-    },
+    PrintOptions::new(40, 2, false, "\n"),
   );
   assert_eq!(result, "1");
 }
@@ -22,16 +22,18 @@ fn test_tracing() {
       });
       print_items
     },
-    PrintOptions {
-      indent_width: 4,
-      use_tabs: false,
-      max_width: 80,
-      new_line_text: "\n",
-    },
+    PrintOptions::new(80, 4, false, "\n"),
   );
 
   // very basic test just to ensure it's working
   assert_eq!(trace_result.print_nodes.len(), 8);
   assert_eq!(trace_result.traces.len(), 7);
   assert_eq!(trace_result.writer_nodes.len(), 4);
+  let condition_traces = trace_result.traces.iter().filter(|t| t.condition_value.is_some()).collect::<Vec<_>>();
+  assert_eq!(condition_traces.len(), 1);
+  assert_eq!(condition_traces[0].condition_value, Some(Some(true)));
+  let last_trace = trace_result.traces.last().unwrap();
+  assert_eq!(last_trace.writer_info.line_number, 1);
+  assert_eq!(last_trace.writer_info.column_number, 19);
+  assert!(trace_result.traces.iter().all(|t| !t.restored_save_point));
 }
@@ -1,7 +1,10 @@
 mod config_helpers;
+mod spec_checks;
 mod spec_helpers;
 mod spec_parser;
+mod trace_report;
 
 pub use config_helpers::*;
 pub use spec_helpers::*;
 pub use spec_parser::*;
+pub use trace_report::*;
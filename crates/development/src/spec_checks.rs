@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+/// Small deterministic random number generator (xorshift) so that
+/// failures found by the extra spec checks are reproducible.
+pub(crate) struct SpecRng(u64);
+
+impl SpecRng {
+  pub fn new(seed_text: &str) -> Self {
+    // fnv-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in seed_text.bytes() {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Self(hash.max(1))
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    let mut value = self.0;
+    value ^= value << 13;
+    value ^= value >> 7;
+    value ^= value << 17;
+    self.0 = value;
+    value
+  }
+
+  /// Gets a random number in `0..len`.
+  pub fn next_index(&mut self, len: usize) -> usize {
+    debug_assert!(len > 0);
+    (self.next_u64() % len as u64) as usize
+  }
+}
+
+/// Gets a random range within the text on char boundaries.
+pub(crate) fn get_random_range(text: &str, rng: &mut SpecRng) -> Range<usize> {
+  let mut start = floor_char_boundary(text, rng.next_index(text.len() + 1));
+  let mut end = floor_char_boundary(text, rng.next_index(text.len() + 1));
+  if start > end {
+    std::mem::swap(&mut start, &mut end);
+  }
+  start..end
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+  while !text.is_char_boundary(index) {
+    index -= 1;
+  }
+  index
+}
+
+const WHITESPACE_REPLACEMENTS: [&str; 6] = [" ", "  ", "\t", "\n", "\n\n", " \n  "];
+
+/// Mutates the whitespace of the text and optionally inserts a line comment.
+///
+/// Whitespace is only ever replaced with other whitespace, so tokens
+/// are never joined together or split apart.
+pub(crate) fn fuzz_text(text: &str, rng: &mut SpecRng, line_comment: Option<&str>) -> String {
+  let mut text = text.to_string();
+  let mutation_count = 1 + rng.next_index(3);
+  for _ in 0..mutation_count {
+    let whitespace_ranges = get_whitespace_ranges(&text);
+    let should_insert_comment = line_comment.is_some() && (whitespace_ranges.is_empty() || rng.next_index(4) == 0);
+    if should_insert_comment {
+      let line_ends = text.match_indices('\n').map(|(index, _)| index).chain([text.len()]).collect::<Vec<_>>();
+      let index = line_ends[rng.next_index(line_ends.len())];
+      text.insert_str(index, &format!(" {} fuzz", line_comment.unwrap()));
+    } else if !whitespace_ranges.is_empty() {
+      let range = whitespace_ranges[rng.next_index(whitespace_ranges.len())].clone();
+      let replacement = WHITESPACE_REPLACEMENTS[rng.next_index(WHITESPACE_REPLACEMENTS.len())];
+      text.replace_range(range, replacement);
+    }
+  }
+  text
+}
+
+fn get_whitespace_ranges(text: &str) -> Vec<Range<usize>> {
+  let mut ranges = Vec::new();
+  let mut start = None;
+  for (index, c) in text.char_indices() {
+    if c.is_whitespace() {
+      start.get_or_insert(index);
+    } else if let Some(start) = start.take() {
+      ranges.push(start..index);
+    }
+  }
+  if let Some(start) = start {
+    ranges.push(start..text.len());
+  }
+  ranges
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn gets_random_ranges_on_char_boundaries() {
+    let text = "a→b→c";
+    let mut rng = SpecRng::new("test");
+    for _ in 0..100 {
+      let range = get_random_range(text, &mut rng);
+      assert!(range.start <= range.end);
+      assert!(text.get(range).is_some());
+    }
+  }
+
+  #[test]
+  fn fuzzes_only_whitespace() {
+    let text = "let a = 5; // comment\nlet  b = 6;";
+    let mut rng = SpecRng::new("test");
+    for _ in 0..100 {
+      let fuzzed = fuzz_text(text, &mut rng, None);
+      assert_eq!(fuzzed.split_whitespace().collect::<Vec<_>>(), text.split_whitespace().collect::<Vec<_>>());
+    }
+    // deterministic
+    assert_eq!(
+      fuzz_text(text, &mut SpecRng::new("seed"), Some("//")),
+      fuzz_text(text, &mut SpecRng::new("seed"), Some("//"))
+    );
+  }
+
+  #[test]
+  fn fuzzes_comments() {
+    let mut rng = SpecRng::new("test");
+    let fuzzed = fuzz_text("a", &mut rng, Some("#"));
+    assert!(fuzzed.starts_with('a') && fuzzed.contains("# fuzz"), "{}", fuzzed);
+  }
+}
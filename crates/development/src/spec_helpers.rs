@@ -8,18 +8,22 @@ use similar::ChangeTag;
 use similar::TextDiff;
 use std::fmt::Display;
 use std::fs;
+use std::ops::Range;
 use std::panic::catch_unwind;
 use std::panic::AssertUnwindSafe;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use super::spec_checks::*;
 use super::*;
 
 struct FailedTestResult {
   expected: String,
   actual: String,
   actual_second: Option<String>,
+  /// The input text when it's different than the spec's (ex. fuzzed input).
+  input: Option<String>,
   message: String,
 }
 
@@ -47,13 +51,42 @@ impl Display for DiffFailedMessage<'_> {
 }
 
 type FormatTextFunc = dyn (Fn(&Path, &str, &SpecConfigMap) -> Result<Option<String>>) + Send + Sync;
+type HostFormatTextFunc<'a> = dyn (Fn(&Path, &str) -> Result<Option<String>>) + 'a;
+type FormatTextWithHostFunc = dyn (Fn(&Path, &str, &SpecConfigMap, &HostFormatTextFunc) -> Result<Option<String>>) + Send + Sync;
+type FormatRangeTextFunc = dyn (Fn(&Path, &str, Range<usize>, &SpecConfigMap) -> Result<Option<String>>) + Send + Sync;
 type GetTraceJsonFunc = dyn (Fn(&Path, &str, &SpecConfigMap) -> String) + Send + Sync;
 
 #[derive(Debug, Clone)]
 pub struct RunSpecsOptions {
   /// Set to true to overwrite the failing tests with the actual result.
   pub fix_failures: bool,
+  /// Formats the output again and checks it stays the same.
   pub format_twice: bool,
+  /// The number of random subranges of the expected output to range format
+  /// per spec, checking that the output stays the same. Defaults to 5.
+  ///
+  /// Only used with `run_specs_with_range_format`. Opt out a spec with
+  /// `(skip-range-format)`.
+  pub range_format_count: u32,
+  /// The number of times to format the spec's input with mutated whitespace
+  /// (and comments when `fuzz_line_comment` is set) per spec, checking that it
+  /// doesn't panic and that formatting the output again doesn't change it.
+  /// Defaults to 5. Opt out a spec with `(skip-fuzz)`.
+  pub fuzz_count: u32,
+  /// The text that starts a line comment (ex. `//`) to insert when fuzzing.
+  pub fuzz_line_comment: Option<&'static str>,
+}
+
+impl Default for RunSpecsOptions {
+  fn default() -> Self {
+    Self {
+      fix_failures: false,
+      format_twice: false,
+      range_format_count: 5,
+      fuzz_count: 5,
+      fuzz_line_comment: None,
+    }
+  }
 }
 
 pub fn run_specs(
@@ -62,6 +95,68 @@ pub fn run_specs(
   run_spec_options: &RunSpecsOptions,
   format_text: Arc<FormatTextFunc>,
   get_trace_json: Arc<GetTraceJsonFunc>,
+) {
+  run_specs_inner(directory_path, parse_spec_options, run_spec_options, format_text, None, get_trace_json)
+}
+
+/// Runs the specs like `run_specs`, but also range formats random subranges
+/// of the expected output when `range_format_count` is set.
+pub fn run_specs_with_range_format(
+  directory_path: &Path,
+  parse_spec_options: &ParseSpecOptions,
+  run_spec_options: &RunSpecsOptions,
+  format_text: Arc<FormatTextFunc>,
+  format_range_text: Arc<FormatRangeTextFunc>,
+  get_trace_json: Arc<GetTraceJsonFunc>,
+) {
+  run_specs_inner(
+    directory_path,
+    parse_spec_options,
+    run_spec_options,
+    format_text,
+    Some(format_range_text),
+    get_trace_json,
+  )
+}
+
+/// Runs the specs like `run_specs_with_range_format`, but `format_text` is also
+/// given a function to host format other files with.
+///
+/// Host format requests are routed back to `format_text` with the requested file
+/// path, like the CLI routes them to the plugin for that file. This allows a spec
+/// with multiple `[file: ...]` sections to format a file that embeds another
+/// file's language.
+pub fn run_specs_with_host_format(
+  directory_path: &Path,
+  parse_spec_options: &ParseSpecOptions,
+  run_spec_options: &RunSpecsOptions,
+  format_text: Arc<FormatTextWithHostFunc>,
+  format_range_text: Option<Arc<FormatRangeTextFunc>>,
+  get_trace_json: Arc<GetTraceJsonFunc>,
+) {
+  run_specs_inner(
+    directory_path,
+    parse_spec_options,
+    run_spec_options,
+    Arc::new(move |file_path, file_text, config| format_with_host(&*format_text, file_path, file_text, config)),
+    format_range_text,
+    get_trace_json,
+  )
+}
+
+fn format_with_host(format_text: &FormatTextWithHostFunc, file_path: &Path, file_text: &str, config: &SpecConfigMap) -> Result<Option<String>> {
+  format_text(file_path, file_text, config, &|file_path, file_text| {
+    format_with_host(format_text, file_path, file_text, config)
+  })
+}
+
+fn run_specs_inner(
+  directory_path: &Path,
+  parse_spec_options: &ParseSpecOptions,
+  run_spec_options: &RunSpecsOptions,
+  format_text: Arc<FormatTextFunc>,
+  format_range_text: Option<Arc<FormatRangeTextFunc>>,
+  get_trace_json: Arc<GetTraceJsonFunc>,
 ) {
   #[cfg(not(debug_assertions))]
   assert_not_fix_failures(run_spec_options);
@@ -77,7 +172,14 @@ pub fn run_specs(
     RunOptions { parallel: true },
     Arc::new(move |test| {
       let file_text = test.read_to_string().unwrap();
-      let specs = parse_specs(file_text, &parse_spec_options);
+      let specs = match try_parse_specs(&file_text, &parse_spec_options) {
+        Ok(specs) => specs,
+        Err(err) => {
+          return TestResult::Failed {
+            output: format!("Error parsing spec file {}:{}: {}", test.path.display(), err.line_number, err.message).into_bytes(),
+          }
+        }
+      };
       let specs = if specs.iter().any(|s| s.is_only) {
         specs.into_iter().filter(|s| s.is_only).collect()
       } else {
@@ -97,13 +199,17 @@ pub fn run_specs(
         }
 
         let test_file_path = &test.path;
-        let maybe_failed_result = run_spec(&spec, test_file_path, &run_spec_options, &format_text, &get_trace_json);
+        let maybe_failed_result = run_spec(&spec, test_file_path, &run_spec_options, &format_text, &format_range_text, &get_trace_json);
 
         sub_tests.push(SubTestResult {
           name: spec.message.clone(),
           result: if let Some(failed_test) = maybe_failed_result {
             let mut output = Vec::<u8>::new();
-            let mut failed_message = format!(
+            let mut failed_message = String::new();
+            if let Some(input) = &failed_test.input {
+              failed_message.push_str(&format!("Input:    `{:?}`,\n", input));
+            }
+            failed_message.push_str(&format!(
               "Failed:   {} ({})\nExpected: `{:?}`,\nActual:   `{:?}`,`,\nDiff:\n{}",
               failed_test.message,
               test_file_path.display(),
@@ -113,7 +219,7 @@ pub fn run_specs(
                 actual: &failed_test.actual,
                 expected: &failed_test.expected
               }
-            );
+            ));
             if let Some(actual_second) = &failed_test.actual_second {
               failed_message.push_str(&format!(
                 "\nTwice:    `{:?}`,\nTwice diff:\n{}",
@@ -141,99 +247,227 @@ pub fn run_specs(
     test_file_path: &Path,
     run_spec_options: &RunSpecsOptions,
     format_text: &Arc<FormatTextFunc>,
+    format_range_text: &Option<Arc<FormatRangeTextFunc>>,
     get_trace_json: &Arc<GetTraceJsonFunc>,
   ) -> Option<FailedTestResult> {
     let spec_file_path_buf = PathBuf::from(&spec.file_name);
-    let format = |file_text: &str| {
-      let result = catch_unwind(AssertUnwindSafe(|| format_text(&spec_file_path_buf, file_text, &spec.config)));
-      if result.is_err() {
-        eprintln!("Panic in spec '{}' in {}\n", spec.message, test_file_path.display());
-      }
-      let result = result.unwrap();
-      result.unwrap_or_else(|err| panic!("Could not parse spec '{}' in {}\nMessage: {:#}", spec.message, test_file_path.display(), err,))
-    };
 
     if spec.is_trace {
       let trace_json = get_trace_json(&spec_file_path_buf, &spec.file_text, &spec.config);
       handle_trace(spec, &trace_json);
       None
+    } else if let Some(expected_error) = &spec.expected_error {
+      check_expected_error(spec, expected_error, format_text)
     } else {
-      let result = format(&spec.file_text).unwrap_or_else(|| spec.file_text.to_string());
-      if result != spec.expected_text {
-        if run_spec_options.fix_failures {
-          // very rough, but good enough
-          let file_text = fs::read_to_string(test_file_path).expect("Expected to read the file.");
-          let file_text = file_text.replace(&spec.expected_text, &result);
-          fs::write(test_file_path, file_text).expect("Expected to write to file.");
-          None
+      let files = [(spec.file_name.as_str(), spec.file_text.as_str(), spec.expected_text.as_str())]
+        .into_iter()
+        .chain(
+          spec
+            .additional_files
+            .iter()
+            .map(|f| (f.file_name.as_str(), f.file_text.as_str(), f.expected_text.as_str())),
+        );
+      for (file_name, file_text, expected_text) in files {
+        let file_path = PathBuf::from(file_name);
+        let format = |file_text: &str| format_file(spec, test_file_path, &file_path, file_text, format_text);
+        let message = if file_name == spec.file_name {
+          spec.message.clone()
         } else {
-          Some(FailedTestResult {
-            expected: spec.expected_text.clone(),
-            actual: result,
-            actual_second: None,
-            message: spec.message.clone(),
-          })
+          format!("{} ({})", spec.message, file_name)
+        };
+        let result = format(file_text).unwrap_or_else(|| file_text.to_string());
+        if result != expected_text {
+          if run_spec_options.fix_failures {
+            // very rough, but good enough
+            let file_text = fs::read_to_string(test_file_path).expect("Expected to read the file.");
+            let file_text = file_text.replace(expected_text, &result);
+            fs::write(test_file_path, file_text).expect("Expected to write to file.");
+            return None;
+          } else {
+            return Some(FailedTestResult {
+              expected: expected_text.to_string(),
+              actual: result,
+              actual_second: None,
+              input: None,
+              message,
+            });
+          }
+        } else if run_spec_options.format_twice && !spec.skip_format_twice {
+          // ensure no changes when formatting twice
+          let twice_result = format(&result).unwrap_or_else(|| result.to_string());
+          if twice_result != expected_text {
+            return Some(FailedTestResult {
+              expected: expected_text.to_string(),
+              actual: result,
+              actual_second: Some(twice_result),
+              input: None,
+              message,
+            });
+          }
         }
-      } else if run_spec_options.format_twice && !spec.skip_format_twice {
-        // ensure no changes when formatting twice
-        let twice_result = format(&result).unwrap_or_else(|| result.to_string());
-        if twice_result != spec.expected_text {
-          Some(FailedTestResult {
-            expected: spec.expected_text.clone(),
-            actual: result,
-            actual_second: Some(twice_result),
-            message: spec.message.clone(),
-          })
-        } else {
+      }
+
+      check_expected_range_format(spec, format_range_text).or_else(|| run_extra_checks(spec, run_spec_options, format_text, format_range_text))
+    }
+  }
+
+  fn format_file(spec: &Spec, test_file_path: &Path, file_path: &Path, file_text: &str, format_text: &Arc<FormatTextFunc>) -> Option<String> {
+    let result = catch_unwind(AssertUnwindSafe(|| format_text(file_path, file_text, &spec.config)));
+    if result.is_err() {
+      eprintln!("Panic in spec '{}' in {}\n", spec.message, test_file_path.display());
+    }
+    let result = result.unwrap();
+    result.unwrap_or_else(|err| panic!("Could not parse spec '{}' in {}\nMessage: {:#}", spec.message, test_file_path.display(), err,))
+  }
+
+  fn check_expected_error(spec: &Spec, expected_error: &str, format_text: &Arc<FormatTextFunc>) -> Option<FailedTestResult> {
+    let file_path = PathBuf::from(&spec.file_name);
+    let failed_result = |actual: String| FailedTestResult {
+      expected: expected_error.to_string(),
+      actual,
+      actual_second: None,
+      input: None,
+      message: format!("{} (expected an error)", spec.message),
+    };
+    match catch_unwind(AssertUnwindSafe(|| format_text(&file_path, &spec.file_text, &spec.config))) {
+      Ok(Ok(result)) => Some(failed_result(result.unwrap_or_else(|| spec.file_text.clone()))),
+      Ok(Err(err)) => {
+        let message = format!("{:#}", err);
+        if message.contains(expected_error) {
           None
+        } else {
+          Some(failed_result(message))
+        }
+      }
+      Err(_) => Some(failed_result("<panic>".to_string())),
+    }
+  }
+
+  fn check_expected_range_format(spec: &Spec, format_range_text: &Option<Arc<FormatRangeTextFunc>>) -> Option<FailedTestResult> {
+    let expected_range_format = spec.expected_range_format.as_ref()?;
+    let Some(format_range_text) = format_range_text else {
+      panic!(
+        "Spec '{}' has an [expect-range] section, but no range formatting function was provided. Use `run_specs_with_range_format`.",
+        spec.message
+      );
+    };
+    let file_path = PathBuf::from(&spec.file_name);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+      format_range_text(&file_path, &spec.file_text, expected_range_format.range.clone(), &spec.config)
+    }));
+    let result = match result {
+      Ok(Ok(result)) => result.unwrap_or_else(|| spec.file_text.clone()),
+      Ok(Err(err)) => format!("<error: {:#}>", err),
+      Err(_) => "<panic>".to_string(),
+    };
+    if result != expected_range_format.expected_text {
+      Some(FailedTestResult {
+        expected: expected_range_format.expected_text.clone(),
+        actual: result,
+        actual_second: None,
+        input: None,
+        message: format!("{} (range {:?})", spec.message, expected_range_format.range),
+      })
+    } else {
+      None
+    }
+  }
+
+  fn run_extra_checks(
+    spec: &Spec,
+    run_spec_options: &RunSpecsOptions,
+    format_text: &Arc<FormatTextFunc>,
+    format_range_text: &Option<Arc<FormatRangeTextFunc>>,
+  ) -> Option<FailedTestResult> {
+    // don't bother when the spec is known to be unstable
+    if spec.skip_format_twice {
+      return None;
+    }
+    let file_path = PathBuf::from(&spec.file_name);
+    let mut rng = SpecRng::new(&spec.message);
+    let failed_result = |expected: &str, actual: &str, input: &str, message: String| FailedTestResult {
+      expected: expected.to_string(),
+      actual: actual.to_string(),
+      actual_second: None,
+      input: Some(input.to_string()),
+      message: format!("{} ({})", spec.message, message),
+    };
+
+    if let Some(format_range_text) = format_range_text.as_ref().filter(|_| !spec.skip_range_format) {
+      for _ in 0..run_spec_options.range_format_count {
+        let range = get_random_range(&spec.expected_text, &mut rng);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+          format_range_text(&file_path, &spec.expected_text, range.clone(), &spec.config)
+        }));
+        let message = format!("range formatting {:?} of the expected output", range);
+        match result {
+          Ok(Ok(Some(result))) if result != spec.expected_text => {
+            return Some(failed_result(
+              &spec.expected_text,
+              &result,
+              &spec.expected_text,
+              format!("{} changed it", message),
+            ));
+          }
+          Ok(Ok(_)) => {}
+          Ok(Err(err)) => {
+            return Some(failed_result(
+              &spec.expected_text,
+              "",
+              &spec.expected_text,
+              format!("{} errored: {:#}", message, err),
+            ))
+          }
+          Err(_) => return Some(failed_result(&spec.expected_text, "", &spec.expected_text, format!("{} panicked", message))),
+        }
+      }
+    }
+
+    if !spec.skip_fuzz {
+      for _ in 0..run_spec_options.fuzz_count {
+        let fuzzed_text = fuzz_text(&spec.file_text, &mut rng, run_spec_options.fuzz_line_comment);
+        let format = |text: &str| catch_unwind(AssertUnwindSafe(|| format_text(&file_path, text, &spec.config)));
+        let first_result = match format(&fuzzed_text) {
+          Ok(Ok(result)) => result.unwrap_or_else(|| fuzzed_text.clone()),
+          // the mutation may have made the input invalid (ex. a newline in a string)
+          Ok(Err(_)) => continue,
+          Err(_) => return Some(failed_result("", "", &fuzzed_text, "formatting fuzzed input panicked".to_string())),
+        };
+        match format(&first_result) {
+          Ok(Ok(Some(second_result))) if second_result != first_result => {
+            return Some(failed_result(
+              &first_result,
+              &second_result,
+              &fuzzed_text,
+              "formatting fuzzed input was not stable".to_string(),
+            ));
+          }
+          Ok(Ok(_)) => {}
+          Ok(Err(err)) => {
+            return Some(failed_result(
+              &first_result,
+              "",
+              &fuzzed_text,
+              format!("formatting the output of fuzzed input errored: {:#}", err),
+            ));
+          }
+          Err(_) => {
+            return Some(failed_result(
+              &first_result,
+              "",
+              &fuzzed_text,
+              "formatting the output of fuzzed input panicked".to_string(),
+            ))
+          }
         }
-      } else {
-        None
       }
     }
+
+    None
   }
 
   fn handle_trace(spec: &Spec, trace_json: &str) {
-    let app_js_text = include_str!("../trace_analyzer/app.js");
-    let app_css_text = include_str!("../trace_analyzer/app.css");
-    let html_file = r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="utf-8">
-    <meta name="viewport" content="width=device-width">
-    <title><!-- title --></title>
-    <script src="https://d3js.org/d3.v5.min.js"></script>
-    <script src="https://d3js.org/d3-quadtree.v1.min.js"></script>
-    <script src="https://d3js.org/d3-timer.v1.min.js"></script>
-    <script src="https://d3js.org/d3-force.v2.min.js"></script>
-    <script src="https://d3js.org/d3-color.v2.min.js"></script>
-    <script src="https://d3js.org/d3-dispatch.v2.min.js"></script>
-    <script src="https://d3js.org/d3-ease.v2.min.js"></script>
-    <script src="https://d3js.org/d3-interpolate.v2.min.js"></script>
-    <script src="https://d3js.org/d3-selection.v2.min.js"></script>
-    <script src="https://d3js.org/d3-timer.v2.min.js"></script>
-    <script src="https://d3js.org/d3-transition.v2.min.js"></script>
-    <script src="https://d3js.org/d3-drag.v2.min.js"></script>
-    <script src="https://d3js.org/d3-zoom.v2.min.js"></script>
-    <script type="text/javascript">
-    <!-- script -->
-    </script>
-    <style>
-    <!-- style -->
-    </style>
-</head>
-<body onload="onLoad()">
-</body>
-</html>"#;
-    let mut script = format!("const rawTraceResult = {};\n", trace_json);
-    script.push_str(&format!("const specMessage = \"{}\";\n", spec.message.replace('"', "\\\"")));
-    script.push_str(app_js_text);
-    let html_file = html_file
-      .replace("<!-- script -->", &script)
-      .replace("<!-- title -->", &format!("Trace - {}", spec.message))
-      .replace("<!-- style -->", app_css_text);
-    let temp_file_path = std::env::temp_dir().join("dprint-core-trace.html");
-    fs::write(&temp_file_path, html_file).unwrap();
+    let temp_file_path = write_trace_report(&std::env::temp_dir(), "dprint-core-trace", &spec.message, trace_json).unwrap();
     let url = format!("file://{}", temp_file_path.to_string_lossy().replace('\\', "/"));
     panic!("\n==============\nTrace output ready! Please open your browser to: {}\n==============\n", url);
   }
@@ -256,3 +490,31 @@ pub fn run_specs(
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn routes_host_format_to_format_text() {
+    let format_text = |file_path: &Path, file_text: &str, _: &SpecConfigMap, host_format: &HostFormatTextFunc| -> Result<Option<String>> {
+      match file_path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => {
+          let inner_text = file_text.trim_start_matches("<script>").trim_end_matches("</script>");
+          let inner_text = host_format(Path::new("file.ts"), inner_text)?.unwrap_or_else(|| inner_text.to_string());
+          Ok(Some(format!("<script>{}</script>", inner_text)))
+        }
+        Some("ts") => Ok(Some(file_text.split_whitespace().collect::<Vec<_>>().join(" "))),
+        _ => Ok(None),
+      }
+    };
+    let result = format_with_host(
+      &format_text,
+      Path::new("index.html"),
+      "<script>const    u    =     2;</script>",
+      &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(result.unwrap(), "<script>const u = 2;</script>");
+  }
+}
@@ -1,16 +1,39 @@
+use std::ops::Range;
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct Spec {
   pub file_name: String,
   pub message: String,
   pub file_text: String,
   pub expected_text: String,
+  /// Text the error message should contain when the spec is expected to fail formatting.
+  pub expected_error: Option<String>,
+  /// Other files formatted separately with the same configuration.
+  pub additional_files: Vec<SpecFile>,
+  pub expected_range_format: Option<SpecRangeFormat>,
   pub is_only: bool,
   pub is_trace: bool,
   pub skip: bool,
   pub skip_format_twice: bool,
+  pub skip_range_format: bool,
+  pub skip_fuzz: bool,
   pub config: SpecConfigMap,
 }
 
+#[derive(PartialEq, Eq, Debug)]
+pub struct SpecFile {
+  pub file_name: String,
+  pub file_text: String,
+  pub expected_text: String,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct SpecRangeFormat {
+  /// Byte range within the spec's `file_text`.
+  pub range: Range<usize>,
+  pub expected_text: String,
+}
+
 pub type SpecConfigMap = serde_json::Map<String, serde_json::Value>;
 
 #[derive(Debug, Clone)]
@@ -19,116 +42,366 @@ pub struct ParseSpecOptions {
   pub default_file_name: &'static str,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecParseError {
+  /// The one-based line number in the spec file.
+  pub line_number: usize,
+  pub message: String,
+}
+
+impl std::fmt::Display for SpecParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {}: {}", self.line_number, self.message)
+  }
+}
+
+impl std::error::Error for SpecParseError {}
+
+/// Parses the specs in a spec file, panicking when the file is invalid.
 pub fn parse_specs(file_text: String, options: &ParseSpecOptions) -> Vec<Spec> {
-  // this function needs a rewrite
+  match try_parse_specs(&file_text, options) {
+    Ok(specs) => specs,
+    Err(err) => panic!("Error parsing spec file. {}", err),
+  }
+}
+
+/// Parses the specs in a spec file.
+pub fn try_parse_specs(file_text: &str, options: &ParseSpecOptions) -> Result<Vec<Spec>, SpecParseError> {
   let file_text = file_text.replace("\r\n", "\n");
-  let (file_path, file_text) = parse_file_path(file_text, options);
-  let (config, file_text) = parse_config(file_text);
-  let lines = file_text.split('\n').collect::<Vec<_>>();
-  let spec_starts = get_spec_starts(&file_path, &lines);
+  let mut lines = Lines::new(&file_text);
+  let file_name = match lines.peek().and_then(|line| parse_header(line.text, "--", "--")) {
+    Some(file_name) => {
+      lines.next();
+      file_name.to_string()
+    }
+    None => options.default_file_name.to_string(),
+  };
+  let config = parse_config_block(&mut lines, "~~")?.unwrap_or_default();
+  let message_separator = get_message_separator(&file_name);
+
+  lines.skip_blank();
+  match lines.peek() {
+    Some(line) if line.text.starts_with(message_separator) => {}
+    Some(line) => return Err(line.error(format!("All spec files should start with a message. (ex. {0} Message {0})", message_separator))),
+    None => return Ok(Vec::new()),
+  }
+
   let mut specs = Vec::new();
+  while let Some(message_line) = lines.next() {
+    let spec_lines = lines.take_while(|line| !line.text.starts_with(message_separator));
+    specs.push(parse_spec(&file_name, message_separator, message_line, spec_lines, &config)?);
+  }
+  Ok(specs)
+}
+
+#[derive(Clone, Copy)]
+struct Line<'a> {
+  text: &'a str,
+  line_number: usize,
+}
+
+impl Line<'_> {
+  fn error(&self, message: impl Into<String>) -> SpecParseError {
+    SpecParseError {
+      line_number: self.line_number,
+      message: message.into(),
+    }
+  }
+}
 
-  for i in 0..spec_starts.len() {
-    let start_index = spec_starts[i];
-    let end_index = if spec_starts.len() == i + 1 { lines.len() } else { spec_starts[i + 1] };
-    let message_line = lines[start_index];
-    let spec = parse_single_spec(&file_path, message_line, &lines[(start_index + 1)..end_index], &config);
+struct Lines<'a> {
+  lines: Vec<Line<'a>>,
+  index: usize,
+}
 
-    specs.push(spec);
+impl<'a> Lines<'a> {
+  fn new(text: &'a str) -> Self {
+    Self::from_lines(text.split('\n').enumerate().map(|(i, text)| Line { text, line_number: i + 1 }).collect())
   }
 
-  return specs;
+  fn from_lines(lines: Vec<Line<'a>>) -> Self {
+    Self { lines, index: 0 }
+  }
 
-  fn parse_file_path(file_text: String, options: &ParseSpecOptions) -> (String, String) {
-    if !file_text.starts_with("--") {
-      return (options.default_file_name.into(), file_text);
+  fn peek(&self) -> Option<Line<'a>> {
+    self.lines.get(self.index).copied()
+  }
+
+  fn next(&mut self) -> Option<Line<'a>> {
+    let line = self.peek();
+    if line.is_some() {
+      self.index += 1;
     }
-    let last_index = file_text.find("--\n").expect("Could not find final --");
+    line
+  }
 
-    (file_text["--".len()..last_index].trim().into(), file_text[(last_index + "--\n".len())..].into())
+  fn skip_blank(&mut self) {
+    while self.peek().map(|line| line.text.trim().is_empty()).unwrap_or(false) {
+      self.index += 1;
+    }
   }
 
-  fn parse_config(file_text: String) -> (SpecConfigMap, String) {
-    if !file_text.starts_with("~~") {
-      return (Default::default(), file_text);
+  fn take_while(&mut self, predicate: impl Fn(&Line<'a>) -> bool) -> Vec<Line<'a>> {
+    let start = self.index;
+    while self.peek().map(|line| predicate(&line)).unwrap_or(false) {
+      self.index += 1;
     }
-    let last_index = file_text.find("~~\n").expect("Could not find final ~~\\n");
+    self.lines[start..self.index].to_vec()
+  }
+}
 
-    let config_text = file_text["~~".len()..last_index].replace('\n', "");
-    let config_text = config_text.trim();
-    let mut config: SpecConfigMap = Default::default();
+fn parse_header<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+  let text = text.trim_end();
+  if text.len() >= start.len() + end.len() && text.starts_with(start) && text.ends_with(end) {
+    Some(text[start.len()..text.len() - end.len()].trim())
+  } else {
+    None
+  }
+}
 
-    if config_text.starts_with('{') {
-      config = serde_json::from_str(config_text).expect("Error parsing config json.");
+/// Parses a `~~ key: value ~~` block, which may span multiple lines.
+fn parse_config_block(lines: &mut Lines, start_text: &str) -> Result<Option<SpecConfigMap>, SpecParseError> {
+  let start_line = match lines.peek() {
+    Some(line) if line.text.starts_with(start_text) || line.text.trim_end() == "~~" => line,
+    _ => return Ok(None),
+  };
+  let mut config_text = String::new();
+  loop {
+    let Some(line) = lines.next() else {
+      return Err(start_line.error("Could not find the closing ~~ of the config."));
+    };
+    let text = if line.line_number == start_line.line_number {
+      &line.text["~~".len()..]
     } else {
-      for item in config_text.split(',') {
-        let first_colon = item.find(':').expect("Could not find colon in config option.");
-        let key = item[0..first_colon].trim();
-        let value = item[first_colon + ":".len()..].trim();
-
-        config.insert(
-          key.into(),
-          match value.parse::<bool>() {
-            Ok(value) => value.into(),
-            Err(_) => match value.parse::<i32>() {
-              Ok(value) => value.into(),
-              Err(_) => value.into(),
-            },
-          },
-        );
+      line.text
+    };
+    match text.trim_end().strip_suffix("~~") {
+      Some(text) => {
+        config_text.push_str(text);
+        break;
       }
+      None => config_text.push_str(text),
     }
+  }
+  parse_config_text(config_text.trim(), start_line).map(Some)
+}
 
-    (config, file_text[(last_index + "~~\n".len())..].into())
+fn parse_config_text(config_text: &str, line: Line) -> Result<SpecConfigMap, SpecParseError> {
+  if config_text.starts_with('{') {
+    return serde_json::from_str(config_text).map_err(|err| line.error(format!("Error parsing config json. {}", err)));
   }
 
-  fn get_spec_starts(file_name: &str, lines: &[&str]) -> Vec<usize> {
-    let mut result = Vec::new();
-    let message_separator = get_message_separator(file_name);
+  let mut config = SpecConfigMap::new();
+  for item in config_text.split(',').filter(|item| !item.trim().is_empty()) {
+    let Some(first_colon) = item.find(':') else {
+      return Err(line.error(format!("Could not find colon in config option: {}", item.trim())));
+    };
+    let key = item[0..first_colon].trim();
+    let value = item[first_colon + ":".len()..].trim();
+
+    config.insert(
+      key.into(),
+      match value.parse::<bool>() {
+        Ok(value) => value.into(),
+        Err(_) => match value.parse::<i32>() {
+          Ok(value) => value.into(),
+          Err(_) => value.into(),
+        },
+      },
+    );
+  }
+  Ok(config)
+}
 
-    if !lines.first().unwrap().starts_with(message_separator) {
-      panic!("All spec files should start with a message. (ex. {0} Message {0})", message_separator);
+enum Section<'a> {
+  Expect,
+  ExpectError,
+  ExpectRange(&'a str),
+}
+
+fn parse_section<'a>(line: &Line<'a>) -> Result<Section<'a>, SpecParseError> {
+  match parse_header(line.text, "[expect", "]") {
+    Some("") => Ok(Section::Expect),
+    Some("-error") => Ok(Section::ExpectError),
+    Some(name) if name.starts_with("-range ") => Ok(Section::ExpectRange(name["-range ".len()..].trim())),
+    _ => Err(line.error(format!("Unknown section: {}", line.text.trim_end()))),
+  }
+}
+
+fn parse_file_header<'a>(line: &Line<'a>) -> Option<&'a str> {
+  parse_header(line.text, "[file:", "]")
+}
+
+fn parse_spec(file_name: &str, message_separator: &str, message_line: Line, lines: Vec<Line>, file_config: &SpecConfigMap) -> Result<Spec, SpecParseError> {
+  let Some(message) = parse_header(message_line.text, message_separator, message_separator).filter(|m| !m.is_empty()) else {
+    return Err(message_line.error(format!("Expected a message line. (ex. {0} Message {0})", message_separator)));
+  };
+  let mut lines = Lines::from_lines(lines);
+  let mut config = file_config.clone();
+  // require a space so strikethrough text in markdown isn't mistaken for config
+  if let Some(spec_config) = parse_config_block(&mut lines, "~~ ")? {
+    config.extend(spec_config);
+  }
+
+  let input_files = parse_files(file_name, lines.take_while(|line| !line.text.starts_with("[expect")), message_line)?;
+  let mut expected_files = None;
+  let mut expected_error = None;
+  let mut expected_range_format = None;
+  while let Some(section_line) = lines.next() {
+    let section = parse_section(&section_line)?;
+    let section_lines = lines.take_while(|line| !line.text.starts_with("[expect"));
+    let duplicate_error = || section_line.error(format!("Duplicate section: {}", section_line.text.trim_end()));
+    match section {
+      Section::Expect => {
+        if expected_files.is_some() || expected_error.is_some() {
+          return Err(duplicate_error());
+        }
+        expected_files = Some((parse_files(&input_files[0].0, section_lines, section_line)?, section_line));
+      }
+      Section::ExpectError => {
+        if expected_files.is_some() || expected_error.is_some() {
+          return Err(duplicate_error());
+        }
+        let text = join_lines(&section_lines);
+        if text.trim().is_empty() {
+          return Err(section_line.error("Expected the text of the error message below [expect-error]."));
+        }
+        expected_error = Some(text.trim().to_string());
+      }
+      Section::ExpectRange(range_text) => {
+        if expected_range_format.is_some() {
+          return Err(duplicate_error());
+        }
+        let range = parse_line_range(range_text, &input_files[0].1).ok_or_else(|| {
+          section_line.error(format!(
+            "Invalid range '{}'. Expected an inclusive range of one-based line numbers within the input (ex. [expect-range 2-3]).",
+            range_text
+          ))
+        })?;
+        expected_range_format = Some(SpecRangeFormat {
+          range,
+          expected_text: join_lines(&section_lines),
+        });
+      }
     }
+  }
 
-    for (i, line) in lines.iter().enumerate() {
-      if line.starts_with(message_separator) {
-        result.push(i);
+  let mut input_files = input_files.into_iter();
+  let (main_file_name, main_file_text) = input_files.next().unwrap();
+  let (expected_text, additional_files) = match expected_files {
+    Some((expected_files, section_line)) => {
+      let input_files = input_files.collect::<Vec<_>>();
+      let input_names = [&main_file_name]
+        .into_iter()
+        .chain(input_files.iter().map(|(name, _)| name))
+        .collect::<Vec<_>>();
+      let expected_names = expected_files.iter().map(|(name, _)| name).collect::<Vec<_>>();
+      if input_names != expected_names {
+        return Err(section_line.error(format!(
+          "Expected the files in the [expect] section to match the input files ({:?}), but found {:?}.",
+          input_names, expected_names
+        )));
       }
+      let mut expected_files = expected_files.into_iter();
+      let expected_text = expected_files.next().unwrap().1;
+      let additional_files = input_files
+        .into_iter()
+        .zip(expected_files)
+        .map(|((file_name, file_text), (_, expected_text))| SpecFile {
+          file_name,
+          file_text,
+          expected_text,
+        })
+        .collect();
+      (expected_text, additional_files)
     }
+    None if expected_error.is_some() => {
+      if input_files.next().is_some() {
+        return Err(message_line.error("Specs with an [expect-error] section cannot have multiple files."));
+      }
+      (String::new(), Vec::new())
+    }
+    None => return Err(message_line.error("Expected an [expect] or [expect-error] section.")),
+  };
 
-    result
-  }
+  let lower_case_message_line = message_line.text.to_ascii_lowercase();
+  let is_trace = lower_case_message_line.contains("(trace)");
+  Ok(Spec {
+    file_name: main_file_name,
+    message: message.to_string(),
+    file_text: main_file_text,
+    expected_text,
+    expected_error,
+    additional_files,
+    expected_range_format,
+    is_only: lower_case_message_line.contains("(only)") || is_trace,
+    is_trace,
+    skip: lower_case_message_line.contains("(skip)"),
+    skip_format_twice: lower_case_message_line.contains("(skip-format-twice)"),
+    skip_range_format: lower_case_message_line.contains("(skip-range-format)"),
+    skip_fuzz: lower_case_message_line.contains("(skip-fuzz)"),
+    config,
+  })
+}
 
-  fn parse_single_spec(file_name: &str, message_line: &str, lines: &[&str], config: &SpecConfigMap) -> Spec {
-    let file_text = lines.join("\n");
-    let parts = file_text.split("[expect]").collect::<Vec<&str>>();
-    let start_text = parts[0][0..parts[0].len() - "\n".len()].into(); // remove last newline
-    let expected_text = parts[1]["\n".len()..].into(); // remove first newline
-    let lower_case_message_line = message_line.to_ascii_lowercase();
-    let message_separator = get_message_separator(file_name);
-    let is_trace = lower_case_message_line.contains("(trace)");
-
-    Spec {
-      file_name: String::from(file_name),
-      message: message_line[message_separator.len()..message_line.len() - message_separator.len()]
-        .trim()
-        .into(),
-      file_text: start_text,
-      expected_text,
-      is_only: lower_case_message_line.contains("(only)") || is_trace,
-      is_trace,
-      skip: lower_case_message_line.contains("(skip)"),
-      skip_format_twice: lower_case_message_line.contains("(skip-format-twice)"),
-      config: config.clone(),
+/// Splits the lines into files by the `[file: name]` headers. The first file
+/// uses the default file name when the lines don't start with a header.
+fn parse_files(default_file_name: &str, lines: Vec<Line>, section_line: Line) -> Result<Vec<(String, String)>, SpecParseError> {
+  let mut files = Vec::new();
+  let mut lines = Lines::from_lines(lines);
+  let first_file_name = match lines.peek().and_then(|line| parse_file_header(&line)) {
+    Some(_) => None,
+    None => Some(default_file_name.to_string()),
+  };
+  if let Some(file_name) = first_file_name {
+    files.push((file_name, join_lines(&lines.take_while(|line| parse_file_header(line).is_none()))));
+  }
+  while let Some(header_line) = lines.next() {
+    let file_name = parse_file_header(&header_line).unwrap();
+    if file_name.is_empty() {
+      return Err(header_line.error("Expected a file name. (ex. [file: file.ts])"));
+    }
+    if files.iter().any(|(name, _)| name == file_name) {
+      return Err(header_line.error(format!("Duplicate file: {}", file_name)));
     }
+    files.push((file_name.to_string(), join_lines(&lines.take_while(|line| parse_file_header(line).is_none()))));
+  }
+  if files.is_empty() {
+    return Err(section_line.error("Expected file text."));
   }
+  Ok(files)
+}
 
-  fn get_message_separator(file_name: &str) -> &'static str {
-    if file_name.ends_with(".md") {
-      "!!"
-    } else {
-      "=="
+fn join_lines(lines: &[Line]) -> String {
+  lines.iter().map(|line| line.text).collect::<Vec<_>>().join("\n")
+}
+
+/// Gets the byte range of an inclusive one-based line range (ex. `2-3` or `2`).
+fn parse_line_range(range_text: &str, text: &str) -> Option<Range<usize>> {
+  let (start, end) = match range_text.split_once('-') {
+    Some((start, end)) => (start.trim().parse::<usize>().ok()?, end.trim().parse::<usize>().ok()?),
+    None => {
+      let line = range_text.parse::<usize>().ok()?;
+      (line, line)
     }
+  };
+  let mut line_ranges = Vec::new();
+  let mut line_start = 0;
+  for line in text.split('\n') {
+    line_ranges.push(line_start..line_start + line.len());
+    line_start += line.len() + 1;
+  }
+  if start == 0 || start > end || end > line_ranges.len() {
+    return None;
+  }
+  Some(line_ranges[start - 1].start..line_ranges[end - 1].end)
+}
+
+fn get_message_separator(file_name: &str) -> &'static str {
+  if file_name.ends_with(".md") {
+    "!!"
+  } else {
+    "=="
   }
 }
 
@@ -172,11 +445,16 @@ mod tests {
         file_name: "test.ts".into(),
         file_text: "start\nmultiple\n".into(),
         expected_text: "expected\nmultiple\n".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message 1".into(),
         is_only: false,
         is_trace: false,
         skip: false,
         skip_format_twice: false,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
@@ -186,11 +464,16 @@ mod tests {
         file_name: "test.ts".into(),
         file_text: "start2\n".into(),
         expected_text: "expected2\n".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message 2 (only) (skip) (skip-format-twice)".into(),
         is_only: true,
         is_trace: false,
         skip: true,
         skip_format_twice: true,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
@@ -200,11 +483,16 @@ mod tests {
         file_name: "test.ts".into(),
         file_text: "test\n".into(),
         expected_text: "test\n".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message 3 (trace)".into(),
         is_only: true,
         is_trace: true,
         skip: false,
         skip_format_twice: false,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
@@ -224,11 +512,16 @@ mod tests {
         file_name: "asdf.ts".into(),
         file_text: "start".into(),
         expected_text: "expected".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message".into(),
         is_only: false,
         is_trace: false,
         skip: false,
         skip_format_twice: false,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
@@ -256,11 +549,16 @@ mod tests {
         file_name: "asdf.ts".into(),
         file_text: "start".into(),
         expected_text: "expected".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message".into(),
         is_only: false,
         is_trace: false,
         skip: false,
         skip_format_twice: false,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: [("test.test".into(), "other".into()), ("lineWidth".into(), 40.into())]
           .iter()
           .cloned()
@@ -299,11 +597,16 @@ mod tests {
         file_name: "test.md".into(),
         file_text: "start\nmultiple\n".into(),
         expected_text: "expected\nmultiple\n".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message 1".into(),
         is_only: false,
         is_trace: false,
         skip: false,
         skip_format_twice: false,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
@@ -313,13 +616,155 @@ mod tests {
         file_name: "test.md".into(),
         file_text: "start2\n".into(),
         expected_text: "expected2\n".into(),
+        expected_error: None,
+        additional_files: Vec::new(),
+        expected_range_format: None,
         message: "message 2 (only) (skip) (skip-format-twice)".into(),
         is_only: true,
         is_trace: false,
         skip: true,
         skip_format_twice: true,
+        skip_range_format: false,
+        skip_fuzz: false,
         config: Default::default(),
       }
     );
   }
+
+  #[test]
+  fn it_parses_spec_config() {
+    let specs = parse_specs(
+      [
+        "~~ lineWidth: 40, useTabs: true ~~",
+        "== message ==",
+        "~~ lineWidth: 80,",
+        "indentWidth: 2 ~~",
+        "start",
+        "[expect]",
+        "expected",
+        "== message 2 ==",
+        "~~strikethrough~~",
+        "[expect]",
+        "~~strikethrough~~",
+      ]
+      .join("\n"),
+      &ParseSpecOptions { default_file_name: "test.ts" },
+    );
+
+    assert_eq!(specs.len(), 2);
+    assert_eq!(specs[0].file_text, "start");
+    assert_eq!(
+      specs[0].config,
+      [
+        ("lineWidth".into(), 80.into()),
+        ("useTabs".into(), true.into()),
+        ("indentWidth".into(), 2.into())
+      ]
+      .iter()
+      .cloned()
+      .collect::<SpecConfigMap>()
+    );
+    assert_eq!(specs[1].file_text, "~~strikethrough~~");
+    assert_eq!(specs[1].config.len(), 2);
+  }
+
+  #[test]
+  fn it_parses_multiple_files_errors_and_ranges() {
+    let specs = parse_specs(
+      vec![
+        "== multiple files ==",
+        "[file: index.html]",
+        "<script src=\"a.ts\"></script>",
+        "[file: a.ts]",
+        "let  a",
+        "[expect]",
+        "<script src=\"a.ts\"></script>",
+        "[file: a.ts]",
+        "let a;",
+        "== error ==",
+        "let a = ;",
+        "[expect-error]",
+        "Expression expected",
+        "",
+        "== range ==",
+        "a  ;",
+        "b  ;",
+        "c  ;",
+        "[expect]",
+        "a;",
+        "b;",
+        "c;",
+        "[expect-range 2-3]",
+        "a  ;",
+        "b;",
+        "c;",
+      ]
+      .join("\n"),
+      &ParseSpecOptions { default_file_name: "test.ts" },
+    );
+
+    assert_eq!(specs.len(), 3);
+    assert_eq!(specs[0].file_name, "index.html");
+    assert_eq!(specs[0].file_text, "<script src=\"a.ts\"></script>");
+    assert_eq!(specs[0].expected_text, "<script src=\"a.ts\"></script>");
+    assert_eq!(
+      specs[0].additional_files,
+      vec![SpecFile {
+        file_name: "a.ts".into(),
+        file_text: "let  a".into(),
+        expected_text: "let a;".into(),
+      }]
+    );
+    assert_eq!(specs[1].file_text, "let a = ;");
+    assert_eq!(specs[1].expected_error, Some("Expression expected".into()));
+    assert_eq!(specs[1].expected_text, "");
+    assert_eq!(specs[2].expected_text, "a;\nb;\nc;");
+    assert_eq!(
+      specs[2].expected_range_format,
+      Some(SpecRangeFormat {
+        range: 5..14,
+        expected_text: "a  ;\nb;\nc;".into(),
+      })
+    );
+    assert_eq!(&specs[2].file_text[5..14], "b  ;\nc  ;");
+  }
+
+  #[test]
+  fn it_reports_line_accurate_errors() {
+    fn get_error(lines: &[&str]) -> String {
+      try_parse_specs(&lines.join("\n"), &ParseSpecOptions { default_file_name: "test.ts" })
+        .unwrap_err()
+        .to_string()
+    }
+
+    assert_eq!(
+      get_error(&["start", "== message =="]),
+      "line 1: All spec files should start with a message. (ex. == Message ==)"
+    );
+    assert_eq!(
+      get_error(&["~~ lineWidth: 40", "== message =="]),
+      "line 1: Could not find the closing ~~ of the config."
+    );
+    assert_eq!(
+      get_error(&["== message ==", "~~ lineWidth ~~", "[expect]"]),
+      "line 2: Could not find colon in config option: lineWidth"
+    );
+    assert_eq!(
+      get_error(&["== message ==", "a", "[expect]", "a", "", "== message 2 ==", "b", ""]),
+      "line 6: Expected an [expect] or [expect-error] section."
+    );
+    assert_eq!(get_error(&["== message ==", "a", "[expected]", "a"]), "line 3: Unknown section: [expected]");
+    assert_eq!(
+      get_error(&["== message ==", "a", "[expect]", "a", "[expect]", "a"]),
+      "line 5: Duplicate section: [expect]"
+    );
+    assert_eq!(
+      get_error(&["== message ==", "a", "[file: b.ts]", "b", "[expect]", "a", "[file: c.ts]", "c"]),
+      "line 5: Expected the files in the [expect] section to match the input files ([\"test.ts\", \"b.ts\"]), but found [\"test.ts\", \"c.ts\"]."
+    );
+    assert_eq!(
+      get_error(&["== message ==", "a", "[expect]", "a", "[expect-range 2-3]", "a"]),
+      "line 5: Invalid range '2-3'. Expected an inclusive range of one-based line numbers within the input (ex. [expect-range 2-3])."
+    );
+  }
 }
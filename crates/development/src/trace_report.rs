@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width">
+    <title><!-- title --></title>
+    <script type="text/javascript">
+    <!-- script -->
+    </script>
+    <style>
+    <!-- style -->
+    </style>
+</head>
+<body onload="onLoad()">
+</body>
+</html>"#;
+
+/// Gets an html report for the JSON serialized output of `dprint_core::formatting::trace_printing`.
+///
+/// The report embeds the trace along with all its scripts and styles, so it works offline.
+pub fn get_trace_report_html(title: &str, trace_json: &str) -> String {
+  let mut script = format!("const rawTraceResult = {};\n", escape_for_script(trace_json));
+  script.push_str(&format!("const specMessage = {};\n", escape_for_script(&serde_json::to_string(title).unwrap())));
+  script.push_str(include_str!("../trace_analyzer/app.js"));
+  HTML_TEMPLATE
+    .replace("<!-- title -->", &format!("Trace - {}", escape_html(title)))
+    .replace("<!-- style -->", include_str!("../trace_analyzer/app.css"))
+    .replace("<!-- script -->", &script)
+}
+
+/// Writes the html report and the trace JSON to `<file_stem>.html` and `<file_stem>.json`
+/// in the provided directory, then returns the path of the html file.
+pub fn write_trace_report(dir_path: &Path, file_stem: &str, title: &str, trace_json: &str) -> std::io::Result<PathBuf> {
+  let html_file_path = dir_path.join(format!("{}.html", file_stem));
+  fs::write(&html_file_path, get_trace_report_html(title, trace_json))?;
+  fs::write(dir_path.join(format!("{}.json", file_stem)), trace_json)?;
+  Ok(html_file_path)
+}
+
+fn escape_for_script(text: &str) -> String {
+  // prevent strings in the JSON from closing the script element
+  text.replace("</", "<\\/")
+}
+
+fn escape_html(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn embeds_trace_and_title() {
+    let html = get_trace_report_html("a \"<b>\"", r#"{"traces":[],"printNodes":[{"text":"</script>"}]}"#);
+    assert!(html.contains("<title>Trace - a \"&lt;b&gt;\"</title>"));
+    assert!(html.contains(r#"const rawTraceResult = {"traces":[],"printNodes":[{"text":"<\/script>"}]};"#));
+    assert!(html.contains(r#"const specMessage = "a \"<b>\"";"#));
+    assert!(html.contains("function onLoad()"));
+    // everything is inlined
+    assert!(!html.contains("<script src="));
+    assert!(!html.contains("https://"));
+  }
+}
@@ -110,6 +110,8 @@ function getNodeHoverText(node) {
       return `Anchor: ${printItem.content.name} (${node.printNodeId})`;
     case "conditionReevaluation":
       return `Condition reevaluation: ${printItem.content.name} (${printItem.content.conditionId}) (${node.printNodeId})`;
+    case "sourcePosition":
+      return `Source position: ${printItem.content} (${node.printNodeId})`;
   }
 }
 
@@ -222,183 +224,217 @@ function getNodesAndLinks() {
   }
 }
 
-/** @param {(printNodeId: number) => void} onPrintNodeSelect */
+/**
+ * Draws the print item graph as plain SVG so the report works offline. Nodes are laid
+ * out in rows by their depth. Scroll to zoom and drag the background to pan.
+ * @param {(printNodeId: number) => void} onPrintNodeSelect
+ */
 function createGraph(onPrintNodeSelect) {
+  const svgNs = "http://www.w3.org/2000/svg";
   const { links, nodes } = getNodesAndLinks();
+  const nodesById = new Map(nodes.map(n => [n.id, n]));
 
-  let wasMouseActivity = false;
   const width = 400;
   const height = 400;
-  const simulation = d3.forceSimulation(nodes)
-    .force("link", d3.forceLink(links).id(/** @param {any} d */ d => d.id).distance(10))
-    .force("charge", d3.forceManyBody().strength(-3000))
-    .force("y", d3.forceY().y(/** @param {any} d */ d => d.depthY * 125));
-  const svg = d3.create("svg")
-    .attr("viewBox", [0, 0, width, height])
-    .style("font", "40px sans-serif")
-    .on("wheel", () => wasMouseActivity = true)
-    .on("click", () => wasMouseActivity = true);
-
-  const arrow = svg.append("svg:defs").selectAll("marker")
-    .data(["end"])
-    .enter().append("svg:marker")
-    .attr("id", String)
-    .attr("orient", "auto");
-  const arrowInnerPath = arrow.append("svg:path").attr("fill", "#000");
-
-  const drag = d3
-    .drag()
-    .on("drag", /** @param {any} event @param {any} d */ function(event, d) {
-      d.x = event.x;
-      d.y = event.y;
-      d3.select(this).raise().attr("transform", `translate(${d.x}, ${d.y})`);
-      refreshLinks();
-    });
-
+  const rowHeight = 125;
+  const columnWidth = 250;
   const nodeRadius = 15;
   const linkThickness = 5;
-  const linkG = svg.append("g");
-  const link = linkG
-    .selectAll("line")
-    .data(links)
-    .join("line")
-    .attr("stroke-opacity", 0.6)
-    .attr("stroke", /** @param {any} d */ d => getLineColor(d))
-    .attr("data-originating-node-id", /** @param {any} d */ d => d.originatingNodeId)
-    .style("stroke-width", linkThickness)
-    .attr("marker-end", "url(#end)")
-    .on("click", /** @param {any} _ @param {any} d */ (_, d) => {
-      /** @type {number | undefined} */
-      const originatingNodeId = d.originatingNodeId;
-      if (originatingNodeId != null) {
-        onPrintNodeSelect(originatingNodeId);
-      }
-    });
-  link.append("title")
-    .text(/** @param {any} d */ d => {
-      if (d.originatingNodeId != null) {
-        return getNodeHoverText(traceResult.getPrintNode(d.originatingNodeId));
-      }
-      return undefined;
-    });
 
-  const nodeG = svg.append("g");
-  const nodeGInner = nodeG.append("g")
-    .selectAll("g")
-    .data(nodes)
-    .join("g")
-    .call(drag);
-  const nodeCircle = nodeGInner
-    .append("circle")
-    .attr("r", nodeRadius)
-    .attr("fill", /** @param {any} d */ d => getNodeColor(d.printNode))
-    .attr("stroke", "#000")
-    .attr("id", /** @param {any} d */ d => `node${d.id}`)
-    .on("click", /** @param {any} _ @param {any} d */ (_, d) => {
-      onPrintNodeSelect(d.id);
+  // lay out each row of nodes centered on zero
+  /** @type {Map<number, import("./types").GraphPrintNode[]>} */
+  const rows = new Map();
+  for (const node of nodes) {
+    const row = rows.get(node.depthY) ?? [];
+    row.push(node);
+    rows.set(node.depthY, row);
+  }
+  for (const row of rows.values()) {
+    row.forEach((node, index) => {
+      node.x = (index - (row.length - 1) / 2) * columnWidth;
+      node.y = node.depthY * rowHeight;
     });
-  nodeGInner
-    .append("text")
-    .attr("x", 50)
-    .attr("y", "0.31em")
-    .text(/** @param {any} d */ d => getNodeHoverText(d.printNode))
-    .clone(true).lower()
-    .attr("fill", "none")
-    .attr("stroke", "white")
-    .attr("stroke-width", 3);
-
-  /** @type {any} */
-  let transform;
-  /** @type {number} */
-  let sqrtK;
-  const zoom = d3.zoom().on("zoom", /** @param {any} e */ e => {
-    transform = e.transform;
-    nodeG.attr("transform", transform);
-    sqrtK = Math.sqrt(transform.k);
-    nodeCircle.attr("r", nodeRadius / sqrtK)
-      .attr("stroke-width", 1 / sqrtK);
-
-    linkG.attr("transform", transform);
-    link.style("stroke-width", linkThickness / sqrtK);
-
-    arrow.attr("markerWidth", 5)
-      .attr("markerHeight", 5)
-      .attr("viewBox", `0 0 ${5 / sqrtK} ${5 / sqrtK}`)
-      .attr("refX", 8 / sqrtK)
-      .attr("refY", 2.5 / sqrtK);
-    arrowInnerPath.attr("d", `M 0 0 L ${5 / sqrtK} ${2.5 / sqrtK} L 0 ${5 / sqrtK} z`);
-    refreshSelectedNode();
-  });
-
-  simulation.on("tick", () => {
-    refreshLinks();
-
-    let minX = Number.MAX_SAFE_INTEGER;
-    let maxX = Number.MIN_SAFE_INTEGER;
-    let minY = Number.MAX_SAFE_INTEGER;
-    let maxY = Number.MIN_SAFE_INTEGER;
-    nodeGInner
-      .attr("transform", /** @param {any} d */ d => {
-        minX = Math.min(minX, d.x);
-        maxX = Math.max(maxX, d.x);
-        minY = Math.min(minY, d.y);
-        maxY = Math.max(maxY, d.y);
-        return `translate(${d.x}, ${d.y})`;
-      });
+  }
 
-    if (!wasMouseActivity) {
-      svg.call(
-        zoom.transform,
-        d3.zoomIdentity
-          .translate(width / 2, height / 2)
-          .scale(0.95 / Math.max((maxX - minX) / width, (maxY - minY) / height))
-          .translate(-(minX + maxX) / 2, -(minY + maxY) / 2),
-      );
+  const svg = createSvgElement("svg", {
+    viewBox: `0 0 ${width} ${height}`,
+    style: "font: 40px sans-serif",
+  });
+  const defs = svg.appendChild(createSvgElement("defs"));
+  const arrow = defs.appendChild(createSvgElement("marker", { id: "end", orient: "auto" }));
+  const arrowInnerPath = arrow.appendChild(createSvgElement("path", { fill: "#000" }));
+  const viewG = svg.appendChild(createSvgElement("g"));
+  const linkG = viewG.appendChild(createSvgElement("g"));
+  const nodeG = viewG.appendChild(createSvgElement("g"));
+
+  const linkElements = links.map(link => {
+    const line = linkG.appendChild(createSvgElement("line", {
+      "stroke-opacity": 0.6,
+      stroke: link.color || (link.originatingNodeId != null ? "blue" : "#000"),
+      "marker-end": "url(#end)",
+    }));
+    if (link.originatingNodeId != null) {
+      const originatingNodeId = link.originatingNodeId;
+      line.dataset.originatingNodeId = String(originatingNodeId);
+      line.addEventListener("click", () => onPrintNodeSelect(originatingNodeId));
+      line.appendChild(createSvgElement("title")).textContent = getNodeHoverText(traceResult.getPrintNode(originatingNodeId));
     }
+    return { line, source: getNode(link.source), target: getNode(link.target) };
   });
 
-  function refreshLinks() {
-    link
-      .attr("x1", /** @param {any} d */ d => d.source.x)
-      .attr("y1", /** @param {any} d */ d => d.source.y)
-      .attr("x2", /** @param {any} d */ d => d.target.x)
-      .attr("y2", /** @param {any} d */ d => d.target.y);
+  /** @type {Map<number, SVGCircleElement>} */
+  const nodeCircles = new Map();
+  /** @type {Map<number, SVGGElement>} */
+  const nodeGroups = new Map();
+  for (const node of nodes) {
+    const group = nodeG.appendChild(createSvgElement("g"));
+    const circle = group.appendChild(createSvgElement("circle", {
+      id: `node${node.id}`,
+      fill: getNodeColor(node.printNode),
+      stroke: "#000",
+    }));
+    circle.addEventListener("click", () => onPrintNodeSelect(node.id));
+    const text = getNodeHoverText(node.printNode);
+    group.appendChild(createSvgElement("text", { x: 50, y: "0.31em", fill: "none", stroke: "white", "stroke-width": 3 })).textContent = text;
+    group.appendChild(createSvgElement("text", { x: 50, y: "0.31em" })).textContent = text;
+    nodeCircles.set(node.id, circle);
+    nodeGroups.set(node.id, group);
+    moveNode(node);
   }
+  refreshLinks();
+
+  // start zoomed to fit all the nodes
+  const xs = nodes.map(n => n.x ?? 0);
+  const ys = nodes.map(n => n.y ?? 0);
+  const [minX, maxX, minY, maxY] = [Math.min(...xs), Math.max(...xs), Math.min(...ys), Math.max(...ys)];
+  const transform = { x: 0, y: 0, k: 1 };
+  transform.k = 0.95 / Math.max((maxX - minX) / width, (maxY - minY) / height, 0.001);
+  transform.x = width / 2 - transform.k * (minX + maxX) / 2;
+  transform.y = height / 2 - transform.k * (minY + maxY) / 2;
+  refreshTransform();
+
+  svg.addEventListener("wheel", e => {
+    e.preventDefault();
+    const point = getSvgPoint(e);
+    const k = transform.k * Math.pow(2, -e.deltaY * 0.002);
+    transform.x = point.x - (point.x - transform.x) * (k / transform.k);
+    transform.y = point.y - (point.y - transform.y) * (k / transform.k);
+    transform.k = k;
+    refreshTransform();
+  });
+
+  /** @type {{ node: import("./types").GraphPrintNode | undefined; lastPoint: { x: number; y: number } } | undefined} */
+  let dragState;
+  svg.addEventListener("mousedown", e => {
+    const target = /** @type {Element} */ (e.target);
+    const nodeId = target.tagName === "circle" ? parseInt(target.id.replace("node", ""), 10) : undefined;
+    dragState = { node: nodeId == null ? undefined : nodesById.get(nodeId), lastPoint: getSvgPoint(e) };
+  });
+  window.addEventListener("mousemove", e => {
+    if (dragState == null) {
+      return;
+    }
+    const point = getSvgPoint(e);
+    const dx = point.x - dragState.lastPoint.x;
+    const dy = point.y - dragState.lastPoint.y;
+    dragState.lastPoint = point;
+    if (dragState.node != null) {
+      dragState.node.x = (dragState.node.x ?? 0) + dx / transform.k;
+      dragState.node.y = (dragState.node.y ?? 0) + dy / transform.k;
+      moveNode(dragState.node);
+      refreshLinks();
+    } else {
+      transform.x += dx;
+      transform.y += dy;
+      refreshTransform();
+    }
+  });
+  window.addEventListener("mouseup", () => dragState = undefined);
 
   let lastId = 0;
   return {
-    element: svg
-      .call(zoom)
-      .call(zoom.transform, d3.zoomIdentity)
-      .node(),
+    element: svg,
     /** @param {number} selectedNodeId */
     setSelectedNodeId(selectedNodeId) {
-      d3.select(`#node${lastId}`)
-        .attr("stroke", "#000")
-        .attr("stroke-width", 1 / sqrtK)
-        .attr("r", nodeRadius / sqrtK);
-      d3.selectAll(`[data-originating-node-id="${lastId}"]`)
-        .style("stroke-width", linkThickness / sqrtK)
-        .attr("marker-end", "url(#end)");
       lastId = selectedNodeId;
       refreshSelectedNode();
     },
   };
 
-  function refreshSelectedNode() {
-    d3.select(`#node${lastId}`)
-      .attr("stroke", "red")
-      .attr("stroke-width", 4 / sqrtK)
-      .attr("r", (nodeRadius + 10) / sqrtK);
-    d3.selectAll(`[data-originating-node-id="${lastId}"]`)
-      .style("stroke-width", (linkThickness + 15) / sqrtK)
+  function refreshTransform() {
+    const sqrtK = Math.sqrt(transform.k);
+    viewG.setAttribute("transform", `translate(${transform.x}, ${transform.y}) scale(${transform.k})`);
+    for (const [id, circle] of nodeCircles) {
+      const isSelected = id === lastId;
+      circle.setAttribute("r", String((isSelected ? nodeRadius + 10 : nodeRadius) / sqrtK));
+      circle.setAttribute("stroke", isSelected ? "red" : "#000");
+      circle.setAttribute("stroke-width", String((isSelected ? 4 : 1) / sqrtK));
+    }
+    for (const { line } of linkElements) {
+      const isSelected = line.dataset.originatingNodeId === String(lastId);
+      line.style.strokeWidth = String((isSelected ? linkThickness + 15 : linkThickness) / sqrtK);
       // not worth the hassle to resize this
-      .attr("marker-end", "");
+      line.setAttribute("marker-end", isSelected ? "" : "url(#end)");
+    }
+    arrow.setAttribute("markerWidth", "5");
+    arrow.setAttribute("markerHeight", "5");
+    arrow.setAttribute("viewBox", `0 0 ${5 / sqrtK} ${5 / sqrtK}`);
+    arrow.setAttribute("refX", String(8 / sqrtK));
+    arrow.setAttribute("refY", String(2.5 / sqrtK));
+    arrowInnerPath.setAttribute("d", `M 0 0 L ${5 / sqrtK} ${2.5 / sqrtK} L 0 ${5 / sqrtK} z`);
+  }
+
+  function refreshSelectedNode() {
+    refreshTransform();
+    // draw the selected node above the others
+    const group = nodeGroups.get(lastId);
+    if (group != null) {
+      nodeG.appendChild(group);
+    }
+  }
+
+  function refreshLinks() {
+    for (const { line, source, target } of linkElements) {
+      line.setAttribute("x1", String(source.x));
+      line.setAttribute("y1", String(source.y));
+      line.setAttribute("x2", String(target.x));
+      line.setAttribute("y2", String(target.y));
+    }
+  }
+
+  /** @param {import("./types").GraphPrintNode} node */
+  function moveNode(node) {
+    nodeGroups.get(node.id)?.setAttribute("transform", `translate(${node.x}, ${node.y})`);
+  }
+
+  /** @param {MouseEvent} e */
+  function getSvgPoint(e) {
+    const rect = svg.getBoundingClientRect();
+    const scale = Math.max(width / rect.width, height / rect.height);
+    return { x: (e.clientX - rect.left) * scale, y: (e.clientY - rect.top) * scale };
   }
 
-  /** @param {any} d */
-  function getLineColor(d) {
-    return d.color || (d.originatingNodeId != null ? "blue" : "#000");
+  /** @param {number} id */
+  function getNode(id) {
+    const node = nodesById.get(id);
+    if (node == null) {
+      throw new Error(`Could not find node: ${id}`);
+    }
+    return node;
+  }
+
+  /**
+   * @template {keyof SVGElementTagNameMap} K
+   * @param {K} name
+   * @param {Record<string, string | number>} [attributes]
+   * @returns {SVGElementTagNameMap[K]}
+   */
+  function createSvgElement(name, attributes) {
+    const element = document.createElementNS(svgNs, name);
+    for (const [key, value] of Object.entries(attributes ?? {})) {
+      element.setAttribute(key, String(value));
+    }
+    return element;
   }
 }
 
@@ -430,6 +466,14 @@ function createInfoArea() {
   mainElement.appendChild(currentTimeLabel);
   const timeSpan = document.createElement("span");
   mainElement.appendChild(timeSpan);
+  const writerLabel = document.createElement("label");
+  writerLabel.textContent = "Writer:";
+  mainElement.appendChild(writerLabel);
+  const writerSpan = document.createElement("span");
+  mainElement.appendChild(writerSpan);
+  const stepSpan = document.createElement("span");
+  stepSpan.className = "step-info";
+  mainElement.appendChild(stepSpan);
 
   return {
     element: mainElement,
@@ -437,6 +481,17 @@ function createInfoArea() {
     setTraceIndex(index) {
       const trace = traceResult.traces[index];
       timeSpan.textContent = formatNanos(trace.nanos);
+      const info = trace.writerInfo;
+      writerSpan.textContent = `line ${info.lineNumber}, column ${info.columnNumber}, indent ${info.indentLevel}`
+        + ` (line start ${info.lineStartIndentLevel})${info.expectNewlineNext ? ", expecting newline" : ""}`;
+      const stepTexts = [];
+      if (trace.conditionValue !== undefined) {
+        stepTexts.push(`Condition resolved: ${trace.conditionValue == null ? "unresolved" : trace.conditionValue}`);
+      }
+      if (trace.restoredSavePoint) {
+        stepTexts.push("Restored save point");
+      }
+      stepSpan.textContent = stepTexts.join(" - ");
     },
   };
 }
@@ -622,6 +677,8 @@ function getNodeColor(node) {
       return "pink";
     case "conditionReevaluation":
       return "purple";
+    case "sourcePosition":
+      return "white";
   }
 }
 
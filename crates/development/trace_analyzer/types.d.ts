@@ -1,7 +1,6 @@
 declare global {
   const rawTraceResult: TracingResult;
   const specMessage: string;
-  const d3: any;
 }
 
 export interface GraphPrintNode {
@@ -10,6 +9,8 @@ export interface GraphPrintNode {
   sources: GraphPrintNode[];
   targets: GraphPrintNode[];
   depthY: number;
+  x?: number;
+  y?: number;
 }
 
 export interface TracingResult {
@@ -22,6 +23,19 @@ export interface Trace {
   nanos: number;
   printNodeId: number;
   writerNodeId: number | undefined;
+  writerInfo: WriterInfo;
+  /** Resolved value when the print node is a condition. `null` when unresolved. */
+  conditionValue: boolean | null | undefined;
+  restoredSavePoint: boolean | undefined;
+}
+
+export interface WriterInfo {
+  lineNumber: number;
+  columnNumber: number;
+  indentLevel: number;
+  lineStartIndentLevel: number;
+  indentWidth: number;
+  expectNewlineNext: boolean;
 }
 
 export interface WriterNode {
@@ -36,7 +50,7 @@ export interface PrintNode {
   printItem: PrintItem;
 }
 
-export type PrintItem = InfoItem | SignalItem | StringItem | ConditionItem | RcPathItem | AnchorItem | ConditionReevaluationItem;
+export type PrintItem = InfoItem | SignalItem | StringItem | ConditionItem | RcPathItem | AnchorItem | ConditionReevaluationItem | SourcePositionItem;
 
 export interface InfoItem {
   kind: "info";
@@ -142,6 +156,11 @@ export interface ConditionReevaluation {
   name: string;
 }
 
+export interface SourcePositionItem {
+  kind: "sourcePosition";
+  content: number;
+}
+
 export interface RcPathItem {
   kind: "rcPath";
   content: number;
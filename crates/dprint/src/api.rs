@@ -0,0 +1,399 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::bail;
+use anyhow::Result;
+use dprint_core::plugins::HostFormatRequest;
+use dprint_core::plugins::NullCancellationToken;
+
+use crate::arg_parser::FilePatternArgs;
+use crate::configuration::resolve_config_from_path;
+use crate::configuration::resolve_config_from_text;
+use crate::configuration::ResolvedConfig;
+use crate::configuration::ResolvedConfigPath;
+use crate::environment::CanonicalizedPathBuf;
+use crate::environment::Environment;
+use crate::environment::RealEnvironment;
+use crate::environment::RealEnvironmentOptions;
+use crate::patterns::FileMatcher;
+use crate::plugins::NativePlugin;
+use crate::plugins::Plugin;
+use crate::plugins::PluginCache;
+use crate::plugins::PluginResolver;
+use crate::resolution::resolve_plugins_scope;
+use crate::resolution::PluginsScope;
+use crate::utils::LogLevel;
+use crate::utils::ResolvedPath;
+use crate::utils::Semaphore;
+
+/// Where a [`Formatter`] gets its configuration from.
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+  /// A local configuration file. Its directory is used as the base path
+  /// for the "includes" and "excludes" patterns.
+  Path(PathBuf),
+  /// A configuration object in the same shape as a configuration file.
+  ///
+  /// Relative paths within it (ex. local plugins or "extends") are resolved
+  /// relative to `dir_path`, which is also the base path for the patterns.
+  Json { dir_path: PathBuf, config: serde_json::Value },
+}
+
+/// Options for creating a [`Formatter`].
+pub struct FormatterOptions {
+  pub config: ConfigSource,
+  /// Plugins compiled into the application. These replace any plugin of the
+  /// same name in the configuration and are used in addition to the others.
+  pub native_plugins: Vec<NativePlugin>,
+}
+
+impl FormatterOptions {
+  pub fn new(config: ConfigSource) -> Self {
+    Self {
+      config,
+      native_plugins: Vec::new(),
+    }
+  }
+}
+
+/// The outcome of formatting some text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatOutput {
+  /// The formatted text, which differs from the input.
+  Changed(Vec<u8>),
+  /// The text was already formatted.
+  Unchanged,
+  /// No plugin formats this file or it's excluded by the configuration.
+  Skipped,
+}
+
+/// The outcome of formatting a file on the file system.
+#[derive(Debug)]
+pub struct FormatPathResult {
+  pub path: PathBuf,
+  pub output: Result<FormatOutput>,
+}
+
+/// Formats files in-process with the CLI's configuration resolution and plugins.
+///
+/// Plugins are loaded once when this is created and kept alive until it's dropped,
+/// so reuse it for formatting many files. The methods block on an internal
+/// runtime, so they shouldn't be called from within an async context.
+pub struct Formatter {
+  // drop the session first because dropping the runtime waits
+  // on the plugin threads, which only exit once the session is gone
+  session: FormatSession<RealEnvironment>,
+  runtime: tokio::runtime::Runtime,
+}
+
+impl Formatter {
+  /// Resolves the configuration and initializes the plugins.
+  ///
+  /// Errors when no plugins were found or the configuration has diagnostics.
+  pub fn new(options: FormatterOptions) -> Result<Self> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build()?;
+    let environment = RealEnvironment::new(RealEnvironmentOptions {
+      log_level: LogLevel::Silent,
+      is_stdout_machine_readable: false,
+    })?;
+    let native_plugins = options.native_plugins.into_iter().map(|p| Box::new(p) as Box<dyn Plugin>).collect();
+    let session = runtime.block_on(FormatSession::new(environment, &options.config, native_plugins))?;
+    Ok(Self { session, runtime })
+  }
+
+  /// Formats the provided text as if it were the file at `file_path`.
+  ///
+  /// The file doesn't need to exist. A relative path is resolved against the
+  /// configuration's base path before the "includes" and "excludes" are applied.
+  pub fn format_text(&self, file_path: impl AsRef<Path>, file_text: &[u8]) -> Result<FormatOutput> {
+    self.runtime.block_on(self.session.format_text(file_path.as_ref(), file_text))
+  }
+
+  /// Formats the files at the provided paths without writing them back.
+  ///
+  /// The files are formatted concurrently and the results are in the same order as the paths.
+  pub fn format_paths(&self, file_paths: &[PathBuf]) -> Vec<FormatPathResult> {
+    self.runtime.block_on(self.session.format_paths(file_paths))
+  }
+}
+
+impl Drop for Formatter {
+  fn drop(&mut self) {
+    self.runtime.block_on(self.session.shutdown());
+  }
+}
+
+struct FormatSession<TEnvironment: Environment> {
+  environment: TEnvironment,
+  plugin_resolver: Rc<PluginResolver<TEnvironment>>,
+  scope: Rc<PluginsScope<TEnvironment>>,
+  file_matcher: FileMatcher<TEnvironment>,
+  base_path: CanonicalizedPathBuf,
+}
+
+impl<TEnvironment: Environment> FormatSession<TEnvironment> {
+  pub async fn new(environment: TEnvironment, config: &ConfigSource, native_plugins: Vec<Box<dyn Plugin>>) -> Result<Self> {
+    let plugin_cache = PluginCache::new(environment.clone());
+    let plugin_resolver = Rc::new(PluginResolver::new(environment.clone(), plugin_cache).with_native_plugins(native_plugins));
+    match Self::resolve_scope(&environment, config, &plugin_resolver).await {
+      Ok((scope, file_matcher, base_path)) => Ok(Self {
+        environment,
+        plugin_resolver,
+        scope,
+        file_matcher,
+        base_path,
+      }),
+      Err(err) => {
+        plugin_resolver.clear_and_shutdown_initialized().await;
+        Err(err)
+      }
+    }
+  }
+
+  async fn resolve_scope(
+    environment: &TEnvironment,
+    config: &ConfigSource,
+    plugin_resolver: &Rc<PluginResolver<TEnvironment>>,
+  ) -> Result<(Rc<PluginsScope<TEnvironment>>, FileMatcher<TEnvironment>, CanonicalizedPathBuf)> {
+    let config = Rc::new(resolve_config_source(config, environment).await?);
+    let scope = resolve_plugins_scope(config.clone(), environment, plugin_resolver).await?;
+    scope.ensure_plugins_found()?;
+    scope.ensure_no_global_config_diagnostics()?;
+
+    let mut messages = Vec::new();
+    for plugin in scope.plugins.values() {
+      for diagnostic in plugin.initialize().await?.config_diagnostics().await? {
+        messages.push(format!("[{}]: {}", plugin.name(), diagnostic));
+      }
+    }
+    if !messages.is_empty() {
+      bail!("Had {} configuration diagnostic(s).\n{}", messages.len(), messages.join("\n"));
+    }
+
+    let file_matcher = FileMatcher::new(environment.clone(), &config, &FilePatternArgs::default(), &config.base_path)?;
+    Ok((Rc::new(scope), file_matcher, config.base_path.clone()))
+  }
+
+  pub async fn format_text(&self, file_path: &Path, file_text: &[u8]) -> Result<FormatOutput> {
+    let file_path = if self.environment.is_absolute_path(file_path) {
+      file_path.to_path_buf()
+    } else {
+      self.base_path.join(file_path)
+    };
+    let file_path = self.environment.canonicalize(&file_path).map(|p| p.into_path_buf()).unwrap_or(file_path);
+    if !self.file_matcher.matches(&file_path) {
+      return Ok(FormatOutput::Skipped);
+    }
+    self.format_matched_text(&file_path, file_text).await
+  }
+
+  async fn format_matched_text(&self, file_path: &Path, file_text: &[u8]) -> Result<FormatOutput> {
+    if self.scope.plugin_name_maps.get_plugin_names_from_file_path(file_path).is_empty() {
+      return Ok(FormatOutput::Skipped);
+    }
+    let result = self
+      .scope
+      .format(HostFormatRequest {
+        file_path: file_path.to_path_buf(),
+        file_bytes: file_text.to_vec(),
+        range: None,
+        override_config: Default::default(),
+        hints: Default::default(),
+        token: Arc::new(NullCancellationToken),
+      })
+      .await?;
+    Ok(match result {
+      Some(text) if text != file_text => FormatOutput::Changed(text),
+      _ => FormatOutput::Unchanged,
+    })
+  }
+
+  pub async fn format_paths(&self, file_paths: &[PathBuf]) -> Vec<FormatPathResult> {
+    let semaphore = Rc::new(Semaphore::new(self.environment.max_threads()));
+    let futures = file_paths.iter().map(|path| {
+      let semaphore = semaphore.clone();
+      async move {
+        let _permit = semaphore.acquire().await;
+        FormatPathResult {
+          path: path.clone(),
+          output: self.format_path(path).await,
+        }
+      }
+    });
+    dprint_core::async_runtime::future::join_all(futures).await
+  }
+
+  async fn format_path(&self, file_path: &Path) -> Result<FormatOutput> {
+    let file_path = self.environment.canonicalize(file_path)?;
+    if !self.file_matcher.matches(&file_path) {
+      return Ok(FormatOutput::Skipped);
+    }
+    let file_text = self.environment.read_file_bytes(&file_path)?;
+    self.format_matched_text(file_path.as_ref(), &file_text).await
+  }
+
+  pub async fn shutdown(&self) {
+    self.plugin_resolver.clear_and_shutdown_initialized().await;
+  }
+}
+
+pub(crate) async fn resolve_config_source<TEnvironment: Environment>(config: &ConfigSource, environment: &TEnvironment) -> Result<ResolvedConfig> {
+  Ok(match config {
+    ConfigSource::Path(file_path) => {
+      let file_path = environment.canonicalize(file_path)?;
+      let Some(base_path) = file_path.parent() else {
+        bail!("Could not find the directory of the configuration file: {}", file_path.display());
+      };
+      let resolved_config_path = ResolvedConfigPath {
+        resolved_path: ResolvedPath::local(file_path),
+        base_path,
+      };
+      resolve_config_from_path(&resolved_config_path, environment).await?
+    }
+    ConfigSource::Json { dir_path, config } => {
+      let dir_path = environment.canonicalize(dir_path)?;
+      let resolved_config_path = ResolvedConfigPath {
+        resolved_path: ResolvedPath::local(dir_path.join_panic_relative("dprint.json")),
+        base_path: dir_path,
+      };
+      resolve_config_from_text(&config.to_string(), &resolved_config_path, environment).await?
+    }
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::environment::TestEnvironment;
+  use crate::environment::TestEnvironmentBuilder;
+
+  async fn create_session(environment: &TestEnvironment, config: ConfigSource) -> Result<FormatSession<TestEnvironment>> {
+    FormatSession::new(environment.clone(), &config, Vec::new()).await
+  }
+
+  #[test]
+  fn should_create_and_drop_formatter() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let plugin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../test-plugin/test_plugin.wasm");
+    let formatter = Formatter::new(FormatterOptions::new(ConfigSource::Json {
+      dir_path: temp_dir.path().to_path_buf(),
+      config: serde_json::json!({
+        "excludes": ["excluded/**"],
+        "plugins": [plugin_path.to_string_lossy()],
+      }),
+    }))
+    .unwrap();
+    assert_eq!(
+      formatter.format_text("file.txt", b"text").unwrap(),
+      FormatOutput::Changed(b"text_formatted".to_vec())
+    );
+    assert_eq!(formatter.format_text("excluded/file.txt", b"text").unwrap(), FormatOutput::Skipped);
+    // the plugin threads exit once the session is gone, so this would hang
+    // if the runtime were dropped before the session
+    drop(formatter);
+  }
+
+  #[test]
+  fn should_format_text_with_config_file() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin().add_remote_process_plugin().add_excludes("excluded/**");
+      })
+      .build();
+    environment.run_in_runtime(async {
+      let session = create_session(&environment, ConfigSource::Path(PathBuf::from("/dprint.json"))).await.unwrap();
+      assert_eq!(
+        session.format_text(Path::new("file.txt"), b"text").await.unwrap(),
+        FormatOutput::Changed(b"text_formatted".to_vec())
+      );
+      assert_eq!(
+        session.format_text(Path::new("file.txt_ps"), b"text_formatted_process").await.unwrap(),
+        FormatOutput::Unchanged
+      );
+      assert_eq!(session.format_text(Path::new("file.other"), b"text").await.unwrap(), FormatOutput::Skipped);
+      assert_eq!(
+        session.format_text(Path::new("/excluded/file.txt"), b"text").await.unwrap(),
+        FormatOutput::Skipped
+      );
+      // relative paths are resolved against the config's directory
+      assert_eq!(
+        session.format_text(Path::new("excluded/file.txt"), b"text").await.unwrap(),
+        FormatOutput::Skipped
+      );
+      session.shutdown().await;
+    });
+  }
+
+  #[test]
+  fn should_format_paths_with_json_config() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .write_file("/project/file1.txt", "text")
+      .write_file("/project/file2.txt", "text_formatted")
+      .write_file("/project/ignored.txt", "text")
+      .build();
+    let config = ConfigSource::Json {
+      dir_path: PathBuf::from("/project"),
+      config: serde_json::json!({
+        "excludes": ["ignored.txt"],
+        "plugins": ["https://plugins.dprint.dev/test-plugin.wasm"],
+      }),
+    };
+    let file_paths = ["/project/file1.txt", "/project/file2.txt", "/project/ignored.txt", "/project/missing.txt"]
+      .into_iter()
+      .map(PathBuf::from)
+      .collect::<Vec<_>>();
+    let results = environment.run_in_runtime(async {
+      let session = create_session(&environment, config).await.unwrap();
+      let results = session.format_paths(&file_paths).await;
+      session.shutdown().await;
+      results
+    });
+    let outputs = results
+      .iter()
+      .map(|result| (result.path.to_string_lossy().to_string(), result.output.as_ref().ok().cloned()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      outputs,
+      vec![
+        ("/project/file1.txt".to_string(), Some(FormatOutput::Changed(b"text_formatted".to_vec()))),
+        ("/project/file2.txt".to_string(), Some(FormatOutput::Unchanged)),
+        ("/project/ignored.txt".to_string(), Some(FormatOutput::Skipped)),
+        ("/project/missing.txt".to_string(), None),
+      ]
+    );
+    // the files aren't written back
+    assert_eq!(environment.read_file("/project/file1.txt").unwrap(), "text");
+  }
+
+  #[test]
+  fn should_error_for_plugin_config_diagnostics() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin().add_config_section("test-plugin", r#"{ "non-existent": 25 }"#);
+      })
+      .build();
+    let result = environment.run_in_runtime(create_session(&environment, ConfigSource::Path(PathBuf::from("/dprint.json"))));
+    assert_eq!(
+      result.err().unwrap().to_string(),
+      "Had 1 configuration diagnostic(s).\n[test-plugin]: Unknown property in configuration (non-existent)"
+    );
+  }
+
+  #[test]
+  fn should_error_when_no_plugins() {
+    let environment = TestEnvironmentBuilder::new().build();
+    let config = ConfigSource::Json {
+      dir_path: PathBuf::from("/"),
+      config: serde_json::json!({}),
+    };
+    let result = environment.run_in_runtime(create_session(&environment, config));
+    assert_eq!(
+      result.err().unwrap().to_string(),
+      "No formatting plugins found. Ensure at least one is specified in the 'plugins' array of the configuration file."
+    );
+  }
+}
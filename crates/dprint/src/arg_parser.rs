@@ -71,7 +71,12 @@ impl CliArgs {
     // these output json or other text that's read by stdout
     matches!(
       self.sub_command,
-      SubCommand::StdInFmt(..) | SubCommand::EditorInfo | SubCommand::OutputResolvedConfig | SubCommand::Completions(..)
+      SubCommand::StdInFmt(..)
+        | SubCommand::StdInFmtFiles(..)
+        | SubCommand::EditorInfo
+        | SubCommand::OutputResolvedConfig
+        | SubCommand::Completions(..)
+        | SubCommand::Bench(..)
     )
   }
 
@@ -107,13 +112,16 @@ pub enum SubCommand {
   OutputFilePaths(OutputFilePathsSubCommand),
   OutputResolvedConfig,
   OutputFormatTimes(OutputFormatTimesSubCommand),
+  Bench(BenchSubCommand),
   Version,
   License,
   Help(String),
   EditorInfo,
   EditorService(EditorServiceSubCommand),
   Lsp,
+  Daemon(DaemonSubCommand),
   StdInFmt(StdInFmtSubCommand),
+  StdInFmtFiles(StdInFmtFilesSubCommand),
   Completions(clap_complete::Shell),
   Upgrade,
   #[cfg(target_os = "windows")]
@@ -125,6 +133,7 @@ impl SubCommand {
       SubCommand::Check(a) => a.allow_no_files,
       SubCommand::Fmt(a) => a.allow_no_files,
       SubCommand::OutputFormatTimes(a) => a.allow_no_files,
+      SubCommand::Bench(a) => a.allow_no_files,
       _ => false,
     }
   }
@@ -134,8 +143,10 @@ impl SubCommand {
       SubCommand::Check(a) => Some(&a.patterns),
       SubCommand::Fmt(a) => Some(&a.patterns),
       SubCommand::StdInFmt(a) => Some(&a.patterns),
+      SubCommand::StdInFmtFiles(a) => Some(&a.patterns),
       SubCommand::OutputFilePaths(a) => Some(&a.patterns),
       SubCommand::OutputFormatTimes(a) => Some(&a.patterns),
+      SubCommand::Bench(a) => Some(&a.patterns),
       SubCommand::Config(_)
       | SubCommand::ClearCache
       | SubCommand::OutputResolvedConfig
@@ -143,6 +154,7 @@ impl SubCommand {
       | SubCommand::License
       | SubCommand::Help(_)
       | SubCommand::Lsp
+      | SubCommand::Daemon(_)
       | SubCommand::EditorInfo
       | SubCommand::EditorService(_)
       | SubCommand::Completions(_)
@@ -160,6 +172,8 @@ pub struct CheckSubCommand {
   pub list_different: bool,
   pub allow_no_files: bool,
   pub only_staged: bool,
+  /// Revision to read the files from instead of the working tree.
+  pub git_ref: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -190,9 +204,23 @@ pub struct OutputFormatTimesSubCommand {
   pub allow_no_files: bool,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct BenchSubCommand {
+  pub patterns: FilePatternArgs,
+  pub allow_no_files: bool,
+  pub warmup_iterations: u32,
+  pub iterations: u32,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct EditorServiceSubCommand {
   pub parent_pid: u32,
+  pub protocol_version: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DaemonSubCommand {
+  pub stop: bool,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -202,6 +230,12 @@ pub struct StdInFmtSubCommand {
   pub patterns: FilePatternArgs,
 }
 
+/// The files are streamed from stdin while formatting, so they aren't read here.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StdInFmtFilesSubCommand {
+  pub patterns: FilePatternArgs,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 #[cfg(target_os = "windows")]
 pub enum HiddenSubCommand {
@@ -260,6 +294,10 @@ fn inner_parse_args<TStdInReader: StdInReader>(args: Vec<String>, std_in_reader:
           file_bytes: std_in_reader.read()?,
           patterns: parse_file_patterns(matches)?,
         })
+      } else if matches.get_flag("stdin-files") {
+        SubCommand::StdInFmtFiles(StdInFmtFilesSubCommand {
+          patterns: parse_file_patterns(matches)?,
+        })
       } else {
         SubCommand::Fmt(FmtSubCommand {
           diff: matches.get_flag("diff"),
@@ -281,6 +319,7 @@ fn inner_parse_args<TStdInReader: StdInReader>(args: Vec<String>, std_in_reader:
       only_staged: matches.get_flag("staged"),
       list_different: matches.get_flag("list-different"),
       allow_no_files: matches.get_flag("allow-no-files"),
+      git_ref: matches.get_one::<String>("git-ref").map(String::from),
     }),
     ("init", _) => SubCommand::Config(ConfigSubCommand::Init),
     ("config", matches) => SubCommand::Config(match matches.subcommand().unwrap() {
@@ -300,13 +339,23 @@ fn inner_parse_args<TStdInReader: StdInReader>(args: Vec<String>, std_in_reader:
       patterns: parse_file_patterns(matches)?,
       allow_no_files: matches.get_flag("allow-no-files"),
     }),
+    ("bench", matches) => SubCommand::Bench(BenchSubCommand {
+      patterns: parse_file_patterns(matches)?,
+      allow_no_files: matches.get_flag("allow-no-files"),
+      warmup_iterations: *matches.get_one::<u32>("warmup").unwrap(),
+      iterations: *matches.get_one::<u32>("iterations").unwrap(),
+    }),
     ("version", _) => SubCommand::Version,
     ("license", _) => SubCommand::License,
     ("editor-info", _) => SubCommand::EditorInfo,
     ("editor-service", matches) => SubCommand::EditorService(EditorServiceSubCommand {
       parent_pid: matches.get_one::<String>("parent-pid").and_then(|v| v.parse::<u32>().ok()).unwrap(),
+      protocol_version: matches.get_one::<String>("protocol-version").and_then(|v| v.parse::<u32>().ok()).unwrap(),
     }),
     ("lsp", _) => SubCommand::Lsp,
+    ("daemon", matches) => SubCommand::Daemon(DaemonSubCommand {
+      stop: matches.get_flag("stop"),
+    }),
     ("completions", matches) => SubCommand::Completions(matches.get_one::<clap_complete::Shell>("shell").unwrap().to_owned()),
     ("upgrade", _) => SubCommand::Upgrade,
     #[cfg(target_os = "windows")]
@@ -504,6 +553,13 @@ EXAMPLES:
             .required(false)
             .num_args(1)
         )
+        .arg(
+          Arg::new("stdin-files")
+            .long("stdin-files")
+            .help("Format multiple files from stdin and output the results to stdout in the same format. Each file is its path, then its text, each prefixed with its byte length as a big-endian u32.")
+            .num_args(0)
+            .conflicts_with("stdin")
+        )
         .arg(
           Arg::new("diff")
             .long("diff")
@@ -536,6 +592,14 @@ EXAMPLES:
             .help("Only outputs file paths that aren't formatted and doesn't output diffs.")
             .num_args(0)
         )
+        .arg(
+          Arg::new("git-ref")
+            .long("git-ref")
+            .value_name("rev")
+            .help("Checks the files at a git revision (ex. a commit or branch) instead of the working tree. The configuration is also read from the revision.")
+            .num_args(1)
+            .conflicts_with("staged")
+        )
     )
     .subcommand(
       Command::new("config")
@@ -577,6 +641,29 @@ EXAMPLES:
         .add_allow_no_files_arg()
         .add_only_staged_arg()
     )
+    .subcommand(
+      Command::new("bench")
+        .about("Benchmarks the plugins on each file and outputs the results as JSON.")
+        .add_resolve_file_path_args()
+        .add_allow_no_files_arg()
+        .add_only_staged_arg()
+        .arg(
+          Arg::new("warmup")
+            .long("warmup")
+            .help("Number of times to format each file before measuring.")
+            .value_parser(clap::value_parser!(u32))
+            .default_value("1")
+            .num_args(1)
+        )
+        .arg(
+          Arg::new("iterations")
+            .long("iterations")
+            .help("Number of measured iterations.")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .default_value("5")
+            .num_args(1)
+        )
+    )
     .subcommand(
       Command::new("clear-cache")
         .about("Deletes the plugin cache directory.")
@@ -610,11 +697,28 @@ EXAMPLES:
             .required(true)
             .num_args(1)
         )
+        .arg(
+          Arg::new("protocol-version")
+            .long("protocol-version")
+            .value_parser(["5", "6"])
+            .default_value("5")
+            .num_args(1)
+        )
     )
     .subcommand(
       Command::new("lsp")
       .about("Starts up a language server for formatting files.")
     )
+    .subcommand(
+      Command::new("daemon")
+        .about("Runs a background process that keeps plugins loaded between `fmt` and `check` runs.")
+        .arg(
+          Arg::new("stop")
+            .long("stop")
+            .help("Stops the running daemon.")
+            .num_args(0)
+        )
+    )
     .arg(
       Arg::new("config")
         .long("config")
@@ -823,6 +927,62 @@ mod test {
     assert_eq!(fmt_cmd.allow_no_files, true);
   }
 
+  #[test]
+  fn bench_iteration_args() {
+    let cmd = parse_bench_sub_command(vec!["bench"]).unwrap();
+    assert_eq!((cmd.warmup_iterations, cmd.iterations), (1, 5));
+    let cmd = parse_bench_sub_command(vec!["bench", "--warmup", "0", "--iterations", "20"]).unwrap();
+    assert_eq!((cmd.warmup_iterations, cmd.iterations), (0, 20));
+    assert!(parse_bench_sub_command(vec!["bench", "--iterations", "0"]).is_err());
+  }
+
+  #[test]
+  fn daemon_stop_arg() {
+    assert_eq!(
+      test_args(vec!["daemon"]).unwrap().sub_command,
+      SubCommand::Daemon(DaemonSubCommand { stop: false })
+    );
+    assert_eq!(
+      test_args(vec!["daemon", "--stop"]).unwrap().sub_command,
+      SubCommand::Daemon(DaemonSubCommand { stop: true })
+    );
+  }
+
+  #[test]
+  fn check_git_ref_arg() {
+    let cmd = parse_check_sub_command(vec!["check"]).unwrap();
+    assert_eq!(cmd.git_ref, None);
+    let cmd = parse_check_sub_command(vec!["check", "--git-ref", "origin/main"]).unwrap();
+    assert_eq!(cmd.git_ref, Some("origin/main".to_string()));
+    assert!(test_args(vec!["check", "--git-ref", "HEAD", "--staged"]).is_err());
+  }
+
+  #[test]
+  fn stdin_files_arg() {
+    let args = test_args(vec!["fmt", "--stdin-files"]).unwrap();
+    assert_eq!(
+      args.sub_command,
+      SubCommand::StdInFmtFiles(StdInFmtFilesSubCommand { patterns: Default::default() })
+    );
+    assert!(test_args(vec!["fmt", "--stdin-files", "--stdin", "file.txt"]).is_err());
+  }
+
+  fn parse_check_sub_command(args: Vec<&str>) -> Result<CheckSubCommand, ParseArgsError> {
+    let args = test_args(args)?;
+    match args.sub_command {
+      SubCommand::Check(cmd) => Ok(cmd),
+      _ => unreachable!(),
+    }
+  }
+
+  fn parse_bench_sub_command(args: Vec<&str>) -> Result<BenchSubCommand, ParseArgsError> {
+    let args = test_args(args)?;
+    match args.sub_command {
+      SubCommand::Bench(cmd) => Ok(cmd),
+      _ => unreachable!(),
+    }
+  }
+
   fn parse_fmt_sub_command(args: Vec<&str>) -> Result<FmtSubCommand, ParseArgsError> {
     let args = test_args(args)?;
     match args.sub_command {
@@ -0,0 +1,297 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use dprint_core::plugins::NullCancellationToken;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::arg_parser::BenchSubCommand;
+use crate::arg_parser::CliArgs;
+use crate::environment::Environment;
+use crate::plugins::PluginResolver;
+use crate::resolution::resolve_plugins_scope_and_paths;
+use crate::resolution::GetPluginResult;
+use crate::resolution::InitializedPluginWithConfigFormatRequest;
+use crate::resolution::PluginWithConfig;
+use crate::resolution::PluginsScope;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchOutput {
+  warmup_iterations: u32,
+  iterations: u32,
+  plugins: Vec<PluginBenchOutput>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginBenchOutput {
+  name: String,
+  version: String,
+  /// Time to create a new instance of the plugin.
+  instantiation: DurationStats,
+  /// Time for a new instance to receive the configuration. For Wasm plugins
+  /// this includes creating the Wasm instance, which is done lazily.
+  config_registration: DurationStats,
+  /// Format time across all the files.
+  format: DurationStats,
+  files: Vec<FileBenchOutput>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileBenchOutput {
+  path: PathBuf,
+  format: DurationStats,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DurationStats {
+  samples: usize,
+  min_ms: f64,
+  mean_ms: f64,
+  p50_ms: f64,
+  p90_ms: f64,
+  p99_ms: f64,
+  max_ms: f64,
+}
+
+impl DurationStats {
+  fn from_durations(durations: &[Duration]) -> Self {
+    let mut durations = durations.to_vec();
+    durations.sort();
+    let percentile = |p: f64| -> f64 {
+      if durations.is_empty() {
+        return 0.0;
+      }
+      // nearest-rank method
+      let rank = (p / 100.0 * durations.len() as f64).ceil() as usize;
+      to_ms(durations[rank.max(1) - 1])
+    };
+    let total: Duration = durations.iter().sum();
+    Self {
+      samples: durations.len(),
+      min_ms: durations.first().copied().map(to_ms).unwrap_or(0.0),
+      mean_ms: if durations.is_empty() {
+        0.0
+      } else {
+        round_ms(total.as_secs_f64() * 1000.0 / durations.len() as f64)
+      },
+      p50_ms: percentile(50.0),
+      p90_ms: percentile(90.0),
+      p99_ms: percentile(99.0),
+      max_ms: durations.last().copied().map(to_ms).unwrap_or(0.0),
+    }
+  }
+}
+
+fn to_ms(duration: Duration) -> f64 {
+  round_ms(duration.as_secs_f64() * 1000.0)
+}
+
+fn round_ms(value: f64) -> f64 {
+  // microsecond precision
+  (value * 1000.0).round() / 1000.0
+}
+
+#[derive(Default)]
+struct PluginBench {
+  version: String,
+  instantiation: Vec<Duration>,
+  config_registration: Vec<Duration>,
+  files: IndexMap<PathBuf, Vec<Duration>>,
+}
+
+pub async fn bench<TEnvironment: Environment>(
+  cmd: &BenchSubCommand,
+  args: &CliArgs,
+  environment: &TEnvironment,
+  plugin_resolver: &Rc<PluginResolver<TEnvironment>>,
+) -> Result<()> {
+  let scopes = resolve_plugins_scope_and_paths(args, &cmd.patterns, environment, plugin_resolver).await?;
+  scopes.ensure_valid_for_cli_args(args)?;
+  let mut benches: IndexMap<String, PluginBench> = IndexMap::new();
+
+  for scope_and_paths in scopes.into_iter() {
+    let scope = Rc::new(scope_and_paths.scope);
+    for plugin in scope.plugins.values() {
+      let bench = benches.entry(plugin.name().to_string()).or_default();
+      bench.version = plugin.info().version.clone();
+      bench_instantiation(plugin, cmd, bench).await?;
+    }
+
+    // run sequentially so the plugins don't compete for the cpu
+    let mut file_paths_by_plugins = scope_and_paths.file_paths_by_plugins.into_vec();
+    file_paths_by_plugins.sort_by(|a, b| a.1.cmp(&b.1));
+    for (plugin_names, mut file_paths) in file_paths_by_plugins {
+      file_paths.sort();
+      let plugin_names = plugin_names.names().map(ToOwned::to_owned).collect::<Vec<_>>();
+      for file_path in file_paths {
+        let mut file_bytes = environment.read_file_bytes(&file_path)?;
+        for plugin_name in &plugin_names {
+          let (durations, new_bytes) = bench_format(&scope, plugin_name, &file_path, file_bytes, cmd, environment)
+            .await
+            .with_context(|| format!("Error formatting {}", file_path.display()))?;
+          file_bytes = new_bytes;
+          benches
+            .get_mut(plugin_name)
+            .unwrap()
+            .files
+            .entry(file_path.clone())
+            .or_default()
+            .extend(durations);
+        }
+      }
+    }
+  }
+
+  let output = BenchOutput {
+    warmup_iterations: cmd.warmup_iterations,
+    iterations: cmd.iterations,
+    plugins: benches
+      .into_iter()
+      .map(|(name, bench)| PluginBenchOutput {
+        name,
+        version: bench.version,
+        instantiation: DurationStats::from_durations(&bench.instantiation),
+        config_registration: DurationStats::from_durations(&bench.config_registration),
+        format: DurationStats::from_durations(&bench.files.values().flatten().copied().collect::<Vec<_>>()),
+        files: bench
+          .files
+          .into_iter()
+          .map(|(path, durations)| FileBenchOutput {
+            path,
+            format: DurationStats::from_durations(&durations),
+          })
+          .collect(),
+      })
+      .collect(),
+  };
+  environment.log_machine_readable(serde_json::to_string_pretty(&output)?.as_bytes());
+  Ok(())
+}
+
+async fn bench_instantiation(plugin: &Rc<PluginWithConfig>, cmd: &BenchSubCommand, bench: &mut PluginBench) -> Result<()> {
+  for i in 0..cmd.warmup_iterations + cmd.iterations {
+    let start = Instant::now();
+    let instance = plugin.plugin.initialize_unshared().await?;
+    let instantiation = start.elapsed();
+    let start = Instant::now();
+    let result = instance.file_matching_info(plugin.format_config.clone()).await;
+    let config_registration = start.elapsed();
+    instance.shutdown().await;
+    result?;
+    if i >= cmd.warmup_iterations {
+      bench.instantiation.push(instantiation);
+      bench.config_registration.push(config_registration);
+    }
+  }
+  Ok(())
+}
+
+/// Formats the file with the plugin, returning the measured durations and the formatted text.
+async fn bench_format<TEnvironment: Environment>(
+  scope: &Rc<PluginsScope<TEnvironment>>,
+  plugin_name: &str,
+  file_path: &Path,
+  file_bytes: Vec<u8>,
+  cmd: &BenchSubCommand,
+  environment: &TEnvironment,
+) -> Result<(Vec<Duration>, Vec<u8>)> {
+  let plugin = scope.get_plugin(plugin_name);
+  let instance = match plugin.get_or_create_checking_config_diagnostics(environment).await? {
+    GetPluginResult::Success(instance) => instance,
+    GetPluginResult::HadDiagnostics(count) => bail!("Had {} configuration errors.", count),
+  };
+  let mut durations = Vec::with_capacity(cmd.iterations as usize);
+  let mut formatted_bytes = None;
+  for i in 0..cmd.warmup_iterations + cmd.iterations {
+    let start = Instant::now();
+    // host formatting is included in the time of the plugin that requested it
+    let result = instance
+      .format_text(InitializedPluginWithConfigFormatRequest {
+        file_path: file_path.to_path_buf(),
+        file_bytes: file_bytes.clone(),
+        range: None,
+        override_config: Default::default(),
+        on_host_format: scope.create_host_format_callback(),
+        token: Arc::new(NullCancellationToken),
+      })
+      .await?;
+    if i >= cmd.warmup_iterations {
+      durations.push(start.elapsed());
+    }
+    formatted_bytes = result;
+  }
+  Ok((durations, formatted_bytes.unwrap_or(file_bytes)))
+}
+
+#[cfg(test)]
+mod test {
+  use std::time::Duration;
+
+  use pretty_assertions::assert_eq;
+
+  use super::DurationStats;
+  use crate::environment::TestEnvironmentBuilder;
+  use crate::test_helpers::run_test_cli;
+
+  #[test]
+  fn should_output_bench_json() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
+      .write_file("/file.txt", "plugin: text")
+      .write_file("/file2.txt", "text")
+      .write_file("/file3.txt_ps", "text")
+      .build();
+    run_test_cli(vec!["bench", "--warmup", "0", "--iterations", "3", "**/*.*"], &environment).unwrap();
+    let stdout = environment.take_stdout_messages();
+    assert_eq!(stdout.len(), 1);
+    let output: serde_json::Value = serde_json::from_str(&stdout[0]).unwrap();
+    assert_eq!(output["warmupIterations"], 0);
+    assert_eq!(output["iterations"], 3);
+    let plugins = output["plugins"].as_array().unwrap();
+    assert_eq!(
+      plugins.iter().map(|p| p["name"].as_str().unwrap()).collect::<Vec<_>>(),
+      vec!["test-plugin", "test-process-plugin"]
+    );
+    let wasm_plugin = &plugins[0];
+    assert_eq!(wasm_plugin["instantiation"]["samples"], 3);
+    assert_eq!(wasm_plugin["configRegistration"]["samples"], 3);
+    assert_eq!(wasm_plugin["format"]["samples"], 6);
+    assert_eq!(
+      wasm_plugin["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["path"].as_str().unwrap())
+        .collect::<Vec<_>>(),
+      vec!["/file.txt", "/file2.txt"]
+    );
+    assert_eq!(plugins[1]["format"]["samples"], 3);
+  }
+
+  #[test]
+  fn should_calculate_duration_stats() {
+    let durations = (1..=10).rev().map(Duration::from_millis).collect::<Vec<_>>();
+    assert_eq!(
+      DurationStats::from_durations(&durations),
+      DurationStats {
+        samples: 10,
+        min_ms: 1.0,
+        mean_ms: 5.5,
+        p50_ms: 5.0,
+        p90_ms: 9.0,
+        p99_ms: 10.0,
+        max_ms: 10.0,
+      }
+    );
+    assert_eq!(DurationStats::from_durations(&[]).samples, 0);
+  }
+}
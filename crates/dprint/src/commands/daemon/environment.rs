@@ -0,0 +1,305 @@
+use anyhow::bail;
+use anyhow::Result;
+use dprint_core::async_runtime::async_trait;
+use dprint_core::communication::MessageWriter;
+use parking_lot::Mutex;
+use std::ffi::OsString;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::messages::DaemonResponse;
+use crate::environment::CanonicalizedPathBuf;
+use crate::environment::DirEntry;
+use crate::environment::Environment;
+use crate::environment::FilePermissions;
+use crate::environment::GitBlobReader;
+use crate::environment::GitTree;
+use crate::environment::UrlDownloader;
+use crate::plugins::CompilationResult;
+use crate::utils::LogLevel;
+
+pub type DaemonOutputWriter = MessageWriter<Box<dyn Write + Send>>;
+
+/// Options of the CLI invocation the daemon is currently running a command for.
+pub struct DaemonRequestOptions {
+  pub cwd: CanonicalizedPathBuf,
+  pub log_level: LogLevel,
+  pub is_stdout_machine_readable: bool,
+}
+
+struct DaemonRequestState {
+  options: DaemonRequestOptions,
+  stdin: Option<Box<dyn Read + Send>>,
+  writer: DaemonOutputWriter,
+  last_context_name: String,
+}
+
+/// Wraps the daemon's environment so output and the current working
+/// directory come from the client while a request is running.
+///
+/// Requests are run one at a time, so there's only ever one client.
+#[derive(Clone)]
+pub struct DaemonEnvironment<TEnvironment: Environment> {
+  inner: TEnvironment,
+  request: Arc<Mutex<Option<DaemonRequestState>>>,
+}
+
+impl<TEnvironment: Environment> DaemonEnvironment<TEnvironment> {
+  pub fn new(inner: TEnvironment) -> Self {
+    Self {
+      inner,
+      request: Default::default(),
+    }
+  }
+
+  pub fn start_request(&self, options: DaemonRequestOptions, stdin: Box<dyn Read + Send>, writer: DaemonOutputWriter) {
+    *self.request.lock() = Some(DaemonRequestState {
+      options,
+      stdin: Some(stdin),
+      writer,
+      last_context_name: "dprint".to_string(),
+    });
+  }
+
+  pub fn end_request(&self) -> Option<DaemonOutputWriter> {
+    self.request.lock().take().map(|state| state.writer)
+  }
+
+  fn log_to_client(&self, is_stdout: bool, text: &str, context_name: &str) -> bool {
+    let mut request = self.request.lock();
+    let Some(state) = request.as_mut() else {
+      return false;
+    };
+    let mut output_text = String::new();
+    if state.last_context_name != context_name {
+      if !is_stdout || !state.options.is_stdout_machine_readable {
+        output_text.push_str(&format!("[{}]\n", context_name));
+      }
+      state.last_context_name = context_name.to_string();
+    }
+    output_text.push_str(text);
+    if !output_text.ends_with('\n') {
+      output_text.push('\n');
+    }
+    let bytes = output_text.into_bytes();
+    // ignore the error because it only fails when the client went away
+    let _ = if is_stdout {
+      DaemonResponse::Stdout(bytes)
+    } else {
+      DaemonResponse::Stderr(bytes)
+    }
+    .write(&mut state.writer);
+    true
+  }
+}
+
+#[async_trait(?Send)]
+impl<TEnvironment: Environment> UrlDownloader for DaemonEnvironment<TEnvironment> {
+  async fn download_file(&self, url: &str) -> Result<Option<Vec<u8>>> {
+    self.inner.download_file(url).await
+  }
+}
+
+#[async_trait]
+impl<TEnvironment: Environment> Environment for DaemonEnvironment<TEnvironment> {
+  fn is_real(&self) -> bool {
+    self.inner.is_real()
+  }
+
+  fn env_var(&self, name: &str) -> Option<OsString> {
+    self.inner.env_var(name)
+  }
+
+  fn get_staged_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
+    self.inner.get_staged_files(dir_path)
+  }
+
+  fn get_git_tree(&self, dir_path: &Path, git_ref: &str) -> Result<GitTree> {
+    self.inner.get_git_tree(dir_path, git_ref)
+  }
+
+  fn git_blob_reader(&self, dir_path: &Path) -> Result<Box<dyn GitBlobReader>> {
+    self.inner.git_blob_reader(dir_path)
+  }
+
+  fn read_file(&self, file_path: impl AsRef<Path>) -> Result<String> {
+    self.inner.read_file(file_path)
+  }
+
+  fn read_file_bytes(&self, file_path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    self.inner.read_file_bytes(file_path)
+  }
+
+  fn write_file_bytes(&self, file_path: impl AsRef<Path>, bytes: &[u8]) -> Result<()> {
+    self.inner.write_file_bytes(file_path, bytes)
+  }
+
+  fn rename(&self, path_from: impl AsRef<Path>, path_to: impl AsRef<Path>) -> Result<()> {
+    self.inner.rename(path_from, path_to)
+  }
+
+  fn remove_file(&self, file_path: impl AsRef<Path>) -> Result<()> {
+    self.inner.remove_file(file_path)
+  }
+
+  fn remove_dir_all(&self, dir_path: impl AsRef<Path>) -> Result<()> {
+    self.inner.remove_dir_all(dir_path)
+  }
+
+  fn dir_info(&self, dir_path: impl AsRef<Path>) -> std::io::Result<Vec<DirEntry>> {
+    self.inner.dir_info(dir_path)
+  }
+
+  fn path_exists(&self, file_path: impl AsRef<Path>) -> bool {
+    self.inner.path_exists(file_path)
+  }
+
+  fn canonicalize(&self, path: impl AsRef<Path>) -> Result<CanonicalizedPathBuf> {
+    self.inner.canonicalize(path)
+  }
+
+  fn is_absolute_path(&self, path: impl AsRef<Path>) -> bool {
+    self.inner.is_absolute_path(path)
+  }
+
+  fn file_permissions(&self, path: impl AsRef<Path>) -> Result<FilePermissions> {
+    self.inner.file_permissions(path)
+  }
+
+  fn set_file_permissions(&self, path: impl AsRef<Path>, permissions: FilePermissions) -> Result<()> {
+    self.inner.set_file_permissions(path, permissions)
+  }
+
+  fn mk_dir_all(&self, path: impl AsRef<Path>) -> Result<()> {
+    self.inner.mk_dir_all(path)
+  }
+
+  fn cwd(&self) -> CanonicalizedPathBuf {
+    match self.request.lock().as_ref() {
+      Some(state) => state.options.cwd.clone(),
+      None => self.inner.cwd(),
+    }
+  }
+
+  fn current_exe(&self) -> Result<PathBuf> {
+    self.inner.current_exe()
+  }
+
+  fn __log__(&self, text: &str) {
+    let is_machine_readable = self.request.lock().as_ref().map(|state| state.options.is_stdout_machine_readable);
+    match is_machine_readable {
+      Some(true) => {}
+      Some(false) => {
+        self.log_to_client(true, text, "dprint");
+      }
+      None => self.inner.__log__(text),
+    }
+  }
+
+  fn log_stderr_with_context(&self, text: &str, context_name: &str) {
+    if !self.log_to_client(false, text, context_name) {
+      self.inner.log_stderr_with_context(text, context_name);
+    }
+  }
+
+  fn log_machine_readable(&self, bytes: &[u8]) {
+    match self.request.lock().as_mut() {
+      Some(state) => {
+        let _ = DaemonResponse::Stdout(bytes.to_vec()).write(&mut state.writer);
+      }
+      None => self.inner.log_machine_readable(bytes),
+    }
+  }
+
+  fn log_action_with_progress<TResult: Send + Sync, TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + Send + Sync>(
+    &self,
+    message: &str,
+    action: TCreate,
+    total_size: usize,
+  ) -> TResult {
+    if self.request.lock().is_some() {
+      // progress bars aren't shown in the client
+      action(Box::new(|_| {}))
+    } else {
+      self.inner.log_action_with_progress(message, action, total_size)
+    }
+  }
+
+  fn get_cache_dir(&self) -> CanonicalizedPathBuf {
+    self.inner.get_cache_dir()
+  }
+
+  fn get_home_dir(&self) -> Option<CanonicalizedPathBuf> {
+    self.inner.get_home_dir()
+  }
+
+  fn cpu_arch(&self) -> String {
+    self.inner.cpu_arch()
+  }
+
+  fn os(&self) -> String {
+    self.inner.os()
+  }
+
+  fn max_threads(&self) -> usize {
+    self.inner.max_threads()
+  }
+
+  fn cli_version(&self) -> String {
+    self.inner.cli_version()
+  }
+
+  fn get_time_secs(&self) -> u64 {
+    self.inner.get_time_secs()
+  }
+
+  fn get_selection(&self, _prompt_message: &str, _item_indent_width: u16, _items: &[String]) -> Result<usize> {
+    bail!("Prompting is not supported in the daemon.")
+  }
+
+  fn get_multi_selection(&self, _prompt_message: &str, _item_indent_width: u16, _items: &[(bool, String)]) -> Result<Vec<usize>> {
+    bail!("Prompting is not supported in the daemon.")
+  }
+
+  fn confirm(&self, _prompt_message: &str, _default_value: bool) -> Result<bool> {
+    bail!("Prompting is not supported in the daemon.")
+  }
+
+  fn is_ci(&self) -> bool {
+    self.inner.is_ci()
+  }
+
+  fn log_level(&self) -> LogLevel {
+    match self.request.lock().as_ref() {
+      Some(state) => state.options.log_level,
+      None => self.inner.log_level(),
+    }
+  }
+
+  fn compile_wasm(&self, wasm_bytes: &[u8]) -> Result<CompilationResult> {
+    self.inner.compile_wasm(wasm_bytes)
+  }
+
+  fn wasm_cache_key(&self) -> String {
+    self.inner.wasm_cache_key()
+  }
+
+  async fn cpu_usage(&self) -> u8 {
+    self.inner.cpu_usage().await
+  }
+
+  fn stdout(&self) -> Box<dyn Write + Send> {
+    self.inner.stdout()
+  }
+
+  fn stdin(&self) -> Box<dyn Read + Send> {
+    // the daemon's own stdin is never used for a client
+    match self.request.lock().as_mut().and_then(|state| state.stdin.take()) {
+      Some(stdin) => stdin,
+      None => Box::new(std::io::empty()),
+    }
+  }
+}
@@ -0,0 +1,140 @@
+use dprint_core::communication::MessageReader;
+use dprint_core::communication::MessageWriter;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Result;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// A message sent from a CLI invocation to the daemon.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DaemonRequest {
+  Run(RunDaemonRequest),
+  Stop,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunDaemonRequest {
+  pub cwd: PathBuf,
+  /// The CLI arguments, including the executable.
+  pub args: Vec<String>,
+  /// Text read from stdin for `fmt --stdin`.
+  pub stdin: Vec<u8>,
+}
+
+impl DaemonRequest {
+  pub fn read<TRead: Read + Unpin>(reader: &mut MessageReader<TRead>) -> Result<DaemonRequest> {
+    let request = match reader.read_u32()? {
+      0 => {
+        let cwd = PathBuf::from(read_string(reader)?);
+        let args_len = reader.read_u32()?;
+        let mut args = Vec::with_capacity(args_len as usize);
+        for _ in 0..args_len {
+          args.push(read_string(reader)?);
+        }
+        let stdin = reader.read_sized_bytes()?;
+        DaemonRequest::Run(RunDaemonRequest { cwd, args, stdin })
+      }
+      1 => DaemonRequest::Stop,
+      kind => return Err(std::io::Error::new(ErrorKind::InvalidData, format!("Unknown daemon request kind: {}", kind))),
+    };
+    reader.read_success_bytes()?;
+    Ok(request)
+  }
+
+  pub fn write<TWrite: Write + Unpin>(&self, writer: &mut MessageWriter<TWrite>) -> Result<()> {
+    match self {
+      DaemonRequest::Run(request) => {
+        writer.send_u32(0)?;
+        writer.send_sized_bytes(request.cwd.to_string_lossy().as_bytes())?;
+        writer.send_u32(request.args.len() as u32)?;
+        for arg in &request.args {
+          writer.send_sized_bytes(arg.as_bytes())?;
+        }
+        writer.send_sized_bytes(&request.stdin)?;
+      }
+      DaemonRequest::Stop => writer.send_u32(1)?,
+    }
+    writer.send_success_bytes()?;
+    writer.flush()
+  }
+}
+
+/// A message sent from the daemon to the CLI invocation that made the request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DaemonResponse {
+  Stdout(Vec<u8>),
+  Stderr(Vec<u8>),
+  /// The request finished. No more messages follow.
+  Exit(i32),
+}
+
+impl DaemonResponse {
+  pub fn read<TRead: Read + Unpin>(reader: &mut MessageReader<TRead>) -> Result<DaemonResponse> {
+    let response = match reader.read_u32()? {
+      0 => DaemonResponse::Stdout(reader.read_sized_bytes()?),
+      1 => DaemonResponse::Stderr(reader.read_sized_bytes()?),
+      2 => DaemonResponse::Exit(reader.read_u32()? as i32),
+      kind => return Err(std::io::Error::new(ErrorKind::InvalidData, format!("Unknown daemon response kind: {}", kind))),
+    };
+    reader.read_success_bytes()?;
+    Ok(response)
+  }
+
+  pub fn write<TWrite: Write + Unpin>(&self, writer: &mut MessageWriter<TWrite>) -> Result<()> {
+    match self {
+      DaemonResponse::Stdout(bytes) => {
+        writer.send_u32(0)?;
+        writer.send_sized_bytes(bytes)?;
+      }
+      DaemonResponse::Stderr(bytes) => {
+        writer.send_u32(1)?;
+        writer.send_sized_bytes(bytes)?;
+      }
+      DaemonResponse::Exit(code) => {
+        writer.send_u32(2)?;
+        writer.send_u32(*code as u32)?;
+      }
+    }
+    writer.send_success_bytes()?;
+    writer.flush()
+  }
+}
+
+fn read_string<TRead: Read + Unpin>(reader: &mut MessageReader<TRead>) -> Result<String> {
+  String::from_utf8(reader.read_sized_bytes()?).map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn should_round_trip_messages() {
+    let request = DaemonRequest::Run(RunDaemonRequest {
+      cwd: PathBuf::from("/project"),
+      args: vec!["dprint".to_string(), "fmt".to_string()],
+      stdin: vec![1, 2, 3],
+    });
+    let mut bytes = Vec::new();
+    request.write(&mut MessageWriter::new(&mut bytes)).unwrap();
+    DaemonRequest::Stop.write(&mut MessageWriter::new(&mut bytes)).unwrap();
+    let mut reader = MessageReader::new(bytes.as_slice());
+    assert_eq!(DaemonRequest::read(&mut reader).unwrap(), request);
+    assert_eq!(DaemonRequest::read(&mut reader).unwrap(), DaemonRequest::Stop);
+
+    let responses = vec![
+      DaemonResponse::Stdout(b"out".to_vec()),
+      DaemonResponse::Stderr(b"err".to_vec()),
+      DaemonResponse::Exit(-1),
+    ];
+    let mut bytes = Vec::new();
+    for response in &responses {
+      response.write(&mut MessageWriter::new(&mut bytes)).unwrap();
+    }
+    let mut reader = MessageReader::new(bytes.as_slice());
+    for response in responses {
+      assert_eq!(DaemonResponse::read(&mut reader).unwrap(), response);
+    }
+  }
+}
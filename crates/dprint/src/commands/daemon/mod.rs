@@ -0,0 +1,27 @@
+#[cfg(unix)]
+mod environment;
+#[cfg(unix)]
+mod messages;
+#[cfg(unix)]
+mod unix;
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(not(unix))]
+pub async fn run_daemon<TEnvironment: crate::environment::Environment>(
+  _cmd: &crate::arg_parser::DaemonSubCommand,
+  _environment: &TEnvironment,
+) -> anyhow::Result<()> {
+  anyhow::bail!("The daemon is only supported on Unix-like operating systems.")
+}
+
+/// The daemon isn't supported on this platform, so commands always run in-process.
+#[cfg(not(unix))]
+pub fn run_in_daemon<TEnvironment: crate::environment::Environment>(
+  _raw_args: &[String],
+  _args: &crate::arg_parser::CliArgs,
+  _environment: &TEnvironment,
+) -> anyhow::Result<Option<i32>> {
+  Ok(None)
+}
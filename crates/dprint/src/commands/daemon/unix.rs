@@ -0,0 +1,449 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use dprint_core::communication::MessageReader;
+use dprint_core::communication::MessageWriter;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::environment::DaemonEnvironment;
+use super::environment::DaemonOutputWriter;
+use super::environment::DaemonRequestOptions;
+use super::messages::DaemonRequest;
+use super::messages::DaemonResponse;
+use super::messages::RunDaemonRequest;
+use crate::arg_parser::parse_args;
+use crate::arg_parser::CliArgs;
+use crate::arg_parser::DaemonSubCommand;
+use crate::arg_parser::SubCommand;
+use crate::commands;
+use crate::environment::Environment;
+use crate::environment::GitTreeEnvironment;
+use crate::native_plugins::registered_native_plugins;
+use crate::plugins::PluginCache;
+use crate::plugins::PluginResolver;
+use crate::run_cli::AppError;
+use crate::utils::LogLevel;
+use crate::utils::StdInReader;
+
+/// How long a client has to send its request after connecting.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait on a client for more of its streamed stdin or for it
+/// to accept output before giving up on the request.
+const CLIENT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn get_socket_path(environment: &impl Environment) -> PathBuf {
+  // include the version so a CLI never talks to a daemon from another version
+  environment.get_cache_dir().join(format!("daemon-{}.sock", environment.cli_version()))
+}
+
+pub async fn run_daemon<TEnvironment: Environment>(cmd: &DaemonSubCommand, environment: &TEnvironment) -> Result<()> {
+  let socket_path = get_socket_path(environment);
+  if cmd.stop {
+    return stop_daemon(&socket_path, environment);
+  }
+
+  if UnixStream::connect(&socket_path).is_ok() {
+    bail!("The daemon is already running. Stop it with `dprint daemon --stop`.");
+  }
+  if environment.path_exists(&socket_path) {
+    // left over from a daemon that didn't exit gracefully
+    environment.remove_file(&socket_path)?;
+  }
+  let listener = UnixListener::bind(&socket_path).with_context(|| format!("Failed listening on {}", socket_path.display()))?;
+  log_stderr_info!(environment, "Daemon listening on {}", socket_path.display());
+
+  // wrapped so `check --git-ref` can reuse the warm plugins
+  let daemon_environment = GitTreeEnvironment::new(DaemonEnvironment::new(environment.clone()));
+  let plugin_resolver =
+    Rc::new(PluginResolver::new(daemon_environment.clone(), PluginCache::new(daemon_environment.clone())).with_native_plugins(registered_native_plugins()));
+  let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(DaemonRequest, UnixStream)>();
+  dprint_core::async_runtime::spawn_blocking({
+    let environment = environment.clone();
+    move || {
+      for stream in listener.incoming() {
+        if tx.is_closed() {
+          return;
+        }
+        let stream = match stream {
+          Ok(stream) => stream,
+          Err(err) => {
+            log_warn!(environment, "Failed accepting daemon connection: {:#}", err);
+            continue;
+          }
+        };
+        // read on a separate task so a slow client doesn't hold up accepting other connections
+        dprint_core::async_runtime::spawn_blocking({
+          let environment = environment.clone();
+          let tx = tx.clone();
+          move || match read_request(&stream) {
+            Ok(request) => {
+              let _ = tx.send((request, stream));
+            }
+            Err(err) => log_debug!(environment, "Failed reading daemon request: {:#}", err),
+          }
+        });
+      }
+    }
+  });
+
+  while let Some((request, stream)) = rx.recv().await {
+    match request {
+      // the client streams its stdin over the connection after the request
+      DaemonRequest::Run(request) => match stream.try_clone() {
+        Ok(stdin) => {
+          run_request(
+            &daemon_environment,
+            &plugin_resolver,
+            request,
+            Box::new(ClientStdIn(stdin)),
+            MessageWriter::new(Box::new(stream)),
+          )
+          .await
+        }
+        Err(err) => log_debug!(environment, "Failed cloning daemon connection: {:#}", err),
+      },
+      DaemonRequest::Stop => {
+        let _ = DaemonResponse::Exit(0).write(&mut MessageWriter::new(&stream));
+        break;
+      }
+    }
+  }
+
+  // wake up the listener thread so it sees the channel is closed
+  drop(rx);
+  let _ = UnixStream::connect(&socket_path);
+  plugin_resolver.clear_and_shutdown_initialized().await;
+  environment.remove_file(&socket_path)?;
+  log_stderr_info!(environment, "Daemon stopped.");
+  Ok(())
+}
+
+fn read_request(stream: &UnixStream) -> Result<DaemonRequest> {
+  stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+  let request = DaemonRequest::read(&mut MessageReader::new(stream))?;
+  // requests run one at a time, so a client that stalls while streaming its
+  // stdin or reading the output shouldn't hold up the other clients forever
+  stream.set_read_timeout(Some(CLIENT_IDLE_TIMEOUT))?;
+  stream.set_write_timeout(Some(CLIENT_IDLE_TIMEOUT))?;
+  Ok(request)
+}
+
+/// The stdin a client streams over the connection after its request.
+struct ClientStdIn(UnixStream);
+
+impl Read for ClientStdIn {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    self.0.read(buf).map_err(|err| match err.kind() {
+      ErrorKind::WouldBlock | ErrorKind::TimedOut => std::io::Error::new(ErrorKind::TimedOut, "Timed out waiting for the client to send more of stdin."),
+      _ => err,
+    })
+  }
+}
+
+fn stop_daemon(socket_path: &std::path::Path, environment: &impl Environment) -> Result<()> {
+  let Ok(stream) = UnixStream::connect(socket_path) else {
+    log_stderr_info!(environment, "The daemon is not running.");
+    return Ok(());
+  };
+  DaemonRequest::Stop.write(&mut MessageWriter::new(&stream))?;
+  // wait for it to acknowledge
+  DaemonResponse::read(&mut MessageReader::new(&stream))?;
+  log_stderr_info!(environment, "Stopped the daemon.");
+  Ok(())
+}
+
+#[derive(Clone)]
+struct RequestStdInReader(Vec<u8>);
+
+impl StdInReader for RequestStdInReader {
+  fn read(&self) -> Result<Vec<u8>> {
+    Ok(self.0.clone())
+  }
+}
+
+async fn run_request<TEnvironment: Environment>(
+  environment: &GitTreeEnvironment<DaemonEnvironment<TEnvironment>>,
+  plugin_resolver: &Rc<PluginResolver<GitTreeEnvironment<DaemonEnvironment<TEnvironment>>>>,
+  request: RunDaemonRequest,
+  stdin: Box<dyn Read + Send>,
+  mut writer: DaemonOutputWriter,
+) {
+  let (result, log_level) = match parse_args(request.args, RequestStdInReader(request.stdin)) {
+    Ok(args) => match environment.canonicalize(&request.cwd) {
+      Ok(cwd) => {
+        environment.inner().start_request(
+          DaemonRequestOptions {
+            cwd,
+            log_level: args.log_level,
+            is_stdout_machine_readable: args.is_stdout_machine_readable(),
+          },
+          stdin,
+          writer,
+        );
+        let result = run_command(&args, environment, plugin_resolver).await;
+        writer = environment.inner().end_request().unwrap();
+        (result, args.log_level)
+      }
+      Err(err) => (Err(err), args.log_level),
+    },
+    Err(err) => (Err(err.into()), LogLevel::Info),
+  };
+
+  let exit_code = match result {
+    Ok(()) => 0,
+    Err(err) => {
+      let err = AppError::from(err);
+      let text = format!("{:#}", err.inner);
+      if log_level != LogLevel::Silent && !text.is_empty() {
+        let _ = DaemonResponse::Stderr(format!("{}\n", text).into_bytes()).write(&mut writer);
+      }
+      err.exit_code
+    }
+  };
+  let _ = DaemonResponse::Exit(exit_code).write(&mut writer);
+}
+
+async fn run_command<TEnvironment: Environment>(
+  args: &CliArgs,
+  environment: &GitTreeEnvironment<TEnvironment>,
+  plugin_resolver: &Rc<PluginResolver<GitTreeEnvironment<TEnvironment>>>,
+) -> Result<()> {
+  match &args.sub_command {
+    SubCommand::Fmt(cmd) => commands::format(cmd, args, environment, plugin_resolver).await,
+    SubCommand::Check(cmd) => match &cmd.git_ref {
+      Some(git_ref) => commands::check_git_ref_with_resolver(git_ref, cmd, args, environment, plugin_resolver).await,
+      None => commands::check(cmd, args, environment, plugin_resolver).await,
+    },
+    SubCommand::StdInFmt(cmd) => commands::stdin_fmt(cmd, args, environment, plugin_resolver).await,
+    SubCommand::StdInFmtFiles(cmd) => commands::stdin_fmt_files(cmd, args, environment, plugin_resolver).await,
+    _ => Err(anyhow!("The daemon only runs the fmt and check commands.")),
+  }
+}
+
+/// Runs the command in the daemon when it's running and the command is one it supports.
+///
+/// Returns the exit code or `None` when the command should run in-process.
+pub fn run_in_daemon<TEnvironment: Environment>(raw_args: &[String], args: &CliArgs, environment: &TEnvironment) -> Result<Option<i32>> {
+  let stdin = match &args.sub_command {
+    SubCommand::Fmt(_) | SubCommand::Check(_) => Vec::new(),
+    SubCommand::StdInFmt(cmd) => cmd.file_bytes.clone(),
+    SubCommand::StdInFmtFiles(_) => Vec::new(),
+    _ => return Ok(None),
+  };
+  let socket_path = get_socket_path(environment);
+  let Ok(stream) = UnixStream::connect(&socket_path) else {
+    return Ok(None);
+  };
+  log_debug!(environment, "Running with the daemon at {}", socket_path.display());
+
+  DaemonRequest::Run(RunDaemonRequest {
+    cwd: environment.cwd().into_path_buf(),
+    args: raw_args.to_vec(),
+    stdin,
+  })
+  .write(&mut MessageWriter::new(&stream))?;
+  if let SubCommand::StdInFmtFiles(_) = &args.sub_command {
+    // stream the files to the daemon while it outputs the formatted ones
+    let mut stream = stream.try_clone()?;
+    let mut stdin = environment.stdin();
+    std::thread::spawn(move || {
+      let _ = std::io::copy(&mut stdin, &mut stream);
+      let _ = stream.shutdown(Shutdown::Write);
+    });
+  }
+  let mut reader = MessageReader::new(&stream);
+  loop {
+    match DaemonResponse::read(&mut reader).context("The daemon exited before the command completed.")? {
+      DaemonResponse::Stdout(bytes) => environment.log_machine_readable(&bytes),
+      DaemonResponse::Stderr(bytes) => {
+        let _ = std::io::stderr().write_all(&bytes);
+      }
+      DaemonResponse::Exit(exit_code) => return Ok(Some(exit_code)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::environment::TestEnvironment;
+  use crate::environment::TestEnvironmentBuilder;
+  use crate::test_helpers::get_singular_check_text;
+  use crate::test_helpers::get_singular_formatted_text;
+
+  struct RequestOutput {
+    stdout: String,
+    stderr: String,
+    exit_code: i32,
+  }
+
+  struct TestDaemon {
+    environment: GitTreeEnvironment<DaemonEnvironment<TestEnvironment>>,
+    plugin_resolver: Rc<PluginResolver<GitTreeEnvironment<DaemonEnvironment<TestEnvironment>>>>,
+  }
+
+  impl TestDaemon {
+    fn new(environment: &TestEnvironment) -> Self {
+      let environment = GitTreeEnvironment::new(DaemonEnvironment::new(environment.clone()));
+      let plugin_resolver = Rc::new(PluginResolver::new(environment.clone(), PluginCache::new(environment.clone())));
+      Self { environment, plugin_resolver }
+    }
+
+    async fn run(&self, args: &[&str], stdin: &str) -> RequestOutput {
+      let (server, client) = UnixStream::pair().unwrap();
+      let request = RunDaemonRequest {
+        cwd: PathBuf::from("/"),
+        args: std::iter::once("dprint").chain(args.iter().copied()).map(String::from).collect(),
+        stdin: stdin.as_bytes().to_vec(),
+      };
+      let stdin = Box::new(std::io::Cursor::new(stdin.as_bytes().to_vec()));
+      run_request(&self.environment, &self.plugin_resolver, request, stdin, MessageWriter::new(Box::new(server))).await;
+      read_output(&client)
+    }
+  }
+
+  fn read_output(client: &UnixStream) -> RequestOutput {
+    let mut reader = MessageReader::new(client);
+    let mut output = RequestOutput {
+      stdout: String::new(),
+      stderr: String::new(),
+      exit_code: -1,
+    };
+    loop {
+      match DaemonResponse::read(&mut reader).unwrap() {
+        DaemonResponse::Stdout(bytes) => output.stdout.push_str(&String::from_utf8_lossy(&bytes)),
+        DaemonResponse::Stderr(bytes) => output.stderr.push_str(&String::from_utf8(bytes).unwrap()),
+        DaemonResponse::Exit(exit_code) => {
+          output.exit_code = exit_code;
+          return output;
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn should_run_commands_for_client() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin();
+      })
+      .write_file("/file.txt", "text")
+      .build();
+    environment.clone().run_in_runtime({
+      let environment = environment.clone();
+      async move {
+        let daemon = TestDaemon::new(&environment);
+
+        let output = daemon.run(&["check"], "").await;
+        assert_eq!(output.exit_code, 20);
+        assert!(output.stderr.contains(&get_singular_check_text()), "{}", output.stderr);
+
+        let output = daemon.run(&["fmt"], "").await;
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(output.stdout, format!("{}\n", get_singular_formatted_text()));
+        assert_eq!(environment.read_file("/file.txt").unwrap(), "text_formatted");
+
+        let output = daemon.run(&["check"], "").await;
+        assert_eq!((output.exit_code, output.stdout.as_str(), output.stderr.as_str()), (0, "", ""));
+
+        // uses the same plugins to check the files in the git tree
+        environment.add_git_tree_file("main", "dprint.json", &environment.read_file("/dprint.json").unwrap());
+        environment.add_git_tree_file("main", "file.txt", "text");
+        let output = daemon.run(&["check", "--list-different", "--git-ref", "main"], "").await;
+        assert_eq!((output.exit_code, output.stdout.as_str()), (20, "/file.txt\n"));
+        let output = daemon.run(&["check"], "").await;
+        assert_eq!(output.exit_code, 0);
+
+        let output = daemon.run(&["fmt", "--stdin", "file.txt"], "stdin text").await;
+        assert_eq!((output.exit_code, output.stdout.as_str()), (0, "stdin text_formatted"));
+
+        let output = daemon.run(&["fmt", "--stdin-files"], "\0\0\0\x08file.txt\0\0\0\x04text").await;
+        assert_eq!((output.exit_code, output.stdout.as_str()), (0, "\0\0\0\x08file.txt\0\0\0\x0etext_formatted"));
+
+        daemon.plugin_resolver.clear_and_shutdown_initialized().await;
+      }
+    });
+    // nothing should have been logged by the daemon itself
+    assert_eq!(environment.take_stdout_messages(), Vec::<String>::new());
+    assert_eq!(environment.take_stderr_messages(), Vec::<String>::new());
+  }
+
+  #[test]
+  fn should_output_errors_for_client() {
+    let environment = TestEnvironmentBuilder::new().build();
+    environment.clone().run_in_runtime(async move {
+      let daemon = TestDaemon::new(&environment);
+
+      let output = daemon.run(&["fmt", "--unknown-flag"], "").await;
+      assert_eq!(output.exit_code, 10);
+      assert!(output.stderr.contains("--unknown-flag"), "{}", output.stderr);
+
+      let output = daemon.run(&["output-file-paths"], "").await;
+      assert_eq!(
+        (output.exit_code, output.stderr.as_str()),
+        (1, "The daemon only runs the fmt and check commands.\n")
+      );
+
+      let output = daemon.run(&["fmt", "-L", "silent"], "").await;
+      assert_eq!((output.exit_code, output.stderr.as_str()), (11, ""));
+    });
+  }
+
+  #[test]
+  fn should_give_up_on_client_that_stalls_sending_stdin() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin();
+      })
+      .build();
+    environment.clone().run_in_runtime(async move {
+      let daemon = TestDaemon::new(&environment);
+      let (server, client) = UnixStream::pair().unwrap();
+      DaemonRequest::Run(RunDaemonRequest {
+        cwd: PathBuf::from("/"),
+        args: vec!["dprint".to_string(), "fmt".to_string(), "--stdin-files".to_string()],
+        stdin: Vec::new(),
+      })
+      .write(&mut MessageWriter::new(&client))
+      .unwrap();
+      let DaemonRequest::Run(request) = read_request(&server).unwrap() else {
+        unreachable!();
+      };
+      assert_eq!(server.read_timeout().unwrap(), Some(CLIENT_IDLE_TIMEOUT));
+      assert_eq!(server.write_timeout().unwrap(), Some(CLIENT_IDLE_TIMEOUT));
+
+      // send part of a file then stall without closing the connection
+      (&client).write_all(b"\0\0\0\x08file").unwrap();
+      server.set_read_timeout(Some(Duration::from_millis(50))).unwrap();
+      let stdin = Box::new(ClientStdIn(server.try_clone().unwrap()));
+      run_request(
+        &daemon.environment,
+        &daemon.plugin_resolver,
+        request,
+        stdin,
+        MessageWriter::new(Box::new(server)),
+      )
+      .await;
+      let output = read_output(&client);
+      assert_eq!(
+        (output.exit_code, output.stderr.as_str()),
+        (
+          1,
+          "Unexpected end of stdin in a file path: Timed out waiting for the client to send more of stdin.\n"
+        )
+      );
+      daemon.plugin_resolver.clear_and_shutdown_initialized().await;
+    });
+  }
+}
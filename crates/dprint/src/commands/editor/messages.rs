@@ -74,6 +74,43 @@ impl EditorMessage {
         EditorMessageBody::FormatResponse(message_id, data)
       }
       8 => EditorMessageBody::CancelFormat(reader.read_u32()?),
+      9 => EditorMessageBody::ConfigDiagnostics,
+      10 => {
+        let message_id = reader.read_u32()?;
+        let data = reader.read_sized_bytes()?;
+        EditorMessageBody::ConfigDiagnosticsResponse(message_id, data)
+      }
+      11 => {
+        let file_path = reader.read_sized_bytes()?;
+        EditorMessageBody::FilePluginNames(PathBuf::from(String::from_utf8_lossy(&file_path).to_string()))
+      }
+      12 => {
+        let message_id = reader.read_u32()?;
+        let names_len = reader.read_u32()?;
+        let mut names = Vec::with_capacity(names_len as usize);
+        for _ in 0..names_len {
+          names.push(String::from_utf8_lossy(&reader.read_sized_bytes()?).to_string());
+        }
+        EditorMessageBody::FilePluginNamesResponse(message_id, names)
+      }
+      13 => EditorMessageBody::ReloadConfig,
+      14 => {
+        let kind = match reader.read_u32()? {
+          0 => ProgressKind::Start,
+          1 => ProgressKind::End,
+          kind => return Err(std::io::Error::new(ErrorKind::InvalidData, format!("Unknown progress kind: {}", kind))),
+        };
+        let message = reader.read_sized_bytes()?;
+        EditorMessageBody::Progress(ProgressEditorMessageBody {
+          kind,
+          message: String::from_utf8_lossy(&message).to_string(),
+        })
+      }
+      15 => {
+        let message_id = reader.read_u32()?;
+        let data = reader.read_sized_bytes()?;
+        EditorMessageBody::FormatDiagnosticResponse(message_id, data)
+      }
       _ => {
         let data = reader.read_bytes(body_length as usize)?;
         EditorMessageBody::Unknown(message_kind, data)
@@ -128,6 +165,34 @@ impl Message for EditorMessage {
       EditorMessageBody::CancelFormat(message_id) => {
         builder.add_number(*message_id);
       }
+      EditorMessageBody::ConfigDiagnostics => {}
+      EditorMessageBody::ConfigDiagnosticsResponse(message_id, data) => {
+        builder.add_number(*message_id);
+        builder.add_bytes(data);
+      }
+      EditorMessageBody::FilePluginNames(path_buf) => {
+        let path = path_buf.to_string_lossy().to_string();
+        builder.add_owned_bytes(path.into_bytes());
+      }
+      EditorMessageBody::FilePluginNamesResponse(message_id, names) => {
+        builder.add_number(*message_id);
+        builder.add_number(names.len() as u32);
+        for name in names {
+          builder.add_bytes(name.as_bytes());
+        }
+      }
+      EditorMessageBody::ReloadConfig => {}
+      EditorMessageBody::Progress(body) => {
+        builder.add_number(match body.kind {
+          ProgressKind::Start => 0,
+          ProgressKind::End => 1,
+        });
+        builder.add_bytes(body.message.as_bytes());
+      }
+      EditorMessageBody::FormatDiagnosticResponse(message_id, data) => {
+        builder.add_number(*message_id);
+        builder.add_bytes(data);
+      }
       EditorMessageBody::Unknown(_, _) => unreachable!(), // should never be written
     }
     builder.write(writer)?;
@@ -207,6 +272,14 @@ pub enum EditorMessageBody {
   Format(FormatEditorMessageBody),
   FormatResponse(u32, Option<Vec<u8>>),
   CancelFormat(u32),
+  // protocol version 6
+  ConfigDiagnostics,
+  ConfigDiagnosticsResponse(u32, Vec<u8>),
+  FilePluginNames(PathBuf),
+  FilePluginNamesResponse(u32, Vec<String>),
+  ReloadConfig,
+  Progress(ProgressEditorMessageBody),
+  FormatDiagnosticResponse(u32, Vec<u8>),
   #[allow(dead_code)]
   Unknown(u32, Vec<u8>),
 }
@@ -223,9 +296,39 @@ impl EditorMessageBody {
       EditorMessageBody::Format(_) => 6,
       EditorMessageBody::FormatResponse(_, _) => 7,
       EditorMessageBody::CancelFormat(_) => 8,
+      EditorMessageBody::ConfigDiagnostics => 9,
+      EditorMessageBody::ConfigDiagnosticsResponse(_, _) => 10,
+      EditorMessageBody::FilePluginNames(_) => 11,
+      EditorMessageBody::FilePluginNamesResponse(_, _) => 12,
+      EditorMessageBody::ReloadConfig => 13,
+      EditorMessageBody::Progress(_) => 14,
+      EditorMessageBody::FormatDiagnosticResponse(_, _) => 15,
       EditorMessageBody::Unknown(_, _) => unreachable!(),
     }
   }
+
+  /// The minimum editor service protocol version that supports this message.
+  pub fn min_protocol_version(&self) -> u32 {
+    match self {
+      EditorMessageBody::Success(_)
+      | EditorMessageBody::Error(_, _)
+      | EditorMessageBody::Close
+      | EditorMessageBody::IsAlive
+      | EditorMessageBody::CanFormat(_)
+      | EditorMessageBody::CanFormatResponse(_, _)
+      | EditorMessageBody::Format(_)
+      | EditorMessageBody::FormatResponse(_, _)
+      | EditorMessageBody::CancelFormat(_)
+      | EditorMessageBody::Unknown(_, _) => 5,
+      EditorMessageBody::ConfigDiagnostics
+      | EditorMessageBody::ConfigDiagnosticsResponse(_, _)
+      | EditorMessageBody::FilePluginNames(_)
+      | EditorMessageBody::FilePluginNamesResponse(_, _)
+      | EditorMessageBody::ReloadConfig
+      | EditorMessageBody::Progress(_)
+      | EditorMessageBody::FormatDiagnosticResponse(_, _) => 6,
+    }
+  }
 }
 
 #[derive(Debug)]
@@ -235,3 +338,15 @@ pub struct FormatEditorMessageBody {
   pub override_config: Vec<u8>,
   pub file_bytes: Vec<u8>,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressKind {
+  Start,
+  End,
+}
+
+#[derive(Debug)]
+pub struct ProgressEditorMessageBody {
+  pub kind: ProgressKind,
+  pub message: String,
+}
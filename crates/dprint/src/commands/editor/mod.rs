@@ -5,9 +5,11 @@ use dprint_core::communication::MessageReader;
 use dprint_core::communication::MessageWriter;
 use dprint_core::communication::RcIdStore;
 use dprint_core::communication::SingleThreadMessageWriter;
+use dprint_core::plugins::FormatDiagnostic;
 use dprint_core::plugins::HostFormatRequest;
 use std::io::ErrorKind;
 use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
@@ -19,6 +21,7 @@ mod messages;
 use crate::arg_parser::CliArgs;
 use crate::arg_parser::EditorServiceSubCommand;
 use crate::configuration::resolve_config_from_args;
+use crate::configuration::GlobalConfigDiagnostic;
 use crate::configuration::ResolvedConfig;
 use crate::environment::Environment;
 use crate::plugins::PluginResolver;
@@ -29,6 +32,11 @@ use crate::utils::Semaphore;
 
 use self::messages::EditorMessage;
 use self::messages::EditorMessageBody;
+use self::messages::ProgressEditorMessageBody;
+use self::messages::ProgressKind;
+
+const MIN_PROTOCOL_VERSION: u32 = 5;
+const MAX_PROTOCOL_VERSION: u32 = 6;
 
 pub async fn output_editor_info<TEnvironment: Environment>(
   args: &CliArgs,
@@ -39,6 +47,7 @@ pub async fn output_editor_info<TEnvironment: Environment>(
   #[serde(rename_all = "camelCase")]
   struct EditorInfo {
     schema_version: u32,
+    editor_service_protocol_versions: Vec<u32>,
     cli_version: String,
     config_schema_url: String,
     plugins: Vec<EditorPluginInfo>,
@@ -81,7 +90,10 @@ pub async fn output_editor_info<TEnvironment: Environment>(
   }
 
   environment.log_machine_readable(&serde_json::to_vec(&EditorInfo {
+    // keep this at 5 so older extensions continue working and
+    // let newer ones opt into a later protocol via `--protocol-version`
     schema_version: 5,
+    editor_service_protocol_versions: (MIN_PROTOCOL_VERSION..=MAX_PROTOCOL_VERSION).collect(),
     cli_version: environment.cli_version(),
     config_schema_url: "https://dprint.dev/schemas/v0.json".to_string(),
     plugins,
@@ -99,11 +111,12 @@ pub async fn run_editor_service<TEnvironment: Environment>(
   // poll for the existence of the parent process and terminate this process when that process no longer exists
   start_parent_process_checker_task(editor_service_cmd.parent_pid);
 
-  let mut editor_service = EditorService::new(args, environment, plugin_resolver);
+  let mut editor_service = EditorService::new(args, environment, plugin_resolver, editor_service_cmd.protocol_version);
   editor_service.run().await
 }
 
 struct EditorContext {
+  pub protocol_version: u32,
   pub id_generator: IdGenerator,
   pub writer: SingleThreadMessageWriter<EditorMessage>,
   pub cancellation_tokens: RcIdStore<Arc<CancellationToken>>,
@@ -120,7 +133,7 @@ struct EditorService<'a, TEnvironment: Environment> {
 }
 
 impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
-  pub fn new(args: &'a CliArgs, environment: &'a TEnvironment, plugin_resolver: &'a Rc<PluginResolver<TEnvironment>>) -> Self {
+  pub fn new(args: &'a CliArgs, environment: &'a TEnvironment, plugin_resolver: &'a Rc<PluginResolver<TEnvironment>>, protocol_version: u32) -> Self {
     let stdout = environment.stdout();
     let writer = SingleThreadMessageWriter::for_stdout(MessageWriter::new(stdout));
     let max_cores = environment.max_threads();
@@ -132,6 +145,7 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
       plugin_resolver,
       plugins_scope: None,
       context: Rc::new(EditorContext {
+        protocol_version,
         id_generator: Default::default(),
         cancellation_tokens: Default::default(),
         writer,
@@ -165,6 +179,10 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
     });
     loop {
       let Some(message) = rx.recv().await else { return Ok(()) };
+      if message.body.min_protocol_version() > self.context.protocol_version {
+        send_error_response(&self.context, message.id, anyhow!("Unknown message with kind: {}", message.body.as_u32()));
+        continue;
+      }
       match message.body {
         EditorMessageBody::Success(_message_id) => {}
         EditorMessageBody::Error(_message_id, _data) => {}
@@ -189,6 +207,11 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
           if self.plugins_scope.is_none() {
             self.ensure_latest_config().await?;
           }
+          let scope = self.plugins_scope.clone().unwrap();
+          if let Err(err) = scope.ensure_no_global_config_diagnostics() {
+            send_error_response(&self.context, message.id, err.into());
+            continue;
+          }
           let token = Arc::new(CancellationToken::new());
           let request = HostFormatRequest {
             file_path: body.file_path,
@@ -205,13 +228,13 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
               }
             },
             file_bytes: body.file_bytes,
+            hints: Default::default(),
             token: token.clone(),
           };
 
           let token_storage_guard = self.context.cancellation_tokens.store_with_owned_guard(message.id, token.clone());
           let context = self.context.clone();
           let concurrency_limiter = self.concurrency_limiter.clone();
-          let scope = self.plugins_scope.clone().unwrap();
           let _ignore = dprint_core::async_runtime::spawn(async move {
             let _permit = concurrency_limiter.acquire().await;
             if token.is_cancelled() {
@@ -226,7 +249,10 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
 
             let body = match result {
               Ok(text) => EditorMessageBody::FormatResponse(message.id, text),
-              Err(err) => EditorMessageBody::Error(message.id, format!("{:#}", err).into_bytes()),
+              Err(err) => match FormatDiagnostic::from_error(&err).filter(|_| context.protocol_version >= 6) {
+                Some(diagnostic) => EditorMessageBody::FormatDiagnosticResponse(message.id, serde_json::to_vec(diagnostic).unwrap()),
+                None => EditorMessageBody::Error(message.id, format!("{:#}", err).into_bytes()),
+              },
             };
             send_response_body(&context, body);
           });
@@ -239,6 +265,37 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
             token.cancel();
           }
         }
+        EditorMessageBody::ConfigDiagnostics => {
+          let result = self.config_diagnostics().await;
+          handle_message(&self.context, message.id, || {
+            let diagnostics = result?;
+            Ok(EditorMessageBody::ConfigDiagnosticsResponse(message.id, serde_json::to_vec(&diagnostics)?))
+          });
+        }
+        EditorMessageBody::ConfigDiagnosticsResponse(_, _) => {
+          send_error_response(&self.context, message.id, anyhow!("CLI cannot handle a ConfigDiagnosticsResponse message."));
+        }
+        EditorMessageBody::FilePluginNames(path) => {
+          let result = self.file_plugin_names(&path).await;
+          handle_message(&self.context, message.id, || {
+            result.map(|names| EditorMessageBody::FilePluginNamesResponse(message.id, names))
+          });
+        }
+        EditorMessageBody::FilePluginNamesResponse(_, _) => {
+          send_error_response(&self.context, message.id, anyhow!("CLI cannot handle a FilePluginNamesResponse message."));
+        }
+        EditorMessageBody::ReloadConfig => {
+          // force re-creating the plugins scope even when the config is the same
+          self.plugins_scope.take();
+          let result = self.ensure_latest_config().await;
+          handle_message(&self.context, message.id, || result.map(|_| EditorMessageBody::Success(message.id)));
+        }
+        EditorMessageBody::Progress(_) => {
+          send_error_response(&self.context, message.id, anyhow!("CLI cannot handle a Progress message."));
+        }
+        EditorMessageBody::FormatDiagnosticResponse(_, _) => {
+          send_error_response(&self.context, message.id, anyhow!("CLI cannot handle a FormatDiagnosticResponse message."));
+        }
         EditorMessageBody::Unknown(message_kind, _) => {
           send_error_response(&self.context, message.id, anyhow!("Unknown message with kind: {}", message_kind));
         }
@@ -248,17 +305,68 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
 
   async fn can_format(&mut self, file_path: &Path) -> Result<bool> {
     self.ensure_latest_config().await?;
+    let file_path = self.canonicalize_file_path(file_path);
+    log_debug!(self.environment, "Checking can format: {}", file_path.display());
+    Ok(self.plugins_scope.as_ref().map(|s| s.can_format_for_editor(&file_path)).unwrap_or(false))
+  }
+
+  async fn file_plugin_names(&mut self, file_path: &Path) -> Result<Vec<String>> {
+    self.ensure_latest_config().await?;
+    let file_path = self.canonicalize_file_path(file_path);
+    let Some(scope) = self.plugins_scope.as_ref() else {
+      return Ok(Vec::new());
+    };
+    if !scope.can_format_for_editor(&file_path) {
+      return Ok(Vec::new());
+    }
+    Ok(scope.plugin_name_maps.get_plugin_names_from_file_path(&file_path))
+  }
+
+  fn canonicalize_file_path(&self, file_path: &Path) -> PathBuf {
     // canonicalize the file path, then check if it's in the list of file paths.
-    let file_path = self
+    self
       .environment
       .canonicalize(file_path)
       .map(|p| p.into_path_buf())
-      .unwrap_or(file_path.to_path_buf());
-    log_debug!(self.environment, "Checking can format: {}", file_path.display());
-    Ok(self.plugins_scope.as_ref().map(|s| s.can_format_for_editor(&file_path)).unwrap_or(false))
+      .unwrap_or(file_path.to_path_buf())
+  }
+
+  async fn config_diagnostics(&mut self) -> Result<Vec<EditorConfigDiagnostic>> {
+    let scope = self.ensure_latest_plugins_scope().await?;
+    let mut diagnostics = scope
+      .global_config_diagnostics()
+      .iter()
+      .map(|d| {
+        let inner = match d {
+          GlobalConfigDiagnostic::UnknownProperty(d) | GlobalConfigDiagnostic::Other(d) => d,
+        };
+        EditorConfigDiagnostic {
+          plugin_name: None,
+          property_name: inner.property_name.clone(),
+          message: inner.message.clone(),
+        }
+      })
+      .collect::<Vec<_>>();
+    for plugin in scope.plugins.values() {
+      let initialized_plugin = plugin.initialize().await?;
+      for diagnostic in initialized_plugin.config_diagnostics().await? {
+        diagnostics.push(EditorConfigDiagnostic {
+          plugin_name: Some(plugin.name().to_string()),
+          property_name: diagnostic.property_name,
+          message: diagnostic.message,
+        });
+      }
+    }
+    Ok(diagnostics)
   }
 
   async fn ensure_latest_config(&mut self) -> Result<Rc<ResolvedConfig>> {
+    let scope = self.ensure_latest_plugins_scope().await?;
+    scope.ensure_no_global_config_diagnostics()?;
+    Ok(scope.config.clone().unwrap())
+  }
+
+  async fn ensure_latest_plugins_scope(&mut self) -> Result<Rc<PluginsScope<TEnvironment>>> {
     let _update_permit = self.config_semaphore.acquire().await;
     let config = Rc::new(resolve_config_from_args(self.args, self.environment).await?);
 
@@ -272,12 +380,39 @@ impl<'a, TEnvironment: Environment> EditorService<'a, TEnvironment> {
       }
       self.plugin_resolver.clear_and_shutdown_initialized().await;
 
-      let scope = resolve_plugins_scope(config.clone(), self.environment, self.plugin_resolver).await?;
-      scope.ensure_no_global_config_diagnostics()?;
-      self.plugins_scope = Some(Rc::new(scope));
+      send_progress(&self.context, ProgressKind::Start, "Resolving plugins.".to_string());
+      let result = resolve_plugins_scope(config.clone(), self.environment, self.plugin_resolver).await;
+      send_progress(
+        &self.context,
+        ProgressKind::End,
+        match &result {
+          Ok(scope) => format!("Resolved {} plugin(s).", scope.plugins.len()),
+          Err(err) => format!("Failed resolving plugins. {:#}", err),
+        },
+      );
+      // keep the scope even when the global config is invalid so it's not re-resolved
+      // on every request (formatting checks for global config diagnostics)
+      self.plugins_scope = Some(Rc::new(result?));
     }
 
-    Ok(self.plugins_scope.as_ref().unwrap().config.clone().unwrap())
+    Ok(self.plugins_scope.clone().unwrap())
+  }
+}
+
+/// A configuration diagnostic sent to the editor, where the plugin
+/// name is `None` for diagnostics in the global configuration.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EditorConfigDiagnostic {
+  plugin_name: Option<String>,
+  property_name: String,
+  message: String,
+}
+
+fn send_progress(context: &EditorContext, kind: ProgressKind, message: String) {
+  // progress notifications are unsolicited, so only send them to clients that understand them
+  if context.protocol_version >= 6 {
+    send_response_body(context, EditorMessageBody::Progress(ProgressEditorMessageBody { kind, message }));
   }
 }
 
@@ -315,9 +450,12 @@ mod test {
   use dprint_core::communication::RcIdStore;
   use dprint_core::communication::SingleThreadMessageWriter;
   use dprint_core::configuration::ConfigKeyMap;
+  use dprint_core::plugins::FormatDiagnostic;
+  use dprint_core::plugins::FormatDiagnosticSeverity;
   use dprint_core::plugins::FormatRange;
   use dprint_core::plugins::FormatResult;
   use pretty_assertions::assert_eq;
+  use std::cell::RefCell;
   use std::io::Read;
   use std::io::Write;
   use std::path::Path;
@@ -336,6 +474,7 @@ mod test {
   use super::messages::EditorMessage;
   use super::messages::EditorMessageBody;
   use super::messages::FormatEditorMessageBody;
+  use super::messages::ProgressKind;
 
   #[test]
   fn should_output_editor_plugin_info() {
@@ -348,7 +487,7 @@ mod test {
       })
       .build(); // build only, don't initialize
     run_test_cli(vec!["editor-info"], &environment).unwrap();
-    let mut final_output = r#"{"schemaVersion":5,"cliVersion":""#.to_string();
+    let mut final_output = r#"{"schemaVersion":5,"editorServiceProtocolVersions":[5,6],"cliVersion":""#.to_string();
     final_output.push_str(&environment.cli_version());
     final_output.push_str(r#"","configSchemaUrl":"https://dprint.dev/schemas/v0.json","plugins":["#);
     final_output
@@ -370,6 +509,8 @@ mod test {
     Success(oneshot::Sender<Result<()>>),
     Format(oneshot::Sender<Result<Option<Vec<u8>>>>),
     CanFormat(oneshot::Sender<Result<bool>>),
+    ConfigDiagnostics(oneshot::Sender<Result<Vec<u8>>>),
+    FilePluginNames(oneshot::Sender<Result<Vec<String>>>),
   }
 
   #[derive(Clone)]
@@ -377,6 +518,7 @@ mod test {
     writer: Rc<SingleThreadMessageWriter<EditorMessage>>,
     id_generator: Rc<IdGenerator>,
     messages: RcIdStore<MessageResponseChannel>,
+    progress_messages: Rc<RefCell<Vec<(ProgressKind, String)>>>,
   }
 
   impl EditorServiceCommunicator {
@@ -388,6 +530,7 @@ mod test {
         writer,
         id_generator: Default::default(),
         messages: Default::default(),
+        progress_messages: Default::default(),
       };
 
       let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
@@ -402,9 +545,10 @@ mod test {
       });
 
       let messages = communicator.messages.clone();
+      let progress_messages = communicator.progress_messages.clone();
       dprint_core::async_runtime::spawn(async move {
         while let Some(Ok(message)) = rx.recv().await {
-          if let Err(_) = handle_stdout_message(message, &messages) {
+          if let Err(_) = handle_stdout_message(message, &messages, &progress_messages) {
             break;
           }
         }
@@ -451,6 +595,49 @@ mod test {
         .await
     }
 
+    pub async fn config_diagnostics(&self) -> Result<Vec<u8>> {
+      let (tx, rx) = oneshot::channel::<Result<Vec<u8>>>();
+
+      self
+        .send_message(
+          EditorMessageBody::ConfigDiagnostics,
+          MessageResponseChannel::ConfigDiagnostics(tx),
+          rx,
+          Arc::new(CancellationToken::new()),
+        )
+        .await
+    }
+
+    pub async fn file_plugin_names(&self, file_path: impl AsRef<Path>) -> Result<Vec<String>> {
+      let (tx, rx) = oneshot::channel::<Result<Vec<String>>>();
+
+      self
+        .send_message(
+          EditorMessageBody::FilePluginNames(file_path.as_ref().to_path_buf()),
+          MessageResponseChannel::FilePluginNames(tx),
+          rx,
+          Arc::new(CancellationToken::new()),
+        )
+        .await
+    }
+
+    pub async fn reload_config(&self) -> Result<()> {
+      let (tx, rx) = oneshot::channel::<Result<()>>();
+
+      self
+        .send_message(
+          EditorMessageBody::ReloadConfig,
+          MessageResponseChannel::Success(tx),
+          rx,
+          Arc::new(CancellationToken::new()),
+        )
+        .await
+    }
+
+    pub fn take_progress_messages(&self) -> Vec<(ProgressKind, String)> {
+      self.progress_messages.take()
+    }
+
     pub async fn exit(&self) -> Result<()> {
       let (tx, rx) = oneshot::channel::<Result<()>>();
 
@@ -497,7 +684,11 @@ mod test {
     }
   }
 
-  fn handle_stdout_message(message: EditorMessage, messages: &RcIdStore<MessageResponseChannel>) -> Result<()> {
+  fn handle_stdout_message(
+    message: EditorMessage,
+    messages: &RcIdStore<MessageResponseChannel>,
+    progress_messages: &RefCell<Vec<(ProgressKind, String)>>,
+  ) -> Result<()> {
     match message.body {
       EditorMessageBody::Success(message_id) => match messages.take(message_id) {
         Some(MessageResponseChannel::Success(channel)) => {
@@ -518,6 +709,12 @@ mod test {
           Some(MessageResponseChannel::CanFormat(channel)) => {
             let _ignore = channel.send(Err(err));
           }
+          Some(MessageResponseChannel::ConfigDiagnostics(channel)) => {
+            let _ignore = channel.send(Err(err));
+          }
+          Some(MessageResponseChannel::FilePluginNames(channel)) => {
+            let _ignore = channel.send(Err(err));
+          }
           None => {}
         }
       }
@@ -528,6 +725,14 @@ mod test {
         Some(_) => unreachable!(),
         None => {}
       },
+      EditorMessageBody::FormatDiagnosticResponse(message_id, data) => match messages.take(message_id) {
+        Some(MessageResponseChannel::Format(channel)) => {
+          let diagnostic: FormatDiagnostic = serde_json::from_slice(&data).unwrap();
+          let _ignore = channel.send(Err(diagnostic.into()));
+        }
+        Some(_) => unreachable!(),
+        None => {}
+      },
       EditorMessageBody::CanFormatResponse(message_id, value) => match messages.take(message_id) {
         Some(MessageResponseChannel::CanFormat(channel)) => {
           let _ignore = channel.send(Ok(if value == 1 { true } else { false }));
@@ -535,6 +740,23 @@ mod test {
         Some(_) => unreachable!(),
         None => {}
       },
+      EditorMessageBody::ConfigDiagnosticsResponse(message_id, data) => match messages.take(message_id) {
+        Some(MessageResponseChannel::ConfigDiagnostics(channel)) => {
+          let _ignore = channel.send(Ok(data));
+        }
+        Some(_) => unreachable!(),
+        None => {}
+      },
+      EditorMessageBody::FilePluginNamesResponse(message_id, names) => match messages.take(message_id) {
+        Some(MessageResponseChannel::FilePluginNames(channel)) => {
+          let _ignore = channel.send(Ok(names));
+        }
+        Some(_) => unreachable!(),
+        None => {}
+      },
+      EditorMessageBody::Progress(body) => {
+        progress_messages.borrow_mut().push((body.kind, body.message));
+      }
       _ => unreachable!(),
     }
 
@@ -1027,6 +1249,233 @@ mod test {
     result.join().unwrap();
   }
 
+  #[test]
+  fn should_support_protocol_version_6_messages() {
+    let txt_file_path = PathBuf::from("/file.txt");
+    let environment = TestEnvironmentBuilder::new()
+      .add_remote_wasm_plugin()
+      .add_remote_process_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin()
+          .add_remote_process_plugin()
+          .add_config_section(
+            "test-plugin",
+            r#"{
+              "associations": ["**/*.{txt,txt_ps}"],
+              "ending": "wasm",
+              "non-existent": 5
+            }"#,
+          )
+          .add_config_section(
+            "testProcessPlugin",
+            r#"{
+              "associations": ["**/*.txt_ps"]
+            }"#,
+          )
+          .add_includes("**/*.{txt,txt_ps}")
+          .add_excludes("ignored_file.txt");
+      })
+      .write_file(&txt_file_path, "")
+      .initialize()
+      .build();
+    let stdin = environment.stdin_writer();
+    let stdout = environment.stdout_reader();
+
+    let result = std::thread::spawn({
+      let environment = environment.clone();
+      move || {
+        TestEnvironment::new().run_in_runtime(async move {
+          let communicator = EditorServiceCommunicator::new(stdin, stdout);
+
+          assert_eq!(communicator.file_plugin_names(&txt_file_path).await.unwrap(), vec!["test-plugin".to_string()]);
+          assert_eq!(
+            communicator.file_plugin_names("/file.txt_ps").await.unwrap(),
+            vec!["test-plugin".to_string(), "test-process-plugin".to_string()]
+          );
+          assert_eq!(
+            communicator.file_plugin_names("/sub/../file.txt_ps").await.unwrap(),
+            vec!["test-plugin".to_string(), "test-process-plugin".to_string()]
+          );
+          assert_eq!(communicator.file_plugin_names("/ignored_file.txt").await.unwrap(), Vec::<String>::new());
+          assert_eq!(communicator.file_plugin_names("/file.asdf").await.unwrap(), Vec::<String>::new());
+          assert_eq!(
+            communicator.take_progress_messages(),
+            vec![
+              (ProgressKind::Start, "Resolving plugins.".to_string()),
+              (ProgressKind::End, "Resolved 2 plugin(s).".to_string()),
+            ]
+          );
+
+          assert_eq!(
+            bytes_to_string(communicator.config_diagnostics().await.unwrap()),
+            r#"[{"pluginName":"test-plugin","propertyName":"non-existent","message":"Unknown property in configuration"}]"#
+          );
+
+          // config didn't change, so no progress should be reported until forcing a reload
+          assert_eq!(communicator.take_progress_messages(), vec![]);
+          communicator.reload_config().await.unwrap();
+          assert_eq!(
+            communicator.take_progress_messages(),
+            vec![
+              (ProgressKind::Start, "Resolving plugins.".to_string()),
+              (ProgressKind::End, "Resolved 2 plugin(s).".to_string()),
+            ]
+          );
+
+          environment
+            .write_file(
+              PathBuf::from("./dprint.json"),
+              r#"{
+                "includes": ["**/*.txt"],
+                "unknownProperty": true,
+                "plugins": ["https://plugins.dprint.dev/test-plugin.wasm"]
+              }"#,
+            )
+            .unwrap();
+          assert_eq!(
+            bytes_to_string(communicator.config_diagnostics().await.unwrap()),
+            r#"[{"pluginName":null,"propertyName":"unknownProperty","message":"Unknown property in configuration"}]"#
+          );
+          assert_eq!(
+            communicator.take_progress_messages(),
+            vec![
+              (ProgressKind::Start, "Resolving plugins.".to_string()),
+              (ProgressKind::End, "Resolved 1 plugin(s).".to_string()),
+            ]
+          );
+
+          environment
+            .write_file(
+              PathBuf::from("./dprint.json"),
+              r#"{
+                "includes": ["**/*.txt"],
+                "lineWidth": "test",
+                "plugins": ["https://plugins.dprint.dev/test-plugin.wasm"]
+              }"#,
+            )
+            .unwrap();
+          let invalid_config_diagnostics = bytes_to_string(communicator.config_diagnostics().await.unwrap());
+          assert!(
+            invalid_config_diagnostics.contains(r#""propertyName":"lineWidth""#),
+            "{}",
+            invalid_config_diagnostics
+          );
+          assert_eq!(
+            communicator.take_progress_messages(),
+            vec![
+              (ProgressKind::Start, "Resolving plugins.".to_string()),
+              (ProgressKind::End, "Resolved 1 plugin(s).".to_string()),
+            ]
+          );
+
+          // the invalid config didn't change, so the plugins shouldn't be resolved again
+          assert_eq!(bytes_to_string(communicator.config_diagnostics().await.unwrap()), invalid_config_diagnostics);
+          assert!(communicator.check_file(&txt_file_path).await.is_err());
+          assert!(communicator
+            .format_text(&txt_file_path, b"text".to_vec(), None, Default::default(), Default::default())
+            .await
+            .is_err());
+          assert_eq!(communicator.take_progress_messages(), vec![]);
+
+          communicator.exit().await.unwrap();
+        });
+      }
+    });
+
+    run_test_cli(
+      vec!["editor-service", "--parent-pid", &std::process::id().to_string(), "--protocol-version", "6"],
+      &environment,
+    )
+    .unwrap();
+
+    result.join().unwrap();
+  }
+
+  #[test]
+  fn should_not_support_protocol_version_6_messages_by_default() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|c| {
+        c.add_remote_wasm_plugin().add_includes("**/*.txt");
+      })
+      .build();
+    let stdin = environment.stdin_writer();
+    let stdout = environment.stdout_reader();
+
+    let result = std::thread::spawn({
+      move || {
+        TestEnvironment::new().run_in_runtime(async move {
+          let communicator = EditorServiceCommunicator::new(stdin, stdout);
+
+          assert_eq!(communicator.check_file("/file.txt").await.unwrap(), true);
+          assert_eq!(
+            communicator.file_plugin_names("/file.txt").await.err().unwrap().to_string(),
+            "Unknown message with kind: 11"
+          );
+          assert_eq!(communicator.reload_config().await.err().unwrap().to_string(), "Unknown message with kind: 13");
+          // no unsolicited notifications for older clients
+          assert_eq!(communicator.take_progress_messages(), vec![]);
+
+          communicator.exit().await.unwrap();
+        });
+      }
+    });
+
+    run_test_cli(vec!["editor-service", "--parent-pid", &std::process::id().to_string()], &environment).unwrap();
+
+    result.join().unwrap();
+  }
+
+  #[test]
+  fn should_send_format_diagnostics_for_protocol_version_6() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .with_default_config(|c| {
+        c.add_remote_process_plugin().add_includes("**/*.txt_ps");
+      })
+      .build();
+    let stdin = environment.stdin_writer();
+    let stdout = environment.stdout_reader();
+
+    let result = std::thread::spawn({
+      move || {
+        TestEnvironment::new().run_in_runtime(async move {
+          let communicator = EditorServiceCommunicator::new(stdin, stdout);
+
+          let err = communicator
+            .format_text(
+              "/file.txt_ps",
+              "diagnostic: Unexpected token.".to_string().into_bytes(),
+              None,
+              Default::default(),
+              Default::default(),
+            )
+            .await
+            .err()
+            .unwrap();
+          assert_eq!(
+            FormatDiagnostic::from_error(&err),
+            Some(&FormatDiagnostic {
+              message: "Unexpected token.".to_string(),
+              range: Some(12..29),
+              severity: FormatDiagnosticSeverity::Error,
+              code: Some("test".to_string()),
+            })
+          );
+          communicator.take_progress_messages();
+
+          communicator.exit().await.unwrap();
+        });
+      }
+    });
+
+    run_test_cli(
+      vec!["editor-service", "--parent-pid", &std::process::id().to_string(), "--protocol-version", "6"],
+      &environment,
+    )
+    .unwrap();
+
+    result.join().unwrap();
+  }
+
   fn bytes_to_string(bytes: Vec<u8>) -> String {
     String::from_utf8(bytes).unwrap()
   }
@@ -1,8 +1,16 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
 use anyhow::Result;
 use crossterm::style::Stylize;
+use dprint_core::async_runtime::JoinHandle;
+use dprint_core::communication::MessageReader;
 use dprint_core::plugins::HostFormatRequest;
 use dprint_core::plugins::NullCancellationToken;
 use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -13,13 +21,18 @@ use crate::arg_parser::CheckSubCommand;
 use crate::arg_parser::CliArgs;
 use crate::arg_parser::FmtSubCommand;
 use crate::arg_parser::OutputFormatTimesSubCommand;
+use crate::arg_parser::StdInFmtFilesSubCommand;
 use crate::arg_parser::StdInFmtSubCommand;
 use crate::configuration::resolve_config_from_args;
 use crate::environment::Environment;
+use crate::environment::GitTreeEnvironment;
 use crate::format::run_parallelized;
 use crate::format::EnsureStableFormat;
+use crate::format::RunPluginChecks;
 use crate::incremental::get_incremental_file;
+use crate::native_plugins::registered_native_plugins;
 use crate::patterns::FileMatcher;
+use crate::plugins::PluginCache;
 use crate::plugins::PluginResolver;
 use crate::resolution::resolve_plugins_scope;
 use crate::resolution::resolve_plugins_scope_and_paths;
@@ -64,6 +77,7 @@ async fn output_stdin_format<TEnvironment: Environment>(
       file_bytes: file_bytes.to_vec(),
       range: None,
       override_config: Default::default(),
+      hints: Default::default(),
       token: Arc::new(NullCancellationToken),
     })
     .await?;
@@ -74,6 +88,159 @@ async fn output_stdin_format<TEnvironment: Environment>(
   Ok(())
 }
 
+pub async fn stdin_fmt_files<TEnvironment: Environment>(
+  cmd: &StdInFmtFilesSubCommand,
+  args: &CliArgs,
+  environment: &TEnvironment,
+  plugin_resolver: &Rc<PluginResolver<TEnvironment>>,
+) -> Result<()> {
+  let config = Rc::new(resolve_config_from_args(args, environment).await?);
+  let plugins_scope = Rc::new(resolve_plugins_scope(config, environment, plugin_resolver).await?);
+  plugins_scope.ensure_plugins_found()?;
+  plugins_scope.ensure_no_global_config_diagnostics()?;
+  let file_matcher = FileMatcher::new(environment.clone(), plugins_scope.config.as_ref().unwrap(), &cmd.patterns, &environment.cwd())?;
+
+  // read on another thread so files are formatted and output while the rest are still coming in
+  let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<(PathBuf, Vec<u8>)>>(1);
+  dprint_core::async_runtime::spawn_blocking({
+    let mut stdin = environment.stdin();
+    move || {
+      while let Some(result) = read_stdin_file(&mut stdin).transpose() {
+        let is_err = result.is_err();
+        if tx.blocking_send(result).is_err() || is_err {
+          return;
+        }
+      }
+    }
+  });
+
+  // the files are formatted concurrently, but output in the order they were received
+  let max_pending = environment.max_threads();
+  let mut pending = VecDeque::new();
+  let mut read_error = None;
+  let mut is_stdin_done = false;
+  let mut error_count = 0;
+  while !is_stdin_done || !pending.is_empty() {
+    let event = tokio::select! {
+      biased;
+      Some(result) = next_formatted_stdin_file(&mut pending) => StdInFilesEvent::Formatted(result),
+      file = rx.recv(), if !is_stdin_done && pending.len() < max_pending => StdInFilesEvent::Received(file),
+    };
+    match event {
+      StdInFilesEvent::Received(Some(Ok((file_path, file_bytes)))) => {
+        // same as --stdin, only apply the exclusion rules to absolute paths
+        let should_format = !environment.is_absolute_path(&file_path) || {
+          // generated files might not exist on the file system yet
+          let file_path = environment
+            .canonicalize(&file_path)
+            .map(|p| p.into_path_buf())
+            .unwrap_or_else(|_| file_path.clone());
+          file_matcher.matches(file_path)
+        };
+        let handle = dprint_core::async_runtime::spawn({
+          let plugins_scope = plugins_scope.clone();
+          let file_path = file_path.clone();
+          let file_bytes = file_bytes.clone();
+          async move {
+            if !should_format {
+              return Ok(None);
+            }
+            plugins_scope
+              .format(HostFormatRequest {
+                file_path,
+                file_bytes,
+                range: None,
+                override_config: Default::default(),
+                hints: Default::default(),
+                token: Arc::new(NullCancellationToken),
+              })
+              .await
+          }
+        });
+        pending.push_back((file_path, file_bytes, handle));
+      }
+      StdInFilesEvent::Received(Some(Err(err))) => {
+        read_error = Some(err);
+        is_stdin_done = true;
+      }
+      StdInFilesEvent::Received(None) => {
+        is_stdin_done = true;
+      }
+      StdInFilesEvent::Formatted(result) => {
+        let (file_path, file_bytes, _) = pending.pop_front().unwrap();
+        let formatted_bytes = match result {
+          Ok(Some(formatted_bytes)) => formatted_bytes,
+          Ok(None) => file_bytes,
+          Err(err) => {
+            // still output the file so there's a record for every input file
+            log_error!(environment, "Error formatting {}. Message: {:#}", file_path.display(), err);
+            error_count += 1;
+            file_bytes
+          }
+        };
+        let file_path = file_path.to_string_lossy();
+        let mut record = Vec::with_capacity(file_path.len() + formatted_bytes.len() + 8);
+        record.extend_from_slice(&(file_path.len() as u32).to_be_bytes());
+        record.extend_from_slice(file_path.as_bytes());
+        record.extend_from_slice(&(formatted_bytes.len() as u32).to_be_bytes());
+        record.extend(formatted_bytes);
+        environment.log_machine_readable(&record);
+      }
+    }
+  }
+
+  if let Some(err) = read_error {
+    Err(err)
+  } else if error_count == 0 {
+    Ok(())
+  } else {
+    bail!("Had {} error{} formatting.", error_count, if error_count == 1 { "" } else { "s" })
+  }
+}
+
+enum StdInFilesEvent {
+  Received(Option<Result<(PathBuf, Vec<u8>)>>),
+  Formatted(Result<Option<Vec<u8>>>),
+}
+
+type PendingStdInFile = (PathBuf, Vec<u8>, JoinHandle<Result<Option<Vec<u8>>>>);
+
+/// Waits for the oldest pending file to finish formatting.
+async fn next_formatted_stdin_file(pending: &mut VecDeque<PendingStdInFile>) -> Option<Result<Option<Vec<u8>>>> {
+  let (_, _, handle) = pending.front_mut()?;
+  Some(handle.await.map_err(anyhow::Error::from).and_then(|result| result))
+}
+
+/// Reads the next record of `fmt --stdin-files`, which is the file path and then
+/// the file text, each prefixed with its byte length as a big-endian u32.
+///
+/// Returns `None` when stdin ends between records.
+fn read_stdin_file(reader: &mut impl Read) -> Result<Option<(PathBuf, Vec<u8>)>> {
+  let mut size_bytes = [0; 4];
+  let mut read_count = 0;
+  while read_count < size_bytes.len() {
+    match reader.read(&mut size_bytes[read_count..]) {
+      Ok(0) if read_count == 0 => return Ok(None),
+      Ok(0) => bail!("Unexpected end of stdin in the size of a file path."),
+      Ok(count) => read_count += count,
+      Err(err) if err.kind() == ErrorKind::Interrupted => {}
+      Err(err) => return Err(err.into()),
+    }
+  }
+  let mut reader = MessageReader::new(reader);
+  let file_path = reader
+    .read_bytes(u32::from_be_bytes(size_bytes) as usize)
+    .context("Unexpected end of stdin in a file path")?;
+  let file_path = String::from_utf8(file_path).map_err(|err| anyhow!("File path in stdin was not valid UTF-8: {}", err))?;
+  if file_path.is_empty() {
+    bail!("Expected a file path in stdin, but found an empty value.");
+  }
+  let file_bytes = reader
+    .read_sized_bytes()
+    .with_context(|| format!("Unexpected end of stdin in the text of {}", file_path))?;
+  Ok(Some((PathBuf::from(file_path), file_bytes)))
+}
+
 pub async fn output_format_times<TEnvironment: Environment>(
   cmd: &OutputFormatTimesSubCommand,
   args: &CliArgs,
@@ -85,7 +252,7 @@ pub async fn output_format_times<TEnvironment: Environment>(
   let durations: Arc<Mutex<Vec<(PathBuf, u128)>>> = Arc::new(Mutex::new(Vec::new()));
 
   for scope_and_paths in scopes.into_iter() {
-    run_parallelized(scope_and_paths, environment, None, EnsureStableFormat(false), {
+    run_parallelized(scope_and_paths, environment, None, EnsureStableFormat(false), RunPluginChecks(false), {
       let durations = durations.clone();
       move |file_path, _, _, start_instant, _| {
         let duration = start_instant.elapsed().as_millis();
@@ -128,37 +295,46 @@ pub async fn check<TEnvironment: Environment>(
   scopes.ensure_valid_for_cli_args(args)?;
   let not_formatted_files_count = Arc::new(AtomicCounter::default());
   let list_different = cmd.list_different;
+  // the incremental cache is for the working tree
+  let incremental = if cmd.git_ref.is_some() { Some(false) } else { cmd.incremental };
 
   for scope_and_paths in scopes.into_iter() {
     let incremental_file = scope_and_paths
       .scope
       .config
       .as_ref()
-      .and_then(|config| get_incremental_file(cmd.incremental, config, &scope_and_paths.scope, environment))
+      .and_then(|config| get_incremental_file(incremental, config, &scope_and_paths.scope, environment))
       .map(Arc::new);
-    run_parallelized(scope_and_paths, environment, incremental_file.clone(), EnsureStableFormat(false), {
-      let not_formatted_files_count = not_formatted_files_count.clone();
-      let incremental_file = incremental_file.clone();
-      move |file_path, file_bytes, formatted_bytes, _, environment| {
-        if formatted_bytes != file_bytes {
-          not_formatted_files_count.inc();
-          if list_different {
-            log_stdout_info!(environment, "{}", file_path.display());
+    run_parallelized(
+      scope_and_paths,
+      environment,
+      incremental_file.clone(),
+      EnsureStableFormat(false),
+      RunPluginChecks(true),
+      {
+        let not_formatted_files_count = not_formatted_files_count.clone();
+        let incremental_file = incremental_file.clone();
+        move |file_path, file_bytes, formatted_bytes, _, environment| {
+          if formatted_bytes != file_bytes {
+            not_formatted_files_count.inc();
+            if list_different {
+              log_stdout_info!(environment, "{}", file_path.display());
+            } else {
+              output_difference(&file_path, &file_bytes, &formatted_bytes, &environment);
+            }
           } else {
-            output_difference(&file_path, &file_bytes, &formatted_bytes, &environment);
-          }
-        } else {
-          // update the incremental cache when the file is already formatted correctly
-          // so that this runs faster next time, but don't update it with the
-          // correctly formatted file because it hasn't undergone a stable
-          // formatting check
-          if let Some(incremental_file) = &incremental_file {
-            incremental_file.update_file(&formatted_bytes);
+            // update the incremental cache when the file is already formatted correctly
+            // so that this runs faster next time, but don't update it with the
+            // correctly formatted file because it hasn't undergone a stable
+            // formatting check
+            if let Some(incremental_file) = &incremental_file {
+              incremental_file.update_file(&formatted_bytes);
+            }
           }
+          Ok(())
         }
-        Ok(())
-      }
-    })
+      },
+    )
     .await?;
 
     if let Some(incremental_file) = &incremental_file {
@@ -179,6 +355,28 @@ pub async fn check<TEnvironment: Environment>(
   }
 }
 
+/// Runs `check` on the files of a git revision without reading or writing the working tree.
+pub async fn check_git_ref<TEnvironment: Environment>(git_ref: &str, cmd: &CheckSubCommand, args: &CliArgs, environment: &TEnvironment) -> Result<()> {
+  let environment = GitTreeEnvironment::new(environment.clone());
+  let plugin_resolver =
+    Rc::new(PluginResolver::new(environment.clone(), PluginCache::new(environment.clone())).with_native_plugins(registered_native_plugins()));
+  let result = check_git_ref_with_resolver(git_ref, cmd, args, &environment, &plugin_resolver).await;
+  plugin_resolver.clear_and_shutdown_initialized().await;
+  result
+}
+
+/// Same as [`check_git_ref`], but reuses plugins that were already initialized with the environment.
+pub async fn check_git_ref_with_resolver<TEnvironment: Environment>(
+  git_ref: &str,
+  cmd: &CheckSubCommand,
+  args: &CliArgs,
+  environment: &GitTreeEnvironment<TEnvironment>,
+  plugin_resolver: &Rc<PluginResolver<GitTreeEnvironment<TEnvironment>>>,
+) -> Result<()> {
+  let _git_ref = environment.read_git_ref(git_ref)?;
+  check(cmd, args, environment, plugin_resolver).await
+}
+
 fn output_difference(file_path: &Path, file_bytes: &[u8], formatted_bytes: &[u8], environment: &impl Environment) {
   let file_text = match String::from_utf8(file_bytes.to_vec()) {
     Ok(text) => text,
@@ -232,6 +430,7 @@ pub async fn format<TEnvironment: Environment>(
       environment,
       incremental_file.clone(),
       EnsureStableFormat(cmd.enable_stable_format),
+      RunPluginChecks(false),
       {
         let formatted_files_count = formatted_files_count.clone();
         let incremental_file = incremental_file.clone();
@@ -271,9 +470,13 @@ pub async fn format<TEnvironment: Environment>(
 
 #[cfg(test)]
 mod test {
+  use anyhow::Result;
   use crossterm::style::Stylize;
   use pretty_assertions::assert_eq;
+  use std::io::Write;
+  use std::path::PathBuf;
 
+  use super::read_stdin_file;
   use crate::environment::Environment;
   use crate::environment::TestEnvironment;
   use crate::environment::TestEnvironmentBuilder;
@@ -421,6 +624,128 @@ mod test {
     assert_eq!(error_message.to_string(), "Had 1 error formatting.");
   }
 
+  #[test]
+  fn should_pass_global_whitespace_policies_to_process_plugin() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .with_default_config(|config_file| {
+        config_file
+          .add_config_section("trimTrailingWhitespace", "true")
+          .add_config_section("insertFinalNewLine", "true")
+          .add_config_section("maxConsecutiveBlankLines", "1");
+      })
+      .write_file("/file.txt_ps", "print: a \n\n\n\nb  c ") // special text that makes the plugin print the words
+      .build();
+    run_test_cli(vec!["fmt", "/file.txt_ps"], &environment).unwrap();
+    assert_eq!(environment.take_stdout_messages(), vec![get_singular_formatted_text()]);
+    assert_eq!(environment.read_file("/file.txt_ps").unwrap(), "print: a\n\nb  c\n");
+  }
+
+  #[test]
+  fn should_render_process_plugin_format_diagnostic() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .write_file("/file.txt_ps", "diagnostic: Unexpected token.") // special text that makes the plugin return a diagnostic
+      .build();
+    let error_message = run_test_cli(vec!["fmt", "/file.txt_ps"], &environment).err().unwrap();
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![String::from(concat!(
+        "Error formatting /file.txt_ps. Message: Line 1, column 13: Unexpected token. (test)\n\n",
+        "  diagnostic: Unexpected token.\n",
+        "              ~~~~~~~~~~~~~~~~~"
+      ))]
+    );
+    assert_eq!(error_message.to_string(), "Had 1 error formatting.");
+  }
+
+  #[test]
+  fn should_render_wasm_plugin_format_diagnostic() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .write_file("/file.txt", "diagnostic: Unexpected token.") // special text that makes the plugin return a diagnostic
+      .build();
+    let error_message = run_test_cli(vec!["fmt", "/file.txt"], &environment).err().unwrap();
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![String::from(concat!(
+        "Error formatting /file.txt. Message: Line 1, column 13: Unexpected token. (test)\n\n",
+        "  diagnostic: Unexpected token.\n",
+        "              ~~~~~~~~~~~~~~~~~"
+      ))]
+    );
+    assert_eq!(error_message.to_string(), "Had 1 error formatting.");
+  }
+
+  #[test]
+  fn should_provide_host_format_diagnostic_to_wasm_plugin() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
+      // the wasm plugin moves the process plugin's diagnostic to be within its text
+      .write_file("/file.txt", "plugin: diagnostic: Unexpected token.")
+      .build();
+    let error_message = run_test_cli(vec!["fmt", "/file.txt"], &environment).err().unwrap();
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![String::from(concat!(
+        "Error formatting /file.txt. Message: Line 1, column 21: Unexpected token. (test)\n\n",
+        "  plugin: diagnostic: Unexpected token.\n",
+        "                      ~~~~~~~~~~~~~~~~~"
+      ))]
+    );
+    assert_eq!(error_message.to_string(), "Had 1 error formatting.");
+  }
+
+  #[test]
+  fn should_output_plugin_check_warnings_without_failing() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .write_file("/file.txt_ps", "warn: Prefer shorter lines._formatted_process") // special text that makes the plugin return a warning
+      .build();
+    run_test_cli(vec!["check", "/file.txt_ps"], &environment).unwrap();
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![String::from(concat!(
+        "Warning checking /file.txt_ps. Message: Line 1, column 7: Prefer shorter lines. (test)\n\n",
+        "  warn: Prefer shorter lines._formatted\n",
+        "        ~~~~~~~~~~~~~~~~~~~~~"
+      ))]
+    );
+    // not output when formatting
+    run_test_cli(vec!["fmt", "/file.txt_ps"], &environment).unwrap();
+    assert_eq!(environment.take_stderr_messages(), Vec::<String>::new());
+  }
+
+  #[test]
+  fn should_output_wasm_plugin_check_warnings() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .write_file("/file.txt", "warn: Prefer shorter lines._formatted") // special text that makes the plugin return a warning
+      .build();
+    run_test_cli(vec!["check", "/file.txt"], &environment).unwrap();
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![String::from(concat!(
+        "Warning checking /file.txt. Message: Line 1, column 7: Prefer shorter lines. (test)\n\n",
+        "  warn: Prefer shorter lines._formatted\n",
+        "        ~~~~~~~~~~~~~~~~~~~~~"
+      ))]
+    );
+  }
+
+  #[test]
+  fn should_output_plugin_check_warnings_for_incrementally_skipped_files() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .write_file("/file.txt", "warn: Prefer shorter lines._formatted")
+      .build();
+    let expected_warning = String::from(concat!(
+      "Warning checking /file.txt. Message: Line 1, column 7: Prefer shorter lines. (test)\n\n",
+      "  warn: Prefer shorter lines._formatted\n",
+      "        ~~~~~~~~~~~~~~~~~~~~~"
+    ));
+    run_test_cli(vec!["check", "--incremental"], &environment).unwrap();
+    assert_eq!(environment.take_stderr_messages(), vec![expected_warning.clone()]);
+    // now the file is known to be formatted, but it should still be checked
+    run_test_cli(vec!["check", "--incremental", "--log-level=debug"], &environment).unwrap();
+    let stderr_messages = environment.take_stderr_messages();
+    assert!(stderr_messages.iter().any(|m| m.contains("No change: /file.txt")));
+    assert!(stderr_messages.contains(&expected_warning));
+  }
+
   #[test]
   fn should_handle_wasm_plugin_panicking() {
     let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
@@ -544,6 +869,56 @@ mod test {
     );
   }
 
+  #[test]
+  fn should_format_calling_other_plugin_with_process_plugin_using_language_hint() {
+    let file_path1 = "/file1.txt_ps";
+    let file_path2 = "/file2.txt_ps";
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
+      .write_file(file_path1, "plugin-language: txt: format this text")
+      .write_file(file_path2, "plugin-language: unknown: format this text")
+      .build();
+    run_test_cli(vec!["fmt", "*.txt_ps"], &environment).unwrap();
+    assert_eq!(environment.take_stdout_messages(), vec![get_plural_formatted_text(2)]);
+    assert_eq!(
+      environment.read_file(file_path1).unwrap(),
+      "plugin-language: txt: format this text_formatted_formatted_process"
+    );
+    // falls back to the file path, which no plugin matches
+    assert_eq!(
+      environment.read_file(file_path2).unwrap(),
+      "plugin-language: unknown: format this text_formatted_process"
+    );
+  }
+
+  #[test]
+  fn should_format_calling_other_plugin_with_wasm_plugin_using_language_hint() {
+    let file_path1 = "/file1.txt";
+    let file_path2 = "/file2.txt";
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
+      .with_local_config("/config.json", |c| {
+        c.add_remote_wasm_plugin().add_remote_process_plugin().add_config_section(
+          "testProcessPlugin",
+          r#"{
+            "associations": ["**/*.txt_ps", "**/*.sql"]
+          }"#,
+        );
+      })
+      .write_file(file_path1, "plugin-language: txt_ps: format this text")
+      // only matched by the process plugin's associations
+      .write_file(file_path2, "plugin-language: sql: format this text")
+      .build();
+    run_test_cli(vec!["fmt", "--config", "/config.json", "*.txt"], &environment).unwrap();
+    assert_eq!(environment.take_stdout_messages(), vec![get_plural_formatted_text(2)]);
+    assert_eq!(
+      environment.read_file(file_path1).unwrap(),
+      "plugin-language: txt_ps: format this text_formatted_process_formatted"
+    );
+    assert_eq!(
+      environment.read_file(file_path2).unwrap(),
+      "plugin-language: sql: format this text_formatted_process_formatted"
+    );
+  }
+
   #[test]
   fn should_error_calling_wasm_plugin_with_process_plugin_and_wasm_plugin_errors() {
     let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin()
@@ -1844,6 +2219,61 @@ mod test {
     assert_eq!(logged_messages, vec!["/file1.txt", "/file2.txt",]);
   }
 
+  #[test]
+  fn should_check_files_at_git_ref() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_local_config("/project/dprint.json", |c| {
+        c.add_remote_wasm_plugin();
+      })
+      .write_file("/project/file1.txt", "text_formatted")
+      .set_cwd("/project")
+      .build();
+    let config_text = environment.read_file("/project/dprint.json").unwrap();
+    environment.set_git_root_dir("/project");
+    environment.add_git_tree_file("main", "dprint.json", &config_text);
+    environment.add_git_tree_file("main", "file1.txt", "text");
+    environment.add_git_tree_file("main", "sub/file2.txt", "text");
+    environment.add_git_tree_file("main", "sub/file3.txt", "text_formatted");
+    environment.add_git_tree_file("main", ".gitignore", "ignored.txt");
+    environment.add_git_tree_file("main", "ignored.txt", "text");
+
+    run_test_cli(vec!["check"], &environment).unwrap();
+    let err = run_test_cli(vec!["check", "--list-different", "--git-ref", "main"], &environment).unwrap_err();
+    err.assert_exit_code(20);
+    let mut logged_messages = environment.take_stdout_messages();
+    logged_messages.sort();
+    assert_eq!(logged_messages, vec!["/project/file1.txt", "/project/sub/file2.txt"]);
+    // the working tree is untouched
+    assert_eq!(environment.read_file("/project/file1.txt").unwrap(), "text_formatted");
+    assert!(!environment.path_exists("/project/sub/file2.txt"));
+
+    let err = run_test_cli(vec!["check", "--git-ref", "other"], &environment).unwrap_err();
+    err.assert_exit_code(1);
+  }
+
+  #[test]
+  fn should_resolve_config_from_git_ref() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_local_config("/project/dprint.json", |c| {
+        c.add_remote_wasm_plugin();
+      })
+      .with_local_config("/tree/dprint.json", |c| {
+        c.add_remote_wasm_plugin().add_excludes("excluded.txt");
+      })
+      .write_file("/project/excluded.txt", "text")
+      .set_cwd("/project")
+      .build();
+    environment.set_git_root_dir("/project");
+    environment.add_git_tree_file("main", "dprint.json", &environment.read_file("/tree/dprint.json").unwrap());
+    environment.add_git_tree_file("main", "excluded.txt", "text");
+    environment.add_git_tree_file("main", "file.txt", "text_formatted");
+
+    run_test_cli(vec!["check", "--git-ref", "main"], &environment).unwrap();
+    let err = run_test_cli(vec!["check", "--list-different"], &environment).unwrap_err();
+    err.assert_exit_code(20);
+    assert_eq!(environment.take_stdout_messages(), vec!["/project/excluded.txt"]);
+  }
+
   #[test]
   fn should_handle_bom() {
     let file_path = "/file.txt";
@@ -1995,6 +2425,167 @@ mod test {
     assert_eq!(environment.take_stdout_messages(), vec!["text_new_ending"]);
   }
 
+  /// Encodes the file path and text records of `fmt --stdin-files`.
+  fn stdin_files(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (file_path, file_text) in files {
+      for value in [file_path, file_text] {
+        bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+      }
+    }
+    bytes
+  }
+
+  fn run_test_cli_with_stdin_files(environment: &TestEnvironment, stdin: &[u8]) -> Result<(), TestAppError> {
+    let mut writer = environment.stdin_writer();
+    writer.write_all(stdin).unwrap();
+    drop(writer);
+    run_test_cli(vec!["fmt", "--stdin-files"], environment)
+  }
+
+  fn stdout_records(files: &[(&str, &str)]) -> Vec<String> {
+    files.iter().map(|file| String::from_utf8_lossy(&stdin_files(&[*file])).into_owned()).collect()
+  }
+
+  #[test]
+  fn should_format_multiple_files_from_stdin() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_and_process_plugin().build();
+    let stdin = stdin_files(&[
+      ("file.txt", "text"),
+      ("sub/file.txt_ps", "text2"),
+      ("file.other", "text3\0with nul"),
+      ("file2.txt", "text_formatted"),
+      ("file3.txt", ""),
+    ]);
+    run_test_cli_with_stdin_files(&environment, &stdin).unwrap();
+    assert_eq!(
+      environment.take_stdout_messages(),
+      stdout_records(&[
+        ("file.txt", "text_formatted"),
+        ("sub/file.txt_ps", "text2_formatted_process"),
+        ("file.other", "text3\0with nul"),
+        ("file2.txt", "text_formatted"),
+        ("file3.txt", "_formatted"),
+      ])
+    );
+  }
+
+  #[test]
+  fn should_format_file_from_stdin_with_length_not_valid_as_utf8() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin().build();
+    // 0xD2 (210) in the length prefix of the output isn't valid UTF-8 on its own
+    let file_text = "a".repeat(200);
+    run_test_cli_with_stdin_files(&environment, &stdin_files(&[("file.txt", &file_text)])).unwrap();
+    assert_eq!(
+      environment.take_stdout_messages(),
+      stdout_records(&[("file.txt", &format!("{}_formatted", file_text))])
+    );
+  }
+
+  #[test]
+  fn should_format_no_files_from_stdin() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin().build();
+    run_test_cli_with_stdin_files(&environment, &[]).unwrap();
+    assert_eq!(environment.take_stdout_messages(), Vec::<String>::new());
+  }
+
+  #[test]
+  fn should_format_multiple_files_from_stdin_with_absolute_paths() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|c| {
+        c.add_includes("/src/**.*").add_remote_wasm_plugin();
+      })
+      .build();
+    // the files don't need to exist
+    run_test_cli_with_stdin_files(&environment, &stdin_files(&[("/file.txt", "text"), ("/src/file.txt", "text")])).unwrap();
+    assert_eq!(
+      environment.take_stdout_messages(),
+      stdout_records(&[("/file.txt", "text"), ("/src/file.txt", "text_formatted")])
+    );
+  }
+
+  #[test]
+  fn should_output_all_files_from_stdin_when_one_errors() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin().build();
+    let err = run_test_cli_with_stdin_files(&environment, &stdin_files(&[("file1.txt", "should_error"), ("file2.txt", "text")])).unwrap_err();
+    err.assert_exit_code(1);
+    assert_eq!(err.to_string(), "Had 1 error formatting.");
+    assert_eq!(
+      environment.take_stdout_messages(),
+      stdout_records(&[("file1.txt", "should_error"), ("file2.txt", "text_formatted")])
+    );
+    assert_eq!(environment.take_stderr_messages(), vec!["Error formatting file1.txt. Message: Did error."]);
+  }
+
+  #[test]
+  fn should_output_files_from_stdin_before_it_ends() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin().build();
+    let mut writer = environment.stdin_writer();
+    // only sends the second file once the first one was output
+    let handle = std::thread::spawn({
+      let environment = environment.clone();
+      move || {
+        writer.write_all(&stdin_files(&[("file1.txt", "text1")])).unwrap();
+        let mut messages = Vec::new();
+        for _ in 0..1000 {
+          messages.extend(environment.take_stdout_messages());
+          if !messages.is_empty() {
+            break;
+          }
+          std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        writer.write_all(&stdin_files(&[("file2.txt", "text2")])).unwrap();
+        messages
+      }
+    });
+    run_test_cli(vec!["fmt", "--stdin-files"], &environment).unwrap();
+    assert_eq!(handle.join().unwrap(), stdout_records(&[("file1.txt", "text1_formatted")]));
+    assert_eq!(environment.take_stdout_messages(), stdout_records(&[("file2.txt", "text2_formatted")]));
+  }
+
+  #[test]
+  fn should_error_for_invalid_stdin_files() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin().build();
+    let mut stdin = stdin_files(&[("file1.txt", "text")]);
+    stdin.extend_from_slice(&9u32.to_be_bytes());
+    stdin.extend_from_slice(b"file2.txt");
+    stdin.extend_from_slice(&5u32.to_be_bytes());
+    stdin.extend_from_slice(b"te");
+    let err = run_test_cli_with_stdin_files(&environment, &stdin).unwrap_err();
+    err.assert_exit_code(1);
+    assert_eq!(err.to_string(), "Unexpected end of stdin in the text of file2.txt: Unexpected end of pipe.");
+    // the files before it are still output
+    assert_eq!(environment.take_stdout_messages(), stdout_records(&[("file1.txt", "text_formatted")]));
+  }
+
+  #[test]
+  fn should_read_stdin_files() {
+    fn read_all(bytes: &[u8]) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+      let mut reader = bytes;
+      let mut files = Vec::new();
+      while let Some(file) = read_stdin_file(&mut reader)? {
+        files.push(file);
+      }
+      Ok(files)
+    }
+
+    assert_eq!(read_all(b"").unwrap(), vec![]);
+    assert_eq!(
+      read_all(&stdin_files(&[("a.txt", "a\0b"), ("b.txt", "")])).unwrap(),
+      vec![(PathBuf::from("a.txt"), b"a\0b".to_vec()), (PathBuf::from("b.txt"), Vec::new())]
+    );
+    assert_eq!(
+      read_all(&[0, 0]).unwrap_err().to_string(),
+      "Unexpected end of stdin in the size of a file path."
+    );
+    assert_eq!(read_all(&[0, 0, 0, 5, b'a']).unwrap_err().to_string(), "Unexpected end of stdin in a file path");
+    assert_eq!(
+      read_all(&stdin_files(&[("", "text")])).unwrap_err().to_string(),
+      "Expected a file path in stdin, but found an empty value."
+    );
+  }
+
   #[test]
   fn should_error_if_process_plugin_has_no_checksum_in_config() {
     let environment = TestEnvironmentBuilder::new()
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use tower_lsp::lsp_types::Diagnostic;
 use tower_lsp::lsp_types::MessageType;
 use tower_lsp::Client;
+use url::Url;
 
 pub trait ClientTrait: std::fmt::Debug + Send + Sync {
   fn log(&self, message_type: MessageType, message: String);
+  fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>);
 }
 
 impl ClientTrait for Client {
@@ -14,6 +17,13 @@ impl ClientTrait for Client {
       client.log_message(message_type, &message).await;
     });
   }
+
+  fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+    let client = self.clone();
+    dprint_core::async_runtime::spawn(async move {
+      client.publish_diagnostics(uri, diagnostics, None).await;
+    });
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +38,10 @@ impl ClientWrapper {
     self.log(MessageType::INFO, message);
   }
 
+  pub fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+    self.0.publish_diagnostics(uri, diagnostics)
+  }
+
   fn log(&self, message_type: MessageType, message: String) {
     self.0.log(message_type, message)
   }
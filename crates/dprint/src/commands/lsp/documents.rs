@@ -68,6 +68,10 @@ impl<TEnvironment: Environment> Documents<TEnvironment> {
     Some((entry.text.clone(), entry.line_index.clone()))
   }
 
+  pub fn has_text(&self, uri: &Url, text: &str) -> bool {
+    self.docs.get(uri).map(|entry| entry.text == text).unwrap_or(false)
+  }
+
   pub fn get_content_with_range(&mut self, uri: &Url, lsp_range: lsp_types::Range) -> Option<(String, FormatRange, LineIndex)> {
     let Some(entry) = self.docs.get_mut(uri) else {
       log_warn!(self.environment, "Missing document: {}", uri);
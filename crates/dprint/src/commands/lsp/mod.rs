@@ -1,5 +1,6 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -8,15 +9,20 @@ use anyhow::Context;
 use anyhow::Result;
 use dprint_core::async_runtime::JoinHandle;
 use dprint_core::plugins::process::start_parent_process_checker_task;
+use dprint_core::plugins::FormatDiagnostic;
+use dprint_core::plugins::FormatDiagnosticSeverity;
 use dprint_core::plugins::FormatRange;
 use dprint_core::plugins::HostFormatRequest;
 use parking_lot::Mutex;
+use text_size::TextSize;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::Semaphore;
 use tokio::try_join;
 use tokio_util::sync::CancellationToken;
 use tower_lsp::jsonrpc::Result as LspResult;
+use tower_lsp::lsp_types::Diagnostic;
+use tower_lsp::lsp_types::DiagnosticSeverity;
 use tower_lsp::lsp_types::DidChangeTextDocumentParams;
 use tower_lsp::lsp_types::DidCloseTextDocumentParams;
 use tower_lsp::lsp_types::DidOpenTextDocumentParams;
@@ -25,7 +31,9 @@ use tower_lsp::lsp_types::DocumentRangeFormattingParams;
 use tower_lsp::lsp_types::InitializeParams;
 use tower_lsp::lsp_types::InitializeResult;
 use tower_lsp::lsp_types::InitializedParams;
+use tower_lsp::lsp_types::NumberOrString;
 use tower_lsp::lsp_types::OneOf;
+use tower_lsp::lsp_types::Range;
 use tower_lsp::lsp_types::ServerCapabilities;
 use tower_lsp::lsp_types::ServerInfo;
 use tower_lsp::lsp_types::TextDocumentSyncCapability;
@@ -131,8 +139,17 @@ struct EditorFormatRequest {
   pub token: Arc<CancellationToken>,
 }
 
+#[derive(Default)]
+struct EditorFormatResponse {
+  pub edits: Option<Vec<TextEdit>>,
+  /// Warnings from plugins that check the formatted text.
+  pub check_diagnostics: Vec<Diagnostic>,
+  /// The formatted text when there are edits.
+  pub formatted_text: Option<String>,
+}
+
 enum ChannelMessage {
-  Format(EditorFormatRequest, oneshot::Sender<Result<Option<Vec<TextEdit>>>>),
+  Format(EditorFormatRequest, oneshot::Sender<Result<EditorFormatResponse>>),
   Shutdown(oneshot::Sender<()>),
   /// This message is used for testing.
   #[cfg(test)]
@@ -143,20 +160,20 @@ async fn handle_format_request<TEnvironment: Environment>(
   mut request: EditorFormatRequest,
   scope_container: Rc<LspPluginsScopeContainer<TEnvironment>>,
   environment: &TEnvironment,
-) -> Result<Option<Vec<TextEdit>>> {
+) -> Result<EditorFormatResponse> {
   let Some(parent_dir) = request.file_path.parent() else {
     log_warn!(environment, "Cannot format non-file path: {}", request.file_path.display());
-    return Ok(None);
+    return Ok(Default::default());
   };
   if request.token.is_cancelled() {
-    return Ok(None);
+    return Ok(Default::default());
   }
   let Some(scope) = scope_container.resolve_by_path(parent_dir).await? else {
     log_stderr_info!(environment, "Path did not have a dprint config file: {}", request.file_path.display());
-    return Ok(None);
+    return Ok(Default::default());
   };
   if request.token.is_cancelled() {
-    return Ok(None);
+    return Ok(Default::default());
   }
   // canonicalize the path
   request.file_path = environment
@@ -166,29 +183,111 @@ async fn handle_format_request<TEnvironment: Environment>(
 
   if !scope.can_format_for_editor(&request.file_path) {
     log_debug!(environment, "Excluded file: {}", request.file_path.display());
-    return Ok(None);
+    return Ok(Default::default());
   }
 
-  let Some(result) = scope
+  let result = scope
     .format(HostFormatRequest {
-      file_path: request.file_path,
+      file_path: request.file_path.clone(),
       file_bytes: request.file_text.as_bytes().to_vec(),
       range: request.range,
       override_config: Default::default(),
-      token: request.token,
+      hints: Default::default(),
+      token: request.token.clone(),
     })
-    .await?
-  else {
-    return Ok(None);
+    .await;
+  let result = match result {
+    Ok(result) => result,
+    Err(err) => {
+      let Some(diagnostic) = FormatDiagnostic::from_error(&err) else {
+        return Err(err);
+      };
+      let line_index = request.maybe_line_index.unwrap_or_else(|| LineIndex::new(&request.file_text));
+      return Err(
+        FormatDiagnosticError {
+          message: diagnostic.to_string(),
+          diagnostic: to_lsp_diagnostic(diagnostic, &request.file_text, &line_index),
+        }
+        .into(),
+      );
+    }
+  };
+  let new_text = match result {
+    Some(result) => Some(String::from_utf8(result).context("Failed converting formatted text to utf-8.")?),
+    None => None,
+  };
+
+  // check the formatted text so the diagnostic ranges match the document once the edits are applied
+  let formatted_text = new_text.as_deref().unwrap_or(&request.file_text);
+  let check_diagnostics = match scope.check(&request.file_path, formatted_text.as_bytes(), request.token.clone()).await {
+    Ok(diagnostics) => diagnostics,
+    Err(err) => {
+      log_warn!(environment, "Failed checking {}: {:#}", request.file_path.display(), err);
+      Vec::new()
+    }
   };
+
   dprint_core::async_runtime::spawn_blocking(move || {
-    let new_text = String::from_utf8(result).context("Failed converting formatted text to utf-8.")?;
-    let line_index = request.maybe_line_index.unwrap_or_else(|| LineIndex::new(&request.file_text));
-    Ok(Some(get_edits(&request.file_text, &new_text, &line_index)))
+    let edits = new_text.as_ref().map(|new_text| {
+      let line_index = request.maybe_line_index.unwrap_or_else(|| LineIndex::new(&request.file_text));
+      get_edits(&request.file_text, new_text, &line_index)
+    });
+    let check_diagnostics = if check_diagnostics.is_empty() {
+      Vec::new()
+    } else {
+      let checked_text = new_text.as_deref().unwrap_or(&request.file_text);
+      let line_index = LineIndex::new(checked_text);
+      check_diagnostics.iter().map(|d| to_lsp_diagnostic(d, checked_text, &line_index)).collect()
+    };
+    Ok(EditorFormatResponse {
+      edits,
+      check_diagnostics,
+      formatted_text: new_text,
+    })
   })
   .await?
 }
 
+/// A format error where the plugin provided the location.
+#[derive(Debug)]
+struct FormatDiagnosticError {
+  message: String,
+  diagnostic: Diagnostic,
+}
+
+impl std::fmt::Display for FormatDiagnosticError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for FormatDiagnosticError {}
+
+fn to_lsp_diagnostic(diagnostic: &FormatDiagnostic, file_text: &str, line_index: &LineIndex) -> Diagnostic {
+  let range = diagnostic
+    .range
+    .as_ref()
+    .filter(|r| r.start <= r.end && file_text.is_char_boundary(r.start) && file_text.is_char_boundary(r.end))
+    .map(|r| {
+      Range::new(
+        line_index.position_utf16(TextSize::from(r.start as u32)),
+        line_index.position_utf16(TextSize::from(r.end as u32)),
+      )
+    })
+    .unwrap_or_default();
+  Diagnostic {
+    range,
+    severity: Some(match diagnostic.severity {
+      FormatDiagnosticSeverity::Error => DiagnosticSeverity::ERROR,
+      FormatDiagnosticSeverity::Warning => DiagnosticSeverity::WARNING,
+    }),
+    code: diagnostic.code.clone().map(NumberOrString::String),
+    source: Some("dprint".to_string()),
+    message: diagnostic.message.clone(),
+    ..Default::default()
+  }
+}
+
 pub async fn run_language_server<TEnvironment: Environment>(
   _args: &CliArgs,
   environment: &TEnvironment,
@@ -260,6 +359,15 @@ fn start_message_handler<TEnvironment: Environment>(
 
 struct State<TEnvironment: Environment> {
   documents: Documents<TEnvironment>,
+  /// Documents that currently have format diagnostics published.
+  diagnostic_uris: HashSet<Url>,
+  /// Check diagnostics to publish once the client applies the edits.
+  pending_check_diagnostics: HashMap<Url, PendingCheckDiagnostics>,
+}
+
+struct PendingCheckDiagnostics {
+  formatted_text: String,
+  diagnostics: Vec<Diagnostic>,
 }
 
 struct Backend<TEnvironment: Environment> {
@@ -277,6 +385,8 @@ impl<TEnvironment: Environment> Backend<TEnvironment> {
       sender,
       state: Mutex::new(State {
         documents: Documents::new(environment),
+        diagnostic_uris: Default::default(),
+        pending_check_diagnostics: Default::default(),
       }),
     }
   }
@@ -288,9 +398,29 @@ impl<TEnvironment: Environment> Backend<TEnvironment> {
     let result = self.send_format_request_inner(request).await;
     drop_token.completed();
     let result = match result {
-      Ok(value) => Ok(value),
+      Ok(response) => {
+        self.state.lock().pending_check_diagnostics.remove(uri);
+        if response.check_diagnostics.is_empty() {
+          self.clear_diagnostics(uri);
+        } else if let Some(formatted_text) = response.formatted_text {
+          // the ranges are for the formatted text, so wait for the client to apply the edits
+          self.state.lock().pending_check_diagnostics.insert(
+            uri.clone(),
+            PendingCheckDiagnostics {
+              formatted_text,
+              diagnostics: response.check_diagnostics,
+            },
+          );
+        } else {
+          self.publish_diagnostics(uri, response.check_diagnostics);
+        }
+        Ok(response.edits)
+      }
       Err(err) => {
         log_error!(self.environment, "Failed formatting '{}': {:#}", uri, err);
+        if let Some(err) = err.downcast_ref::<FormatDiagnosticError>() {
+          self.publish_diagnostics(uri, vec![err.diagnostic.clone()]);
+        }
         Ok(None)
       }
     };
@@ -303,7 +433,18 @@ impl<TEnvironment: Environment> Backend<TEnvironment> {
     result
   }
 
-  async fn send_format_request_inner(&self, request: EditorFormatRequest) -> Result<Option<Vec<TextEdit>>> {
+  fn publish_diagnostics(&self, uri: &Url, diagnostics: Vec<Diagnostic>) {
+    self.state.lock().diagnostic_uris.insert(uri.clone());
+    self.client.publish_diagnostics(uri.clone(), diagnostics);
+  }
+
+  fn clear_diagnostics(&self, uri: &Url) {
+    if self.state.lock().diagnostic_uris.remove(uri) {
+      self.client.publish_diagnostics(uri.clone(), Vec::new());
+    }
+  }
+
+  async fn send_format_request_inner(&self, request: EditorFormatRequest) -> Result<EditorFormatResponse> {
     let (sender, receiver) = oneshot::channel();
     self.sender.send(ChannelMessage::Format(request, sender))?;
     receiver.await?
@@ -364,10 +505,26 @@ impl<TEnvironment: Environment> LanguageServer for Backend<TEnvironment> {
   }
 
   async fn did_change(&self, params: DidChangeTextDocumentParams) {
-    self.state.lock().documents.changed(params);
+    let uri = params.text_document.uri.clone();
+    let pending = {
+      let mut state = self.state.lock();
+      state.documents.changed(params);
+      state
+        .pending_check_diagnostics
+        .remove(&uri)
+        .map(|pending| (state.documents.has_text(&uri, &pending.formatted_text), pending.diagnostics))
+    };
+    match pending {
+      Some((true, diagnostics)) => self.publish_diagnostics(&uri, diagnostics),
+      // the document changed in some other way, so the existing diagnostics are out of date
+      Some((false, _)) => self.clear_diagnostics(&uri),
+      None => {}
+    }
   }
 
   async fn did_close(&self, params: DidCloseTextDocumentParams) {
+    self.state.lock().pending_check_diagnostics.remove(&params.text_document.uri);
+    self.clear_diagnostics(&params.text_document.uri);
     self.state.lock().documents.closed(params);
   }
 
@@ -910,6 +1067,182 @@ mod test {
     });
   }
 
+  #[test]
+  fn should_publish_format_diagnostics() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .with_default_config(|c| {
+        c.add_remote_process_plugin().add_includes("**/*.txt_ps");
+      })
+      .build();
+
+    environment.clone().run_in_runtime(async move {
+      let (backend, recv_task, test_client) = setup_backend(environment.clone());
+      let run_test_task = dprint_core::async_runtime::spawn({
+        let environment = environment.clone();
+        let test_client = test_client.clone();
+        async move {
+          let file_uri = Url::parse("file:///file.txt_ps").unwrap();
+          backend
+            .did_open(DidOpenTextDocumentParams {
+              text_document: TextDocumentItem {
+                uri: file_uri.clone(),
+                language_id: "txt".to_string(),
+                version: 0,
+                text: "a\ndiagnostic: Unexpected token.".to_string(),
+              },
+            })
+            .await;
+          let format = || {
+            backend.formatting(DocumentFormattingParams {
+              text_document: TextDocumentIdentifier { uri: file_uri.clone() },
+              options: Default::default(),
+              work_done_progress_params: Default::default(),
+            })
+          };
+
+          // the test plugin only looks at the start of the text
+          assert_eq!(format().await.unwrap().unwrap().len(), 1);
+          assert_eq!(test_client.take_diagnostics(), Vec::new());
+
+          backend
+            .did_change(DidChangeTextDocumentParams {
+              text_document: VersionedTextDocumentIdentifier {
+                uri: file_uri.clone(),
+                version: 1,
+              },
+              content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 0), Position::new(1, 0))),
+                range_length: None,
+                text: String::new(),
+              }],
+            })
+            .await;
+          assert_eq!(format().await.unwrap(), None);
+          assert_eq!(
+            environment.take_stderr_messages(),
+            vec!["Failed formatting 'file:///file.txt_ps': Unexpected token. (test)".to_string()],
+          );
+          assert_eq!(
+            test_client.take_diagnostics(),
+            vec![(
+              file_uri.clone(),
+              vec![Diagnostic {
+                range: Range::new(Position::new(0, 12), Position::new(0, 29)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String("test".to_string())),
+                source: Some("dprint".to_string()),
+                message: "Unexpected token.".to_string(),
+                ..Default::default()
+              }]
+            )]
+          );
+
+          // closing the document should clear the diagnostics
+          backend
+            .did_close(DidCloseTextDocumentParams {
+              text_document: TextDocumentIdentifier { uri: file_uri.clone() },
+            })
+            .await;
+          assert_eq!(test_client.take_diagnostics(), vec![(file_uri.clone(), Vec::new())]);
+
+          backend.shutdown().await.unwrap();
+        }
+      });
+
+      try_join!(recv_task, run_test_task).unwrap();
+      test_client.take_messages();
+    });
+  }
+
+  #[test]
+  fn should_publish_plugin_check_diagnostics() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin()
+      .with_default_config(|c| {
+        c.add_remote_process_plugin().add_includes("**/*.txt_ps");
+      })
+      .build();
+
+    environment.clone().run_in_runtime(async move {
+      let (backend, recv_task, test_client) = setup_backend(environment.clone());
+      let run_test_task = dprint_core::async_runtime::spawn({
+        let test_client = test_client.clone();
+        async move {
+          let file_uri = Url::parse("file:///file.txt_ps").unwrap();
+          backend
+            .did_open(DidOpenTextDocumentParams {
+              text_document: TextDocumentItem {
+                uri: file_uri.clone(),
+                language_id: "txt".to_string(),
+                version: 0,
+                text: "warn: Long line.".to_string(),
+              },
+            })
+            .await;
+          let format = || {
+            backend.formatting(DocumentFormattingParams {
+              text_document: TextDocumentIdentifier { uri: file_uri.clone() },
+              options: Default::default(),
+              work_done_progress_params: Default::default(),
+            })
+          };
+
+          // formats successfully, but waits for the edits to be applied before publishing the warning
+          assert_eq!(format().await.unwrap().unwrap().len(), 1);
+          assert_eq!(test_client.take_diagnostics(), Vec::new());
+          backend
+            .did_change(DidChangeTextDocumentParams {
+              text_document: VersionedTextDocumentIdentifier {
+                uri: file_uri.clone(),
+                version: 1,
+              },
+              content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "warn: Long line._formatted_process".to_string(),
+              }],
+            })
+            .await;
+          assert_eq!(
+            test_client.take_diagnostics(),
+            vec![(
+              file_uri.clone(),
+              vec![Diagnostic {
+                range: Range::new(Position::new(0, 6), Position::new(0, 16)),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("test".to_string())),
+                source: Some("dprint".to_string()),
+                message: "Long line.".to_string(),
+                ..Default::default()
+              }]
+            )]
+          );
+
+          // no longer has the warning, so it should clear the diagnostics
+          backend
+            .did_change(DidChangeTextDocumentParams {
+              text_document: VersionedTextDocumentIdentifier {
+                uri: file_uri.clone(),
+                version: 2,
+              },
+              content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "text_formatted_process".to_string(),
+              }],
+            })
+            .await;
+          assert_eq!(format().await.unwrap(), None);
+          assert_eq!(test_client.take_diagnostics(), vec![(file_uri.clone(), Vec::new())]);
+
+          backend.shutdown().await.unwrap();
+        }
+      });
+
+      try_join!(recv_task, run_test_task).unwrap();
+      test_client.take_messages();
+    });
+  }
+
   fn setup_backend(environment: TestEnvironment) -> (Backend<TestEnvironment>, JoinHandle<()>, Arc<TestClient>) {
     let plugin_cache = PluginCache::new(environment.clone());
     let plugin_resolver = Rc::new(PluginResolver::new(environment.clone(), plugin_cache));
@@ -922,6 +1255,7 @@ mod test {
   #[derive(Debug, Default)]
   struct TestClient {
     logged_messages: Mutex<Vec<(MessageType, String)>>,
+    published_diagnostics: Mutex<Vec<(Url, Vec<Diagnostic>)>>,
   }
 
   impl Drop for TestClient {
@@ -942,11 +1276,19 @@ mod test {
     pub fn take_messages(&self) -> Vec<(MessageType, String)> {
       self.logged_messages.lock().drain(..).collect()
     }
+
+    pub fn take_diagnostics(&self) -> Vec<(Url, Vec<Diagnostic>)> {
+      self.published_diagnostics.lock().drain(..).collect()
+    }
   }
 
   impl ClientTrait for TestClient {
     fn log(&self, message_type: MessageType, message: String) {
       self.logged_messages.lock().push((message_type, message));
     }
+
+    fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+      self.published_diagnostics.lock().push((uri, diagnostics));
+    }
   }
 }
@@ -1,4 +1,6 @@
+mod bench;
 mod config;
+mod daemon;
 mod editor;
 mod formatting;
 mod general;
@@ -7,7 +9,9 @@ mod upgrade;
 #[cfg(target_os = "windows")]
 mod windows_install;
 
+pub use bench::*;
 pub use config::*;
+pub use daemon::*;
 pub use editor::*;
 pub use formatting::*;
 pub use general::*;
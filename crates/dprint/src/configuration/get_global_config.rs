@@ -76,6 +76,9 @@ mod tests {
     config_map.insert(String::from("useTabs"), ConfigMapValue::from_bool(true));
     config_map.insert(String::from("indentWidth"), ConfigMapValue::from_i32(2));
     config_map.insert(String::from("newLineKind"), ConfigMapValue::from_str("crlf"));
+    config_map.insert(String::from("trimTrailingWhitespace"), ConfigMapValue::from_bool(true));
+    config_map.insert(String::from("insertFinalNewLine"), ConfigMapValue::from_bool(true));
+    config_map.insert(String::from("maxConsecutiveBlankLines"), ConfigMapValue::from_i32(2));
     assert_result(
       config_map,
       GlobalConfiguration {
@@ -83,6 +86,9 @@ mod tests {
         use_tabs: Some(true),
         indent_width: Some(2),
         new_line_kind: Some(NewLineKind::CarriageReturnLineFeed),
+        trim_trailing_whitespace: Some(true),
+        insert_final_new_line: Some(true),
+        max_consecutive_blank_lines: Some(2),
       },
       &[],
     );
@@ -103,6 +109,9 @@ mod tests {
         } else {
           NewLineKind::LineFeed
         }),
+        trim_trailing_whitespace: None,
+        insert_final_new_line: None,
+        max_consecutive_blank_lines: None,
       },
       &[],
     );
@@ -119,6 +128,9 @@ mod tests {
         use_tabs: None,
         indent_width: None,
         new_line_kind: None,
+        trim_trailing_whitespace: None,
+        insert_final_new_line: None,
+        max_consecutive_blank_lines: None,
       },
       &["Unexpected non-string, boolean, or int property (test)"],
     );
@@ -136,6 +148,9 @@ mod tests {
         use_tabs: None,
         indent_width: None,
         new_line_kind: None,
+        trim_trailing_whitespace: None,
+        insert_final_new_line: None,
+        max_consecutive_blank_lines: None,
       },
       &[
         "invalid digit found in string (lineWidth)",
@@ -155,6 +170,9 @@ mod tests {
         use_tabs: None,
         indent_width: None,
         new_line_kind: None,
+        trim_trailing_whitespace: None,
+        insert_final_new_line: None,
+        max_consecutive_blank_lines: None,
       },
       &[],
     );
@@ -97,7 +97,6 @@ pub async fn resolve_config_from_path<TEnvironment: Environment>(
   resolved_config_path: &ResolvedConfigPath,
   environment: &TEnvironment,
 ) -> Result<ResolvedConfig, ResolveConfigError> {
-  let base_source = resolved_config_path.resolved_path.source.parent();
   let config_file_path = &resolved_config_path.resolved_path.file_path;
   let config_map = get_config_map_from_path(
     ConfigPathContext {
@@ -108,7 +107,7 @@ pub async fn resolve_config_from_path<TEnvironment: Environment>(
   )
   .map_err(|err| anyhow::anyhow!("{:#}\n    at {}", err, resolved_config_path.resolved_path.source.display()))?;
 
-  let mut config_map = match config_map {
+  let config_map = match config_map {
     Ok(main_config_map) => main_config_map,
     Err(err) => {
       return Err(ResolveConfigError::NotFound {
@@ -118,6 +117,32 @@ pub async fn resolve_config_from_path<TEnvironment: Environment>(
     }
   };
 
+  resolve_config_from_config_map(resolved_config_path, config_map, environment).await
+}
+
+/// Resolves the configuration from text that isn't on the file system, treating
+/// it as if it were the configuration file at the provided path.
+pub async fn resolve_config_from_text<TEnvironment: Environment>(
+  config_file_text: &str,
+  resolved_config_path: &ResolvedConfigPath,
+  environment: &TEnvironment,
+) -> Result<ResolvedConfig, ResolveConfigError> {
+  let config_map = get_config_map_from_text(
+    config_file_text,
+    ConfigPathContext {
+      current: &resolved_config_path.resolved_path,
+      origin: &resolved_config_path.resolved_path,
+    },
+  )?;
+  resolve_config_from_config_map(resolved_config_path, config_map, environment).await
+}
+
+async fn resolve_config_from_config_map<TEnvironment: Environment>(
+  resolved_config_path: &ResolvedConfigPath,
+  mut config_map: ConfigMap,
+  environment: &TEnvironment,
+) -> Result<ResolvedConfig, ResolveConfigError> {
+  let base_source = resolved_config_path.resolved_path.source.parent();
   let plugins_vec = take_plugins_array_from_config_map(&mut config_map, &base_source, environment)?; // always take this out of the config map
   let plugins = filter_duplicate_plugin_sources({
     // filter out any non-wasm plugins from remote config
@@ -306,13 +331,17 @@ fn get_config_map_from_path(path: ConfigPathContext, environment: &impl Environm
     Err(err) => return Ok(Err(err)),
   };
 
-  let mut result = match deserialize_config(&config_file_text) {
+  Ok(Ok(get_config_map_from_text(&config_file_text, path)?))
+}
+
+fn get_config_map_from_text(config_file_text: &str, path: ConfigPathContext) -> Result<ConfigMap> {
+  let mut result = match deserialize_config(config_file_text) {
     Ok(map) => map,
     Err(e) => bail!("Error deserializing. {}", e.to_string()),
   };
   template_expand(path, &mut result)?;
 
-  Ok(Ok(result))
+  Ok(result)
 }
 
 fn template_expand(path_ctx: ConfigPathContext, config_map: &mut ConfigMap) -> Result<()> {
@@ -22,6 +22,26 @@ pub enum DirEntry {
   File { name: std::ffi::OsString, path: PathBuf },
 }
 
+/// The files in a git tree at a revision.
+#[derive(Debug)]
+pub struct GitTree {
+  /// The top level directory of the repository.
+  pub root_dir: CanonicalizedPathBuf,
+  pub files: Vec<GitTreeFile>,
+}
+
+#[derive(Debug)]
+pub struct GitTreeFile {
+  /// Path relative to the repository's root directory.
+  pub path: PathBuf,
+  pub object_id: String,
+}
+
+/// Reads the contents of git objects in a repository.
+pub trait GitBlobReader: Send + Sync {
+  fn read_blob(&self, object_id: &str) -> Result<Vec<u8>>;
+}
+
 #[derive(Debug, Clone)]
 pub enum FilePermissions {
   Std(std::fs::Permissions),
@@ -60,7 +80,13 @@ pub trait Environment: Clone + Send + Sync + UrlDownloader + 'static {
 
   fn env_var(&self, name: &str) -> Option<OsString>;
 
-  fn get_staged_files(&self) -> Result<Vec<PathBuf>>;
+  /// Lists the staged files relative to `dir_path`, which is where git runs.
+  fn get_staged_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>>;
+  /// Lists the files of the repository at `dir_path` at the provided git revision.
+  fn get_git_tree(&self, dir_path: &Path, git_ref: &str) -> Result<GitTree>;
+  /// Opens a reader for the objects of the repository at `dir_path`, which
+  /// should be reused for reading many objects.
+  fn git_blob_reader(&self, dir_path: &Path) -> Result<Box<dyn GitBlobReader>>;
   fn read_file(&self, file_path: impl AsRef<Path>) -> Result<String>;
   fn read_file_bytes(&self, file_path: impl AsRef<Path>) -> Result<Vec<u8>>;
   fn write_file(&self, file_path: impl AsRef<Path>, file_text: &str) -> Result<()> {
@@ -0,0 +1,461 @@
+use anyhow::bail;
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::Read;
+use std::io::Write;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use dprint_core::async_runtime::async_trait;
+
+use super::CanonicalizedPathBuf;
+use super::DirEntry;
+use super::Environment;
+use super::FilePermissions;
+use super::GitBlobReader;
+use super::GitTree;
+use super::UrlDownloader;
+use crate::plugins::CompilationResult;
+use crate::utils::LogLevel;
+
+struct GitTreeIndex {
+  git_ref: String,
+  root_dir: CanonicalizedPathBuf,
+  cache_dir: CanonicalizedPathBuf,
+  /// Object ids by absolute file path.
+  files: HashMap<PathBuf, String>,
+  /// Child entries by absolute directory path.
+  dirs: HashMap<PathBuf, Vec<(PathBuf, bool)>>,
+  blob_reader: Box<dyn GitBlobReader>,
+}
+
+impl GitTreeIndex {
+  fn path_exists(&self, path: &Path) -> bool {
+    self.files.contains_key(path) || self.dirs.contains_key(path) || path == self.root_dir.join(".git")
+  }
+
+  fn bail_modify<T>(&self, path: &Path) -> Result<T> {
+    bail!("Cannot modify {} while reading files from git ref {}.", path.display(), self.git_ref)
+  }
+}
+
+/// Environment that can read the files of the repository from a git
+/// revision instead of the working tree.
+///
+/// Everything goes to the inner environment until a git ref is set with
+/// [`GitTreeEnvironment::read_git_ref`]. After that, paths outside the
+/// repository (ex. the cache directory) still go to the inner environment
+/// and modifying files in the repository is an error.
+#[derive(Clone)]
+pub struct GitTreeEnvironment<TEnvironment: Environment> {
+  inner: TEnvironment,
+  index: Arc<Mutex<Option<Arc<GitTreeIndex>>>>,
+}
+
+/// Goes back to reading the working tree when dropped.
+pub struct GitRefGuard {
+  index: Arc<Mutex<Option<Arc<GitTreeIndex>>>>,
+}
+
+impl Drop for GitRefGuard {
+  fn drop(&mut self) {
+    self.index.lock().take();
+  }
+}
+
+impl<TEnvironment: Environment> GitTreeEnvironment<TEnvironment> {
+  pub fn new(inner: TEnvironment) -> Self {
+    Self {
+      inner,
+      index: Default::default(),
+    }
+  }
+
+  pub fn inner(&self) -> &TEnvironment {
+    &self.inner
+  }
+
+  /// Reads the repository's files from `git_ref` until the returned guard is dropped.
+  pub fn read_git_ref(&self, git_ref: &str) -> Result<GitRefGuard> {
+    if let Some(index) = &*self.index.lock() {
+      bail!("Already reading files from git ref {}.", index.git_ref);
+    }
+    let inner = &self.inner;
+    let GitTree { root_dir, files: tree_files } = inner.get_git_tree(inner.cwd().as_ref(), git_ref)?;
+    let mut files = HashMap::with_capacity(tree_files.len());
+    let mut dirs: HashMap<PathBuf, Vec<(PathBuf, bool)>> = HashMap::new();
+    dirs.insert(root_dir.as_ref().to_path_buf(), Vec::new());
+    for file in tree_files {
+      let file_path = root_dir.join(&file.path);
+      // register each ancestor directory with its parent until reaching one that's known
+      let mut child = (file_path.clone(), false);
+      while let Some(parent) = child.0.parent().map(|parent| parent.to_path_buf()) {
+        let is_new_dir = !dirs.contains_key(&parent);
+        dirs.entry(parent.clone()).or_default().push(child);
+        if !is_new_dir {
+          break;
+        }
+        child = (parent, true);
+      }
+      files.insert(file_path, file.object_id);
+    }
+    let blob_reader = inner.git_blob_reader(root_dir.as_ref())?;
+    *self.index.lock() = Some(Arc::new(GitTreeIndex {
+      git_ref: git_ref.to_string(),
+      cache_dir: inner.get_cache_dir(),
+      root_dir,
+      files,
+      dirs,
+      blob_reader,
+    }));
+    Ok(GitRefGuard { index: self.index.clone() })
+  }
+
+  /// Gets the git tree and normalized path when it's a path in the git tree.
+  fn tree_path(&self, path: &Path) -> Option<(Arc<GitTreeIndex>, PathBuf)> {
+    let index = self.index.lock().clone()?;
+    let path = if self.inner.is_absolute_path(path) {
+      normalize_path(path)
+    } else {
+      normalize_path(&self.inner.cwd().join(path))
+    };
+    let is_in_tree = path.starts_with(&index.root_dir) && !path.starts_with(&index.cache_dir);
+    is_in_tree.then_some((index, path))
+  }
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+  let mut result = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        result.pop();
+      }
+      component => result.push(component),
+    }
+  }
+  result
+}
+
+#[async_trait(?Send)]
+impl<TEnvironment: Environment> UrlDownloader for GitTreeEnvironment<TEnvironment> {
+  async fn download_file(&self, url: &str) -> Result<Option<Vec<u8>>> {
+    self.inner.download_file(url).await
+  }
+}
+
+#[async_trait]
+impl<TEnvironment: Environment> Environment for GitTreeEnvironment<TEnvironment> {
+  fn is_real(&self) -> bool {
+    self.inner.is_real()
+  }
+
+  fn env_var(&self, name: &str) -> Option<OsString> {
+    self.inner.env_var(name)
+  }
+
+  fn get_staged_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
+    self.inner.get_staged_files(dir_path)
+  }
+
+  fn get_git_tree(&self, dir_path: &Path, git_ref: &str) -> Result<GitTree> {
+    self.inner.get_git_tree(dir_path, git_ref)
+  }
+
+  fn git_blob_reader(&self, dir_path: &Path) -> Result<Box<dyn GitBlobReader>> {
+    self.inner.git_blob_reader(dir_path)
+  }
+
+  fn read_file(&self, file_path: impl AsRef<Path>) -> Result<String> {
+    Ok(String::from_utf8(self.read_file_bytes(file_path)?)?)
+  }
+
+  fn read_file_bytes(&self, file_path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let Some((index, path)) = self.tree_path(file_path.as_ref()) else {
+      return self.inner.read_file_bytes(file_path);
+    };
+    match index.files.get(&path) {
+      Some(object_id) => index.blob_reader.read_blob(object_id),
+      None => bail!("Error reading file {}: Not found in git ref {}.", path.display(), index.git_ref),
+    }
+  }
+
+  fn write_file_bytes(&self, file_path: impl AsRef<Path>, bytes: &[u8]) -> Result<()> {
+    match self.tree_path(file_path.as_ref()) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.write_file_bytes(file_path, bytes),
+    }
+  }
+
+  fn rename(&self, path_from: impl AsRef<Path>, path_to: impl AsRef<Path>) -> Result<()> {
+    match self.tree_path(path_from.as_ref()).or_else(|| self.tree_path(path_to.as_ref())) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.rename(path_from, path_to),
+    }
+  }
+
+  fn remove_file(&self, file_path: impl AsRef<Path>) -> Result<()> {
+    match self.tree_path(file_path.as_ref()) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.remove_file(file_path),
+    }
+  }
+
+  fn remove_dir_all(&self, dir_path: impl AsRef<Path>) -> Result<()> {
+    match self.tree_path(dir_path.as_ref()) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.remove_dir_all(dir_path),
+    }
+  }
+
+  fn dir_info(&self, dir_path: impl AsRef<Path>) -> std::io::Result<Vec<DirEntry>> {
+    let Some((index, path)) = self.tree_path(dir_path.as_ref()) else {
+      return self.inner.dir_info(dir_path);
+    };
+    let Some(entries) = index.dirs.get(&path) else {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("Directory not found in git ref {}.", index.git_ref),
+      ));
+    };
+    Ok(
+      entries
+        .iter()
+        .map(|(path, is_dir)| {
+          if *is_dir {
+            DirEntry::Directory(path.clone())
+          } else {
+            DirEntry::File {
+              name: path.file_name().unwrap().to_os_string(),
+              path: path.clone(),
+            }
+          }
+        })
+        .collect(),
+    )
+  }
+
+  fn path_exists(&self, file_path: impl AsRef<Path>) -> bool {
+    match self.tree_path(file_path.as_ref()) {
+      Some((index, path)) => index.path_exists(&path),
+      None => self.inner.path_exists(file_path),
+    }
+  }
+
+  fn canonicalize(&self, path: impl AsRef<Path>) -> Result<CanonicalizedPathBuf> {
+    match self.tree_path(path.as_ref()) {
+      // the files don't necessarily exist in the working tree, so this can't resolve symlinks
+      Some((index, tree_path)) if index.path_exists(&tree_path) => Ok(CanonicalizedPathBuf::new(tree_path)),
+      Some((index, tree_path)) => bail!("Error canonicalizing path {}: Not found in git ref {}.", tree_path.display(), index.git_ref),
+      None => self.inner.canonicalize(path),
+    }
+  }
+
+  fn is_absolute_path(&self, path: impl AsRef<Path>) -> bool {
+    self.inner.is_absolute_path(path)
+  }
+
+  fn file_permissions(&self, path: impl AsRef<Path>) -> Result<FilePermissions> {
+    match self.tree_path(path.as_ref()) {
+      Some((index, path)) => bail!("Cannot get file permissions of {} in git ref {}.", path.display(), index.git_ref),
+      None => self.inner.file_permissions(path),
+    }
+  }
+
+  fn set_file_permissions(&self, path: impl AsRef<Path>, permissions: FilePermissions) -> Result<()> {
+    match self.tree_path(path.as_ref()) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.set_file_permissions(path, permissions),
+    }
+  }
+
+  fn mk_dir_all(&self, path: impl AsRef<Path>) -> Result<()> {
+    match self.tree_path(path.as_ref()) {
+      Some((index, path)) => index.bail_modify(&path),
+      None => self.inner.mk_dir_all(path),
+    }
+  }
+
+  fn cwd(&self) -> CanonicalizedPathBuf {
+    self.inner.cwd()
+  }
+
+  fn current_exe(&self) -> Result<PathBuf> {
+    self.inner.current_exe()
+  }
+
+  fn __log__(&self, text: &str) {
+    self.inner.__log__(text)
+  }
+
+  fn log_stderr_with_context(&self, text: &str, context_name: &str) {
+    self.inner.log_stderr_with_context(text, context_name)
+  }
+
+  fn log_machine_readable(&self, bytes: &[u8]) {
+    self.inner.log_machine_readable(bytes)
+  }
+
+  fn log_action_with_progress<TResult: Send + Sync, TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + Send + Sync>(
+    &self,
+    message: &str,
+    action: TCreate,
+    total_size: usize,
+  ) -> TResult {
+    self.inner.log_action_with_progress(message, action, total_size)
+  }
+
+  fn get_cache_dir(&self) -> CanonicalizedPathBuf {
+    self.inner.get_cache_dir()
+  }
+
+  fn get_home_dir(&self) -> Option<CanonicalizedPathBuf> {
+    self.inner.get_home_dir()
+  }
+
+  fn cpu_arch(&self) -> String {
+    self.inner.cpu_arch()
+  }
+
+  fn os(&self) -> String {
+    self.inner.os()
+  }
+
+  fn max_threads(&self) -> usize {
+    self.inner.max_threads()
+  }
+
+  fn cli_version(&self) -> String {
+    self.inner.cli_version()
+  }
+
+  fn get_time_secs(&self) -> u64 {
+    self.inner.get_time_secs()
+  }
+
+  fn get_selection(&self, prompt_message: &str, item_indent_width: u16, items: &[String]) -> Result<usize> {
+    self.inner.get_selection(prompt_message, item_indent_width, items)
+  }
+
+  fn get_multi_selection(&self, prompt_message: &str, item_indent_width: u16, items: &[(bool, String)]) -> Result<Vec<usize>> {
+    self.inner.get_multi_selection(prompt_message, item_indent_width, items)
+  }
+
+  fn confirm(&self, prompt_message: &str, default_value: bool) -> Result<bool> {
+    self.inner.confirm(prompt_message, default_value)
+  }
+
+  fn is_ci(&self) -> bool {
+    self.inner.is_ci()
+  }
+
+  fn log_level(&self) -> LogLevel {
+    self.inner.log_level()
+  }
+
+  fn compile_wasm(&self, wasm_bytes: &[u8]) -> Result<CompilationResult> {
+    self.inner.compile_wasm(wasm_bytes)
+  }
+
+  fn wasm_cache_key(&self) -> String {
+    self.inner.wasm_cache_key()
+  }
+
+  async fn cpu_usage(&self) -> u8 {
+    self.inner.cpu_usage().await
+  }
+
+  fn stdout(&self) -> Box<dyn Write + Send> {
+    self.inner.stdout()
+  }
+
+  fn stdin(&self) -> Box<dyn Read + Send> {
+    self.inner.stdin()
+  }
+
+  fn progress_bars(&self) -> Option<&Arc<crate::utils::ProgressBars>> {
+    self.inner.progress_bars()
+  }
+
+  #[cfg(windows)]
+  fn ensure_system_path(&self, directory_path: &str) -> Result<()> {
+    self.inner.ensure_system_path(directory_path)
+  }
+
+  #[cfg(windows)]
+  fn remove_system_path(&self, directory_path: &str) -> Result<()> {
+    self.inner.remove_system_path(directory_path)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::environment::TestEnvironment;
+
+  #[test]
+  fn should_read_files_from_git_tree() {
+    let inner = TestEnvironment::new();
+    inner.set_git_root_dir("/project");
+    inner.add_git_tree_file("main", "a.txt", "a");
+    inner.add_git_tree_file("main", "sub/dir/b.txt", "b");
+    inner.write_file("/project/a.txt", "working tree").unwrap();
+    inner.write_file("/project/c.txt", "c").unwrap();
+    inner.write_file("/other/d.txt", "d").unwrap();
+    let environment = GitTreeEnvironment::new(inner.clone());
+    let guard = environment.read_git_ref("main").unwrap();
+
+    assert_eq!(environment.read_file("/project/a.txt").unwrap(), "a");
+    assert_eq!(environment.read_file("/project/sub/../sub/dir/b.txt").unwrap(), "b");
+    assert!(environment.read_file("/project/c.txt").is_err());
+    assert_eq!(environment.read_file("/other/d.txt").unwrap(), "d");
+    assert!(environment.path_exists("/project/sub/dir"));
+    assert!(environment.path_exists("/project/.git"));
+    assert!(!environment.path_exists("/project/c.txt"));
+    assert_eq!(
+      environment.canonicalize("/project/sub/./dir").unwrap(),
+      CanonicalizedPathBuf::new_for_testing("/project/sub/dir")
+    );
+    assert!(environment.canonicalize("/project/c.txt").is_err());
+
+    let mut entries = environment
+      .dir_info("/project")
+      .unwrap()
+      .into_iter()
+      .map(|entry| match entry {
+        DirEntry::Directory(path) => format!("dir {}", path.display()),
+        DirEntry::File { path, .. } => format!("file {}", path.display()),
+      })
+      .collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, vec!["dir /project/sub", "file /project/a.txt"]);
+    assert!(environment.dir_info("/project/missing").is_err());
+
+    assert_eq!(
+      environment.write_file("/project/a.txt", "new").err().unwrap().to_string(),
+      "Cannot modify /project/a.txt while reading files from git ref main."
+    );
+    environment.write_file("/other/e.txt", "e").unwrap();
+    assert_eq!(inner.read_file("/other/e.txt").unwrap(), "e");
+    assert_eq!(inner.read_file("/project/a.txt").unwrap(), "working tree");
+
+    // goes back to the working tree once the guard is dropped
+    assert!(environment.read_git_ref("main").is_err());
+    drop(guard);
+    assert_eq!(environment.read_file("/project/a.txt").unwrap(), "working tree");
+    assert_eq!(environment.read_file("/project/c.txt").unwrap(), "c");
+  }
+
+  #[test]
+  fn should_error_for_unknown_git_ref() {
+    let environment = GitTreeEnvironment::new(TestEnvironment::new());
+    assert!(environment.read_git_ref("unknown").is_err());
+  }
+}
@@ -2,6 +2,7 @@ mod canonicalized_path_buf;
 #[allow(clippy::module_inception)]
 #[macro_use]
 mod environment;
+mod git_tree_environment;
 mod real_environment;
 #[cfg(test)]
 mod test_environment;
@@ -10,6 +11,7 @@ mod test_environment_builder;
 
 pub use canonicalized_path_buf::*;
 pub use environment::*;
+pub use git_tree_environment::*;
 pub use real_environment::*;
 
 #[cfg(test)]
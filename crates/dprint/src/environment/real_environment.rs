@@ -7,10 +7,18 @@ use parking_lot::Mutex;
 use std::ffi::OsString;
 use std::fs;
 use std::hash::Hash;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
 use std::process::Command;
+use std::process::Stdio;
 use std::sync::Arc;
 use std::time::SystemTime;
 use sysinfo::System;
@@ -21,6 +29,9 @@ use super::CanonicalizedPathBuf;
 use super::DirEntry;
 use super::Environment;
 use super::FilePermissions;
+use super::GitBlobReader;
+use super::GitTree;
+use super::GitTreeFile;
 use super::UrlDownloader;
 use crate::plugins::CompilationResult;
 use crate::utils::log_action_with_progress;
@@ -132,8 +143,9 @@ impl Environment for RealEnvironment {
     }
   }
 
-  fn get_staged_files(&self) -> Result<Vec<PathBuf>> {
+  fn get_staged_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
     let output = Command::new("git")
+      .current_dir(dir_path)
       .arg("diff")
       .arg("--name-only")
       .arg("--relative")
@@ -144,6 +156,41 @@ impl Environment for RealEnvironment {
     Ok(String::from_utf8_lossy(&output.stdout).lines().map(PathBuf::from).collect())
   }
 
+  fn get_git_tree(&self, dir_path: &Path, git_ref: &str) -> Result<GitTree> {
+    if git_ref.starts_with('-') {
+      bail!("Invalid git ref: {}", git_ref);
+    }
+    let root_dir = run_git(dir_path, &["rev-parse", "--show-toplevel"])?;
+    let root_dir = canonicalize_path(String::from_utf8_lossy(&root_dir).trim())?;
+    log_debug!(self, "Listing files of git ref {} in {}", git_ref, root_dir.display());
+    let output = run_git(dir_path, &["ls-tree", "-r", "-z", "--full-tree", git_ref])?;
+    let mut files = Vec::new();
+    for record in output.split(|b| *b == 0).filter(|record| !record.is_empty()) {
+      // <mode> SP <type> SP <object> TAB <file>
+      let record = String::from_utf8_lossy(record);
+      let Some((info, path)) = record.split_once('\t') else {
+        bail!("Unexpected git ls-tree output: {}", record);
+      };
+      let mut parts = info.split(' ');
+      let (Some(mode), Some(kind), Some(object_id)) = (parts.next(), parts.next(), parts.next()) else {
+        bail!("Unexpected git ls-tree output: {}", record);
+      };
+      // skip symlinks and submodules
+      if kind == "blob" && mode != "120000" {
+        files.push(GitTreeFile {
+          path: PathBuf::from(path),
+          object_id: object_id.to_string(),
+        });
+      }
+    }
+    Ok(GitTree { root_dir, files })
+  }
+
+  fn git_blob_reader(&self, dir_path: &Path) -> Result<Box<dyn GitBlobReader>> {
+    log_debug!(self, "Starting git cat-file in {}", dir_path.display());
+    Ok(Box::new(GitCatFileBatch::start(dir_path)?))
+  }
+
   fn write_file_bytes(&self, file_path: impl AsRef<Path>, bytes: &[u8]) -> Result<()> {
     log_debug!(self, "Writing file: {}", file_path.as_ref().display());
     #[allow(clippy::disallowed_methods)]
@@ -475,6 +522,80 @@ fn resolve_max_threads(env_var: Option<String>, available_parallelism: Option<No
   }
 }
 
+fn run_git(dir_path: &Path, args: &[&str]) -> Result<Vec<u8>> {
+  let output = Command::new("git").current_dir(dir_path).args(args).output().context("Failed running git.")?;
+  if !output.status.success() {
+    bail!("Failed running `git {}`: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+  }
+  Ok(output.stdout)
+}
+
+/// Reads blobs with a single `git cat-file --batch` process.
+struct GitCatFileBatch(Mutex<GitCatFileProcess>);
+
+struct GitCatFileProcess {
+  child: Child,
+  stdin: Option<ChildStdin>,
+  stdout: BufReader<ChildStdout>,
+}
+
+impl GitCatFileBatch {
+  fn start(dir_path: &Path) -> Result<Self> {
+    let mut child = Command::new("git")
+      .current_dir(dir_path)
+      .args(["cat-file", "--batch"])
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+      .context("Failed running git.")?;
+    let stdin = child.stdin.take().unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    Ok(Self(Mutex::new(GitCatFileProcess {
+      child,
+      stdin: Some(stdin),
+      stdout,
+    })))
+  }
+}
+
+impl GitBlobReader for GitCatFileBatch {
+  fn read_blob(&self, object_id: &str) -> Result<Vec<u8>> {
+    if object_id.is_empty() || object_id.contains(char::is_whitespace) {
+      bail!("Invalid git object id: {}", object_id);
+    }
+    let mut process = self.0.lock();
+    let process = &mut *process;
+    let stdin = process.stdin.as_mut().unwrap();
+    writeln!(stdin, "{}", object_id)?;
+    stdin.flush()?;
+
+    // <object> SP <type> SP <size> LF, or <object> SP missing LF
+    let mut header = String::new();
+    if process.stdout.read_line(&mut header)? == 0 {
+      bail!("Failed reading git blob {}: git cat-file exited.", object_id);
+    }
+    let mut parts = header.trim_end().split(' ');
+    let size = match (parts.next(), parts.next(), parts.next().and_then(|size| size.parse::<usize>().ok())) {
+      (Some(_), Some("blob"), Some(size)) => size,
+      _ => bail!("Failed reading git blob {}: {}", object_id, header.trim_end()),
+    };
+    // the contents are followed by a LF
+    let mut bytes = vec![0; size + 1];
+    process.stdout.read_exact(&mut bytes)?;
+    bytes.pop();
+    Ok(bytes)
+  }
+}
+
+impl Drop for GitCatFileProcess {
+  fn drop(&mut self) {
+    // closing stdin makes git exit
+    drop(self.stdin.take());
+    let _ = self.child.wait();
+  }
+}
+
 fn canonicalize_path(path: impl AsRef<Path>) -> Result<CanonicalizedPathBuf> {
   // use this to avoid //?//C:/etc... like paths on windows (UNC)
   match dunce::canonicalize(path.as_ref()) {
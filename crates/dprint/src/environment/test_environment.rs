@@ -21,6 +21,9 @@ use super::CanonicalizedPathBuf;
 use super::DirEntry;
 use super::Environment;
 use super::FilePermissions;
+use super::GitBlobReader;
+use super::GitTree;
+use super::GitTreeFile;
 use super::UrlDownloader;
 use crate::plugins::CompilationResult;
 use crate::utils::get_bytes_hash;
@@ -59,8 +62,18 @@ impl Drop for TestPipeWriter {
 }
 
 impl Read for TestPipeReader {
-  fn read(&mut self, _: &mut [u8]) -> Result<usize, std::io::Error> {
-    panic!("Not implemented");
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut buffer_data = self.0.buffer_data.lock();
+
+    while buffer_data.data.len() == buffer_data.read_pos && !buffer_data.closed && !buf.is_empty() {
+      self.0.cond_var.wait(&mut buffer_data);
+    }
+
+    let read_pos = buffer_data.read_pos;
+    let count = std::cmp::min(buf.len(), buffer_data.data.len() - read_pos);
+    buf[..count].copy_from_slice(&buffer_data.data[read_pos..read_pos + count]);
+    buffer_data.read_pos += count;
+    Ok(count)
   }
 
   fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), std::io::Error> {
@@ -73,6 +86,9 @@ impl Read for TestPipeReader {
     if buffer_data.data.len() == buffer_data.read_pos && buffer_data.closed {
       return Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Broken pipe."));
     }
+    if buffer_data.data.len() < buffer_data.read_pos + buf.len() {
+      return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Unexpected end of pipe."));
+    }
 
     buf.copy_from_slice(&buffer_data.data[buffer_data.read_pos..buffer_data.read_pos + buf.len()]);
     buffer_data.read_pos += buf.len();
@@ -96,6 +112,14 @@ impl Write for TestPipeWriter {
   }
 }
 
+struct TestGitBlobReader(Arc<Mutex<HashMap<String, Vec<u8>>>>);
+
+impl GitBlobReader for TestGitBlobReader {
+  fn read_blob(&self, object_id: &str) -> Result<Vec<u8>> {
+    Ok(self.0.lock().get(object_id).unwrap().clone())
+  }
+}
+
 #[derive(Clone)]
 pub struct TestEnvironment {
   log_level: Arc<Mutex<LogLevel>>,
@@ -103,6 +127,9 @@ pub struct TestEnvironment {
   env_vars: Arc<Mutex<HashMap<String, OsString>>>,
   files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
   staged_files: Arc<Mutex<Vec<PathBuf>>>,
+  git_root_dir: Arc<Mutex<PathBuf>>,
+  git_trees: Arc<Mutex<HashMap<String, Vec<PathBuf>>>>,
+  git_blobs: Arc<Mutex<HashMap<String, Vec<u8>>>>,
   file_permissions: Arc<Mutex<HashMap<PathBuf, FilePermissions>>>,
   stdout_messages: Arc<Mutex<Vec<String>>>,
   stderr_messages: Arc<Mutex<Vec<String>>>,
@@ -130,6 +157,9 @@ impl TestEnvironment {
       env_vars: Default::default(),
       files: Default::default(),
       staged_files: Default::default(),
+      git_root_dir: Arc::new(Mutex::new(PathBuf::from("/"))),
+      git_trees: Default::default(),
+      git_blobs: Default::default(),
       file_permissions: Default::default(),
       stdout_messages: Default::default(),
       stderr_messages: Default::default(),
@@ -247,6 +277,18 @@ impl TestEnvironment {
   pub fn set_staged_file(&self, file: impl AsRef<Path>) {
     self.staged_files.lock().push(file.as_ref().to_path_buf())
   }
+  pub fn set_git_root_dir(&self, dir_path: impl AsRef<Path>) {
+    *self.git_root_dir.lock() = dir_path.as_ref().to_path_buf();
+  }
+
+  /// Adds a file with a path relative to the git root directory to the tree of a git ref.
+  pub fn add_git_tree_file(&self, git_ref: &str, file_path: impl AsRef<Path>, text: &str) {
+    let file_path = file_path.as_ref().to_path_buf();
+    let object_id = format!("{}:{}", git_ref, file_path.display());
+    self.git_blobs.lock().insert(object_id, text.as_bytes().to_vec());
+    self.git_trees.lock().entry(git_ref.to_string()).or_default().push(file_path);
+  }
+
   pub fn set_dir_info_error(&self, err: std::io::Error) {
     *self.dir_info_error.lock() = Some(err);
   }
@@ -318,10 +360,35 @@ impl Environment for TestEnvironment {
     self.env_vars.lock().get(name).cloned()
   }
 
-  fn get_staged_files(&self) -> Result<Vec<PathBuf>> {
+  fn get_staged_files(&self, _dir_path: &Path) -> Result<Vec<PathBuf>> {
     Ok(self.staged_files.lock().clone())
   }
 
+  fn get_git_tree(&self, _dir_path: &Path, git_ref: &str) -> Result<GitTree> {
+    let git_trees = self.git_trees.lock();
+    let Some(files) = git_trees.get(git_ref) else {
+      bail!(
+        "Failed running `git ls-tree -r -z --full-tree {}`: fatal: Not a valid object name {}",
+        git_ref,
+        git_ref
+      );
+    };
+    Ok(GitTree {
+      root_dir: self.canonicalize(&*self.git_root_dir.lock())?,
+      files: files
+        .iter()
+        .map(|path| GitTreeFile {
+          path: path.clone(),
+          object_id: format!("{}:{}", git_ref, path.display()),
+        })
+        .collect(),
+    })
+  }
+
+  fn git_blob_reader(&self, _dir_path: &Path) -> Result<Box<dyn GitBlobReader>> {
+    Ok(Box::new(TestGitBlobReader(self.git_blobs.clone())))
+  }
+
   fn read_file(&self, file_path: impl AsRef<Path>) -> Result<String> {
     let file_bytes = self.read_file_bytes(file_path)?;
     Ok(String::from_utf8(file_bytes.to_vec()).unwrap())
@@ -480,7 +547,8 @@ impl Environment for TestEnvironment {
 
   fn log_machine_readable(&self, text: &[u8]) {
     assert!(*self.is_stdout_machine_readable.lock());
-    self.stdout_messages.lock().push(String::from_utf8(text.to_vec()).unwrap());
+    // lossy because binary output (ex. the length prefixes of `fmt --stdin-files`) might not be valid UTF-8
+    self.stdout_messages.lock().push(String::from_utf8_lossy(text).into_owned());
   }
 
   fn log_action_with_progress<TResult: Send + Sync, TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + Send + Sync>(
@@ -3,8 +3,10 @@ use anyhow::Result;
 use dprint_core::async_runtime::future;
 use dprint_core::configuration::ConfigKeyMap;
 use dprint_core::plugins::CriticalFormatError;
+use dprint_core::plugins::FormatDiagnostic;
 use dprint_core::plugins::NullCancellationToken;
 use std::borrow::Cow;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -32,11 +34,16 @@ struct TaskWork {
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct EnsureStableFormat(pub bool);
 
+/// Whether to output the warnings of plugins that support checking files.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct RunPluginChecks(pub bool);
+
 pub async fn run_parallelized<F, TEnvironment: Environment>(
   scope_and_paths: PluginsScopeAndPaths<TEnvironment>,
   environment: &TEnvironment,
   incremental_file: Option<Arc<IncrementalFile<TEnvironment>>>,
   ensure_stable_format: EnsureStableFormat,
+  run_plugin_checks: RunPluginChecks,
   f: F,
 ) -> Result<()>
 where
@@ -144,7 +151,17 @@ where
                 }
               }
             });
-            let result = run_for_file_path(environment, incremental_file, scope, plugins, file_path.clone(), ensure_stable_format, f).await;
+            let result = run_for_file_path(
+              environment,
+              incremental_file,
+              scope,
+              plugins,
+              file_path.clone(),
+              ensure_stable_format,
+              run_plugin_checks,
+              f,
+            )
+            .await;
             long_format_token.cancel();
             if let Err(err) = result {
               if let Some(err) = err.downcast_ref::<CriticalFormatError>() {
@@ -178,6 +195,7 @@ where
   };
 
   #[inline]
+  #[allow(clippy::too_many_arguments)]
   async fn run_for_file_path<F, TEnvironment: Environment>(
     environment: TEnvironment,
     incremental_file: Option<Arc<IncrementalFile<TEnvironment>>>,
@@ -185,6 +203,7 @@ where
     plugins: Rc<Vec<InitializedPluginWithConfig>>,
     file_path: PathBuf,
     ensure_stable_format: EnsureStableFormat,
+    run_plugin_checks: RunPluginChecks,
     f: F,
   ) -> Result<()>
   where
@@ -193,25 +212,29 @@ where
     // it's a big perf improvement to do this work on a blocking thread
     let result = dprint_core::async_runtime::spawn_blocking(move || {
       let file_text = environment.read_file_bytes(&file_path)?;
-
-      if let Some(incremental_file) = &incremental_file {
-        if incremental_file.is_file_known_formatted(&file_text) {
-          log_debug!(environment, "No change: {}", file_path.display());
-          return Ok::<_, anyhow::Error>(None);
-        }
-      }
-      Ok(Some((file_path, file_text, environment)))
+      let is_known_formatted = incremental_file.as_ref().map(|f| f.is_file_known_formatted(&file_text)).unwrap_or(false);
+      Ok::<_, anyhow::Error>((file_path, file_text, environment, is_known_formatted))
     })
     .await
     .unwrap()?;
 
-    let Some((file_path, file_text, environment)) = result else {
+    let (file_path, file_text, environment, is_known_formatted) = result;
+    if is_known_formatted {
+      log_debug!(environment, "No change: {}", file_path.display());
+      // the incremental cache only knows about formatting, so still check the file
+      if run_plugin_checks.0 {
+        output_plugin_checks(&environment, &plugins, &file_path, &file_text).await;
+      }
       return Ok(());
-    };
+    }
 
     let (start_instant, formatted_text) =
       run_single_pass_for_file_path(environment.clone(), scope.clone(), plugins.clone(), file_path.clone(), &file_text).await?;
 
+    if run_plugin_checks.0 {
+      output_plugin_checks(&environment, &plugins, &file_path, &file_text).await;
+    }
+
     let formatted_text = if ensure_stable_format.0 && formatted_text != file_text {
       get_stabilized_format_text(environment.clone(), scope, plugins, file_path.clone(), formatted_text).await?
     } else {
@@ -223,6 +246,44 @@ where
     Ok(())
   }
 
+  /// Outputs the warnings of any plugins that check the file. These
+  /// are only informational and don't cause the command to fail.
+  async fn output_plugin_checks<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &[InitializedPluginWithConfig],
+    file_path: &Path,
+    file_text: &[u8],
+  ) {
+    for plugin in plugins {
+      match plugin
+        .check_text(file_path.to_path_buf(), file_text.to_vec(), Arc::new(NullCancellationToken))
+        .await
+      {
+        Ok(diagnostics) => {
+          if diagnostics.is_empty() {
+            continue;
+          }
+          let file_text = String::from_utf8_lossy(file_text);
+          for diagnostic in diagnostics {
+            log_warn!(
+              environment,
+              "Warning checking {}. Message: {}",
+              file_path.display(),
+              diagnostic.render(&file_text)
+            );
+          }
+        }
+        Err(err) => log_warn!(
+          environment,
+          "Failed checking {} with {}. Message: {:#}",
+          file_path.display(),
+          plugin.info().name,
+          err
+        ),
+      }
+    }
+  }
+
   async fn get_stabilized_format_text<TEnvironment: Environment>(
     environment: TEnvironment,
     scope: Rc<PluginsScope<TEnvironment>>,
@@ -300,7 +361,7 @@ where
           String::new()
         },
       );
-      if let Some(text) = format_text_result? {
+      if let Some(text) = format_text_result.map_err(|err| render_format_diagnostic(err, &file_text))? {
         file_text = Cow::Owned(text)
       }
     }
@@ -326,6 +387,35 @@ where
   }
 }
 
+/// A format diagnostic along with the text of the file it was reported
+/// for, which displays with a snippet of the file text.
+#[derive(Debug)]
+pub struct FileFormatDiagnostic {
+  pub diagnostic: FormatDiagnostic,
+  file_text: String,
+}
+
+impl std::fmt::Display for FileFormatDiagnostic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.diagnostic.render(&self.file_text))
+  }
+}
+
+impl std::error::Error for FileFormatDiagnostic {}
+
+/// Attaches the file text to the error when the
+/// plugin provided a format diagnostic.
+fn render_format_diagnostic(err: anyhow::Error, file_text: &[u8]) -> anyhow::Error {
+  match err.downcast::<FormatDiagnostic>() {
+    Ok(diagnostic) => FileFormatDiagnostic {
+      diagnostic,
+      file_text: String::from_utf8_lossy(file_text).into_owned(),
+    }
+    .into(),
+    Err(err) => err,
+  }
+}
+
 fn target_cpu_decrease_bound(number_threads: usize) -> u8 {
   if number_threads < 3 {
     100 // never decrease
@@ -477,11 +567,25 @@ fn add_permits(semaphores: &[Rc<Semaphore>], amount: usize) {
 
 #[cfg(test)]
 mod test {
+  use anyhow::anyhow;
   use std::rc::Rc;
 
   use super::*;
   use crate::utils::Semaphore;
 
+  #[test]
+  fn should_keep_format_diagnostic_when_rendering() {
+    let mut diagnostic = FormatDiagnostic::new("Unexpected token.");
+    diagnostic.range = Some(5..8);
+    let err = render_format_diagnostic(diagnostic.clone().into(), b"let\nabc def;");
+    assert_eq!(err.to_string(), "Line 2, column 2: Unexpected token.\n\n  abc def;\n   ~~~");
+    assert_eq!(err.downcast_ref::<FileFormatDiagnostic>().unwrap().diagnostic, diagnostic);
+
+    let err = render_format_diagnostic(anyhow!("Other."), b"text");
+    assert_eq!(err.to_string(), "Other.");
+    assert!(err.downcast_ref::<FileFormatDiagnostic>().is_none());
+  }
+
   #[test]
   fn target_cpu_calc() {
     run_test(0, 0..100);
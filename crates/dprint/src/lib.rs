@@ -0,0 +1,132 @@
+//! The dprint code formatter.
+//!
+//! Besides the `dprint` binary, this crate can be used as a library to format
+//! in-process with the same configuration resolution and plugins as the CLI.
+//! See [`Formatter`].
+//!
+//! ```no_run
+//! use dprint::ConfigSource;
+//! use dprint::FormatOutput;
+//! use dprint::Formatter;
+//! use dprint::FormatterOptions;
+//!
+//! let formatter = Formatter::new(FormatterOptions::new(ConfigSource::Path("dprint.json".into())))?;
+//! if let FormatOutput::Changed(text) = formatter.format_text("generated.ts", b"const a=1")? {
+//!   std::fs::write("generated.ts", text)?;
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+#![deny(clippy::print_stderr)]
+#![deny(clippy::print_stdout)]
+#![deny(clippy::unused_async)]
+
+#[macro_use]
+mod environment;
+
+use anyhow::Result;
+use dprint_core::plugins::process::setup_exit_process_panic_hook;
+use environment::RealEnvironment;
+use environment::RealEnvironmentOptions;
+use run_cli::AppError;
+use std::rc::Rc;
+use utils::LogLevel;
+use utils::RealStdInReader;
+
+mod api;
+mod arg_parser;
+mod commands;
+mod configuration;
+mod format;
+mod incremental;
+mod native_plugins;
+mod paths;
+mod patterns;
+mod plugins;
+mod resolution;
+mod run_cli;
+mod utils;
+
+#[cfg(any(test, feature = "spec-harness"))]
+mod plugin_spec_harness;
+#[cfg(test)]
+mod test_helpers;
+
+pub use api::*;
+#[cfg(feature = "spec-harness")]
+pub use plugin_spec_harness::PluginSpecHarness;
+#[cfg(feature = "spec-harness")]
+pub use plugin_spec_harness::SpecHarnessPlugin;
+pub use plugins::NativePlugin;
+
+/// Runs the CLI with the process arguments. This is what the `dprint` binary calls.
+#[doc(hidden)]
+pub fn run_main() {
+  run_main_with_native_plugins(Vec::new())
+}
+
+/// Runs the CLI with the process arguments and plugins compiled into the binary.
+///
+/// Use this in the `main` function of a custom build of the CLI. The native
+/// plugins are always used, so they don't need to be in the configuration file.
+///
+/// ```ignore
+/// // in the main function
+/// dprint::run_main_with_native_plugins(vec![
+///   dprint::NativePlugin::from_async(|| my_plugin::MyPluginHandler::default()),
+/// ]);
+/// ```
+pub fn run_main_with_native_plugins(native_plugins: Vec<NativePlugin>) {
+  native_plugins::set_native_plugins(native_plugins);
+  setup_exit_process_panic_hook();
+  let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+  rt.block_on(async move {
+    match run().await {
+      Ok(_) => {}
+      Err((err, log_level)) => {
+        if log_level != LogLevel::Silent {
+          let result = format!("{:#}", err.inner);
+          #[allow(clippy::print_stderr)]
+          if !result.is_empty() {
+            eprintln!("{}", result);
+          }
+        }
+        std::process::exit(err.exit_code);
+      }
+    }
+  });
+}
+
+async fn run() -> Result<(), (AppError, LogLevel)> {
+  let raw_args: Vec<String> = std::env::args().collect();
+  let args = arg_parser::parse_args(raw_args.clone(), RealStdInReader).map_err(|err| (err.into(), LogLevel::Info))?;
+
+  let environment = RealEnvironment::new(RealEnvironmentOptions {
+    log_level: args.log_level,
+    is_stdout_machine_readable: args.is_stdout_machine_readable(),
+  })
+  .map_err(|err| (err.into(), args.log_level))?;
+  match commands::run_in_daemon(&raw_args, &args, &environment) {
+    Ok(None) => {}
+    Ok(Some(0)) => return Ok(()),
+    // the daemon already sent the error output
+    Ok(Some(exit_code)) => {
+      return Err((
+        AppError {
+          inner: anyhow::anyhow!(""),
+          exit_code,
+        },
+        args.log_level,
+      ))
+    }
+    Err(err) => return Err((err.into(), args.log_level)),
+  }
+
+  let plugin_cache = plugins::PluginCache::new(environment.clone());
+  let plugin_resolver =
+    Rc::new(plugins::PluginResolver::new(environment.clone(), plugin_cache).with_native_plugins(native_plugins::registered_native_plugins()));
+
+  let result = run_cli::run_cli(&args, &environment, &plugin_resolver).await;
+  plugin_resolver.clear_and_shutdown_initialized().await;
+  result.map_err(|err| (err.into(), args.log_level))
+}
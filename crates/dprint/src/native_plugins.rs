@@ -0,0 +1,23 @@
+use std::sync::OnceLock;
+
+use crate::plugins::NativePlugin;
+use crate::plugins::Plugin;
+
+static NATIVE_PLUGINS: OnceLock<Vec<NativePlugin>> = OnceLock::new();
+
+/// Sets the plugins that are compiled into this binary. Only the first call has an effect.
+pub fn set_native_plugins(native_plugins: Vec<NativePlugin>) {
+  let _ = NATIVE_PLUGINS.set(native_plugins);
+}
+
+/// Plugins that are compiled into this binary, which are provided
+/// to `run_main_with_native_plugins` by custom builds of the CLI.
+///
+/// These are always used, so they don't need to be specified in the
+/// configuration file's "plugins" array.
+pub fn registered_native_plugins() -> Vec<Box<dyn Plugin>> {
+  match NATIVE_PLUGINS.get() {
+    Some(native_plugins) => native_plugins.iter().map(|plugin| Box::new(plugin.clone()) as Box<dyn Plugin>).collect(),
+    None => Vec::new(),
+  }
+}
@@ -0,0 +1,412 @@
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::Context;
+use anyhow::Result;
+use dprint_core::configuration::ConfigKeyMap;
+use dprint_core::plugins::FormatRange;
+use dprint_core::plugins::HostFormatRequest;
+use dprint_core::plugins::NullCancellationToken;
+use dprint_development::try_parse_specs;
+use dprint_development::ParseSpecOptions;
+use dprint_development::Spec;
+use dprint_development::SpecConfigMap;
+
+use crate::api::resolve_config_source;
+use crate::api::ConfigSource;
+use crate::environment::CanonicalizedPathBuf;
+use crate::environment::Environment;
+#[cfg(feature = "spec-harness")]
+use crate::environment::RealEnvironment;
+#[cfg(feature = "spec-harness")]
+use crate::environment::RealEnvironmentOptions;
+use crate::plugins::PluginCache;
+use crate::plugins::PluginResolver;
+use crate::resolution::resolve_plugins_scope;
+use crate::resolution::PluginsScope;
+use crate::utils::get_sha256_checksum;
+#[cfg(feature = "spec-harness")]
+use crate::utils::LogLevel;
+
+/// A built plugin to run the specs against.
+pub enum SpecHarnessPlugin<'a> {
+  /// A built Wasm plugin file.
+  Wasm(&'a Path),
+  /// A built process plugin executable.
+  Process { name: &'a str, executable_path: &'a Path },
+}
+
+/// Runs spec files against built plugins end-to-end.
+///
+/// Unlike `dprint_development::run_specs`, which calls a Rust closure, this resolves
+/// and initializes the plugins with the same `PluginResolver` and `PluginsScope` code
+/// as the CLI, so the Wasm and process plugin communication and host formatting between
+/// plugins are also tested.
+///
+/// ```no_run
+/// use dprint::PluginSpecHarness;
+/// use dprint::SpecHarnessPlugin;
+/// use dprint_development::ParseSpecOptions;
+/// use std::path::Path;
+///
+/// let harness = PluginSpecHarness::new(&[SpecHarnessPlugin::Wasm(Path::new(
+///   "target/wasm32-unknown-unknown/release/my_plugin.wasm",
+/// ))])?;
+/// harness.run_specs(Path::new("tests/specs"), &ParseSpecOptions { default_file_name: "file.ts" });
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[cfg(feature = "spec-harness")]
+pub struct PluginSpecHarness {
+  harness: SpecHarness<RealEnvironment>,
+  // holds the plugin files
+  _temp_dir: tempfile::TempDir,
+}
+
+#[cfg(feature = "spec-harness")]
+impl PluginSpecHarness {
+  /// Sets up the plugins in a temporary directory. They're resolved again
+  /// for every spec file, using the dprint cache like the CLI.
+  pub fn new(plugins: &[SpecHarnessPlugin]) -> Result<Self> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let environment = RealEnvironment::new(RealEnvironmentOptions {
+      log_level: LogLevel::Silent,
+      is_stdout_machine_readable: false,
+    })?;
+    let dir_path = environment.canonicalize(temp_dir.path())?;
+    Ok(Self {
+      harness: SpecHarness::new(environment, dir_path, plugins)?,
+      _temp_dir: temp_dir,
+    })
+  }
+
+  /// Runs all the spec files in the directory, panicking with the failures.
+  pub fn run_specs(&self, dir_path: &Path, parse_spec_options: &ParseSpecOptions) {
+    self.harness.run_specs(dir_path, parse_spec_options)
+  }
+
+  /// Runs the specs in the provided spec file text, returning the failures.
+  pub fn run_spec_file_text(&self, file_text: &str, parse_spec_options: &ParseSpecOptions) -> Vec<String> {
+    self.harness.run_spec_file_text(file_text, parse_spec_options)
+  }
+}
+
+struct SpecHarness<TEnvironment: Environment> {
+  environment: TEnvironment,
+  dir_path: CanonicalizedPathBuf,
+  plugin_references: Vec<String>,
+}
+
+impl<TEnvironment: Environment> SpecHarness<TEnvironment> {
+  /// Writes the plugins to the directory so they're resolved like local plugins.
+  #[allow(clippy::disallowed_methods)] // reads the built plugins
+  fn new(environment: TEnvironment, dir_path: CanonicalizedPathBuf, plugins: &[SpecHarnessPlugin]) -> Result<Self> {
+    let mut plugin_references = Vec::with_capacity(plugins.len());
+    for (i, plugin) in plugins.iter().enumerate() {
+      match plugin {
+        SpecHarnessPlugin::Wasm(file_path) => {
+          let wasm_bytes = std::fs::read(file_path).with_context(|| format!("Could not read Wasm plugin {}", file_path.display()))?;
+          let plugin_path = dir_path.join_panic_relative(format!("plugin-{}.wasm", i));
+          environment.write_file_bytes(&plugin_path, &wasm_bytes)?;
+          plugin_references.push(plugin_path.to_string_lossy().to_string());
+        }
+        SpecHarnessPlugin::Process { name, executable_path } => {
+          let executable_bytes =
+            std::fs::read(executable_path).with_context(|| format!("Could not read process plugin executable {}", executable_path.display()))?;
+          let zip_bytes = create_process_plugin_zip(name, &executable_bytes)?;
+          let zip_file_name = format!("{}.zip", name);
+          environment.write_file_bytes(dir_path.join_panic_relative(&zip_file_name), &zip_bytes)?;
+          let plugin_file_bytes = create_process_plugin_file(name, &zip_file_name, &get_sha256_checksum(&zip_bytes));
+          let plugin_file_path = dir_path.join_panic_relative(format!("{}.json", name));
+          environment.write_file_bytes(&plugin_file_path, &plugin_file_bytes)?;
+          plugin_references.push(format!("{}@{}", plugin_file_path.display(), get_sha256_checksum(&plugin_file_bytes)));
+        }
+      }
+    }
+    Ok(Self {
+      environment,
+      dir_path,
+      plugin_references,
+    })
+  }
+
+  #[allow(clippy::disallowed_methods)] // spec files are on the real file system
+  fn run_specs(&self, dir_path: &Path, parse_spec_options: &ParseSpecOptions) {
+    let mut failures = Vec::new();
+    for file_path in get_spec_file_paths(dir_path) {
+      let file_text = std::fs::read_to_string(&file_path).unwrap();
+      failures.extend(
+        self
+          .run_spec_file_text(&file_text, parse_spec_options)
+          .into_iter()
+          .map(|failure| format!("{}: {}", file_path.display(), failure)),
+      );
+    }
+    if !failures.is_empty() {
+      panic!("{} spec failure(s):\n\n{}", failures.len(), failures.join("\n\n"));
+    }
+  }
+
+  fn run_spec_file_text(&self, file_text: &str, parse_spec_options: &ParseSpecOptions) -> Vec<String> {
+    let specs = match try_parse_specs(file_text, parse_spec_options) {
+      Ok(specs) => specs,
+      Err(err) => return vec![format!("Error parsing spec file. {}", err)],
+    };
+    let specs = if specs.iter().any(|s| s.is_only) {
+      specs.into_iter().filter(|s| s.is_only).collect()
+    } else {
+      specs
+    };
+    let config = ConfigSource::Json {
+      dir_path: self.dir_path.clone().into_path_buf(),
+      config: serde_json::json!({ "plugins": self.plugin_references }),
+    };
+    let environment = self.environment.clone();
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    runtime.block_on(async move {
+      let plugin_cache = PluginCache::new(environment.clone());
+      let plugin_resolver = Rc::new(PluginResolver::new(environment.clone(), plugin_cache));
+      let failures = match resolve_scope(&environment, &config, &plugin_resolver).await {
+        Ok(scope) => {
+          let scope = Rc::new(scope);
+          let mut failures = Vec::new();
+          for spec in specs.iter().filter(|spec| !spec.skip) {
+            if let Some(failure) = run_spec(&scope, spec).await {
+              failures.push(format!("Failed: {}\n{}", spec.message, failure));
+            }
+          }
+          failures
+        }
+        Err(err) => vec![format!("Error resolving plugins. {:#}", err)],
+      };
+      plugin_resolver.clear_and_shutdown_initialized().await;
+      failures
+    })
+  }
+}
+
+async fn resolve_scope<TEnvironment: Environment>(
+  environment: &TEnvironment,
+  config: &ConfigSource,
+  plugin_resolver: &Rc<PluginResolver<TEnvironment>>,
+) -> Result<PluginsScope<TEnvironment>> {
+  let config = resolve_config_source(config, environment).await?;
+  let scope = resolve_plugins_scope(Rc::new(config), environment, plugin_resolver).await?;
+  scope.ensure_plugins_found()?;
+  Ok(scope)
+}
+
+/// Creates a process plugin zip file containing the executable for the provided plugin name.
+pub(crate) fn create_process_plugin_zip(plugin_name: &str, executable_bytes: &[u8]) -> Result<Vec<u8>> {
+  let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+  let options = zip::write::SimpleFileOptions::default()
+    .compression_method(zip::CompressionMethod::Stored)
+    .unix_permissions(0o755);
+  let file_name = if cfg!(target_os = "windows") {
+    format!("{}.exe", plugin_name)
+  } else {
+    plugin_name.to_string()
+  };
+  zip.start_file(file_name, options)?;
+  zip.write_all(executable_bytes)?;
+  Ok(zip.finish()?.into_inner())
+}
+
+/// Creates a process plugin file that uses the same zip file on every platform.
+fn create_process_plugin_file(plugin_name: &str, zip_reference: &str, zip_checksum: &str) -> Vec<u8> {
+  let path = serde_json::json!({
+    "reference": zip_reference,
+    "checksum": zip_checksum,
+  });
+  let mut plugin_file = serde_json::json!({
+    "schemaVersion": 2,
+    "kind": "process",
+    "name": plugin_name,
+    "version": "0.1.0",
+  });
+  for platform in [
+    "linux-x86_64",
+    "linux-x86_64-musl",
+    "linux-aarch64",
+    "linux-aarch64-musl",
+    "linux-riscv64",
+    "linux-riscv64-musl",
+    "darwin-x86_64",
+    "darwin-aarch64",
+    "windows-x86_64",
+    "windows-aarch64",
+  ] {
+    plugin_file[platform] = path.clone();
+  }
+  serde_json::to_vec_pretty(&plugin_file).unwrap()
+}
+
+async fn run_spec<TEnvironment: Environment>(scope: &Rc<PluginsScope<TEnvironment>>, spec: &Spec) -> Option<String> {
+  if let Some(expected_error) = &spec.expected_error {
+    return match format_text(scope, &spec.file_name, &spec.file_text, None, &spec.config).await {
+      Ok(result) => Some(format!("Expected an error containing {:?}, but got: {:?}", expected_error, result)),
+      Err(err) if !format!("{:#}", err).contains(expected_error) => Some(format!("Expected an error containing {:?}, but got: {:#}", expected_error, err)),
+      Err(_) => None,
+    };
+  }
+
+  let files = [(spec.file_name.as_str(), spec.file_text.as_str(), spec.expected_text.as_str())]
+    .into_iter()
+    .chain(
+      spec
+        .additional_files
+        .iter()
+        .map(|f| (f.file_name.as_str(), f.file_text.as_str(), f.expected_text.as_str())),
+    );
+  for (file_name, file_text, expected_text) in files {
+    let result = match format_text(scope, file_name, file_text, None, &spec.config).await {
+      Ok(result) => result,
+      Err(err) => return Some(format!("Error formatting {}: {:#}", file_name, err)),
+    };
+    if result != expected_text {
+      return Some(format!("{}\nExpected: {:?}\nActual:   {:?}", file_name, expected_text, result));
+    }
+    if !spec.skip_format_twice {
+      match format_text(scope, file_name, &result, None, &spec.config).await {
+        Ok(twice_result) if twice_result != result => {
+          return Some(format!(
+            "{} (formatting twice)\nExpected: {:?}\nActual:   {:?}",
+            file_name, result, twice_result
+          ));
+        }
+        Ok(_) => {}
+        Err(err) => return Some(format!("Error formatting {} twice: {:#}", file_name, err)),
+      }
+    }
+  }
+
+  if let Some(expected_range_format) = &spec.expected_range_format {
+    match format_text(scope, &spec.file_name, &spec.file_text, Some(expected_range_format.range.clone()), &spec.config).await {
+      Ok(result) if result != expected_range_format.expected_text => {
+        return Some(format!(
+          "{} (range {:?})\nExpected: {:?}\nActual:   {:?}",
+          spec.file_name, expected_range_format.range, expected_range_format.expected_text, result
+        ));
+      }
+      Ok(_) => {}
+      Err(err) => return Some(format!("Error range formatting {}: {:#}", spec.file_name, err)),
+    }
+  }
+
+  None
+}
+
+async fn format_text<TEnvironment: Environment>(
+  scope: &Rc<PluginsScope<TEnvironment>>,
+  file_name: &str,
+  file_text: &str,
+  range: FormatRange,
+  config: &SpecConfigMap,
+) -> Result<String> {
+  let override_config: ConfigKeyMap = serde_json::from_value(serde_json::Value::Object(config.clone()))?;
+  let result = scope
+    .format(HostFormatRequest {
+      file_path: PathBuf::from("/").join(file_name),
+      file_bytes: file_text.as_bytes().to_vec(),
+      range,
+      override_config,
+      hints: Default::default(),
+      token: Arc::new(NullCancellationToken),
+    })
+    .await?;
+  Ok(match result {
+    Some(bytes) => String::from_utf8(bytes)?,
+    None => file_text.to_string(),
+  })
+}
+
+#[allow(clippy::disallowed_methods)]
+fn get_spec_file_paths(dir_path: &Path) -> Vec<PathBuf> {
+  let mut file_paths = Vec::new();
+  let mut entries = std::fs::read_dir(dir_path)
+    .unwrap_or_else(|err| panic!("Could not read spec directory {}: {:#}", dir_path.display(), err))
+    .map(|entry| entry.unwrap().path())
+    .collect::<Vec<_>>();
+  entries.sort();
+  for path in entries {
+    if path.is_dir() {
+      file_paths.extend(get_spec_file_paths(&path));
+    } else {
+      file_paths.push(path);
+    }
+  }
+  file_paths
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::environment::TestEnvironment;
+  use crate::environment::TestEnvironmentBuilder;
+  use crate::test_helpers::TEST_PROCESS_PLUGIN_PATH;
+
+  fn specs_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/plugin_specs")
+  }
+
+  fn wasm_plugin_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../test-plugin/test_plugin.wasm")
+  }
+
+  fn create_harness(plugins: &[SpecHarnessPlugin]) -> SpecHarness<TestEnvironment> {
+    let environment = TestEnvironmentBuilder::new().build();
+    let dir_path = environment.canonicalize("/plugins").unwrap();
+    SpecHarness::new(environment, dir_path, plugins).unwrap()
+  }
+
+  #[test]
+  fn runs_specs_against_wasm_and_process_plugins() {
+    let wasm_plugin_path = wasm_plugin_path();
+    let harness = create_harness(&[
+      SpecHarnessPlugin::Wasm(&wasm_plugin_path),
+      SpecHarnessPlugin::Process {
+        name: "test-process-plugin",
+        executable_path: &TEST_PROCESS_PLUGIN_PATH,
+      },
+    ]);
+    harness.run_specs(&specs_dir(), &ParseSpecOptions { default_file_name: "file.txt" });
+    // ignore progress messages like compiling the Wasm plugin
+    harness.environment.clear_logs();
+  }
+
+  #[test]
+  fn reports_failures() {
+    let wasm_plugin_path = wasm_plugin_path();
+    let harness = create_harness(&[SpecHarnessPlugin::Wasm(&wasm_plugin_path)]);
+    let failures = harness.run_spec_file_text(&["== message ==", "text"].join("\n"), &ParseSpecOptions { default_file_name: "file.txt" });
+    assert_eq!(
+      failures,
+      vec!["Error parsing spec file. line 1: Expected an [expect] or [expect-error] section.".to_string()]
+    );
+
+    let failures = harness.run_spec_file_text(
+      &[
+        "== wrong output ==",
+        "text",
+        "[expect]",
+        "text",
+        "== wrong error ==",
+        "should_error",
+        "[expect-error]",
+        "Other error",
+      ]
+      .join("\n"),
+      &ParseSpecOptions { default_file_name: "file.txt" },
+    );
+    assert_eq!(
+      failures,
+      vec![
+        "Failed: wrong output\nfile.txt\nExpected: \"text\"\nActual:   \"text_formatted\"".to_string(),
+        "Failed: wrong error\nExpected an error containing \"Other error\", but got: Did error.".to_string(),
+      ]
+    );
+    harness.environment.clear_logs();
+  }
+}
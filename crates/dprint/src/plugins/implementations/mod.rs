@@ -1,7 +1,9 @@
+mod native;
 mod process;
 mod public;
 mod wasm;
 
+pub use native::NativePlugin;
 pub use public::*;
 pub use wasm::WASMER_COMPILER_VERSION;
 
@@ -72,6 +74,7 @@ mod test {
             file_bytes: "plugin: plugin: wait_cancellation".to_string().into_bytes(),
             range: None,
             override_config: Default::default(),
+            hints: Default::default(),
             token: token.clone(),
           })
           .await
@@ -123,6 +126,7 @@ mod test {
               file_bytes: "plugin: plugin: wait_cancellation".to_string().into_bytes(),
               range: None,
               override_config: Default::default(),
+              hints: Default::default(),
               token,
             })
             .await
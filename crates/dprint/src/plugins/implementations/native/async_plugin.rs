@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::Result;
+use dprint_core::async_runtime::async_trait;
+use dprint_core::configuration::ConfigurationDiagnostic;
+use dprint_core::configuration::WhitespaceOptions;
+use dprint_core::plugins::AsyncPluginHandler;
+use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckRequest;
+use dprint_core::plugins::CheckResult;
+use dprint_core::plugins::ConfigChange;
+use dprint_core::plugins::FileMatchingInfo;
+use dprint_core::plugins::FormatConfigId;
+use dprint_core::plugins::FormatRequest;
+use dprint_core::plugins::FormatResult;
+
+use super::get_plugin_config_with_override;
+use super::ResolvedConfig;
+use crate::plugins::FormatConfig;
+use crate::plugins::InitializedPlugin;
+use crate::plugins::InitializedPluginCheckRequest;
+use crate::plugins::InitializedPluginFormatRequest;
+
+pub struct InitializedAsyncNativePlugin<THandler: AsyncPluginHandler> {
+  handler: Rc<THandler>,
+  configs: RefCell<HashMap<FormatConfigId, Rc<ResolvedConfig<THandler::Configuration>>>>,
+}
+
+impl<THandler: AsyncPluginHandler> InitializedAsyncNativePlugin<THandler> {
+  pub fn new(handler: THandler) -> Self {
+    Self {
+      handler: Rc::new(handler),
+      configs: Default::default(),
+    }
+  }
+
+  async fn get_resolved_config(&self, config: &FormatConfig) -> Rc<ResolvedConfig<THandler::Configuration>> {
+    if let Some(resolved_config) = self.configs.borrow().get(&config.id) {
+      return resolved_config.clone();
+    }
+    let result = self.handler.resolve_config(config.plugin.clone(), config.global.clone()).await;
+    let resolved_config = Rc::new(ResolvedConfig::from(result));
+    self.configs.borrow_mut().insert(config.id, resolved_config.clone());
+    resolved_config
+  }
+}
+
+#[async_trait(?Send)]
+impl<THandler: AsyncPluginHandler> InitializedPlugin for InitializedAsyncNativePlugin<THandler> {
+  async fn license_text(&self) -> Result<String> {
+    Ok(self.handler.license_text())
+  }
+
+  async fn resolved_config(&self, config: Arc<FormatConfig>) -> Result<String> {
+    let resolved_config = self.get_resolved_config(&config).await;
+    Ok(serde_json::to_string(&*resolved_config.config)?)
+  }
+
+  async fn file_matching_info(&self, config: Arc<FormatConfig>) -> Result<FileMatchingInfo> {
+    Ok(self.get_resolved_config(&config).await.file_matching.clone())
+  }
+
+  async fn config_diagnostics(&self, config: Arc<FormatConfig>) -> Result<Vec<ConfigurationDiagnostic>> {
+    Ok(self.get_resolved_config(&config).await.diagnostics.clone())
+  }
+
+  async fn check_config_updates(&self, message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>> {
+    self.handler.check_config_updates(message).await
+  }
+
+  async fn format_text(&self, request: InitializedPluginFormatRequest) -> FormatResult {
+    if request.token.is_cancelled() {
+      return Ok(None);
+    }
+    let config = if request.override_config.is_empty() {
+      self.get_resolved_config(&request.config).await.config.clone()
+    } else {
+      let plugin_config = get_plugin_config_with_override(&request.config, request.override_config);
+      Arc::new(self.handler.resolve_config(plugin_config, request.config.global.clone()).await.config)
+    };
+    let on_host_format = request.on_host_format;
+    self
+      .handler
+      .format(
+        FormatRequest {
+          file_path: request.file_path,
+          file_bytes: request.file_text,
+          config_id: request.config.id,
+          config,
+          range: request.range,
+          whitespace: WhitespaceOptions::from(&request.config.global),
+          token: request.token,
+        },
+        move |host_request| (on_host_format)(host_request),
+      )
+      .await
+  }
+
+  async fn check_text(&self, request: InitializedPluginCheckRequest) -> CheckResult {
+    if request.token.is_cancelled() {
+      return Ok(Vec::new());
+    }
+    let config = self.get_resolved_config(&request.config).await.config.clone();
+    self
+      .handler
+      .check(CheckRequest {
+        file_path: request.file_path,
+        file_bytes: request.file_text,
+        config_id: request.config.id,
+        config,
+        token: request.token,
+      })
+      .await
+  }
+
+  async fn shutdown(&self) {
+    // do nothing
+  }
+}
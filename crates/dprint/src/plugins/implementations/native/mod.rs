@@ -0,0 +1,384 @@
+mod async_plugin;
+mod sync_plugin;
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::Result;
+use dprint_core::async_runtime::async_trait;
+use dprint_core::configuration::ConfigKeyMap;
+use dprint_core::configuration::ConfigurationDiagnostic;
+use dprint_core::plugins::AsyncPluginHandler;
+use dprint_core::plugins::FileMatchingInfo;
+use dprint_core::plugins::PluginInfo;
+use dprint_core::plugins::PluginResolveConfigurationResult;
+use dprint_core::plugins::SyncPluginHandler;
+
+use crate::plugins::FormatConfig;
+use crate::plugins::InitializedPlugin;
+use crate::plugins::Plugin;
+
+type CreateInstanceFn = dyn Fn() -> Rc<dyn InitializedPlugin> + Send + Sync;
+
+/// A plugin compiled into the CLI binary.
+///
+/// These run in the same process as the CLI, so there is no Wasm or
+/// inter-process communication overhead. Note that a panic in a native
+/// plugin will exit the CLI.
+#[derive(Clone)]
+pub struct NativePlugin {
+  info: PluginInfo,
+  create_instance: Arc<CreateInstanceFn>,
+}
+
+impl NativePlugin {
+  /// Creates a native plugin from an async handler (the same trait process plugins implement).
+  ///
+  /// The function is called each time the CLI creates an instance of the plugin.
+  pub fn from_async<THandler: AsyncPluginHandler>(create_handler: impl Fn() -> THandler + Send + Sync + 'static) -> Self {
+    Self {
+      info: create_handler().plugin_info(),
+      create_instance: Arc::new(move || Rc::new(async_plugin::InitializedAsyncNativePlugin::new(create_handler()))),
+    }
+  }
+
+  /// Creates a native plugin from a sync handler (the same trait Wasm plugins implement).
+  ///
+  /// Each instance of the handler runs on its own thread, similar to a Wasm instance.
+  pub fn from_sync<TConfiguration, THandler>(create_handler: impl Fn() -> THandler + Send + Sync + 'static) -> Self
+  where
+    TConfiguration: Clone + serde::Serialize + 'static,
+    THandler: SyncPluginHandler<TConfiguration> + 'static,
+  {
+    let create_handler = Arc::new(create_handler);
+    Self {
+      info: create_handler().plugin_info(),
+      create_instance: Arc::new(move || {
+        let create_handler = create_handler.clone();
+        Rc::new(sync_plugin::InitializedSyncNativePlugin::new(move || create_handler()))
+      }),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl Plugin for NativePlugin {
+  fn info(&self) -> &PluginInfo {
+    &self.info
+  }
+
+  fn is_process_plugin(&self) -> bool {
+    false
+  }
+
+  async fn initialize(&self) -> Result<Rc<dyn InitializedPlugin>> {
+    Ok((self.create_instance)())
+  }
+}
+
+struct ResolvedConfig<TConfiguration> {
+  config: std::sync::Arc<TConfiguration>,
+  file_matching: FileMatchingInfo,
+  diagnostics: Vec<ConfigurationDiagnostic>,
+}
+
+impl<TConfiguration: Clone + serde::Serialize> From<PluginResolveConfigurationResult<TConfiguration>> for ResolvedConfig<TConfiguration> {
+  fn from(result: PluginResolveConfigurationResult<TConfiguration>) -> Self {
+    Self {
+      config: std::sync::Arc::new(result.config),
+      file_matching: result.file_matching,
+      diagnostics: result.diagnostics,
+    }
+  }
+}
+
+/// Gets the plugin config with the override config applied.
+fn get_plugin_config_with_override(config: &FormatConfig, override_config: ConfigKeyMap) -> ConfigKeyMap {
+  let mut plugin_config = config.plugin.clone();
+  for (key, value) in override_config {
+    plugin_config.insert(key, value);
+  }
+  plugin_config
+}
+
+#[cfg(test)]
+mod test {
+  use std::path::PathBuf;
+  use std::rc::Rc;
+
+  use anyhow::Result;
+  use dprint_core::async_runtime::async_trait;
+  use dprint_core::async_runtime::LocalBoxFuture;
+  use dprint_core::configuration::get_unknown_property_diagnostics;
+  use dprint_core::configuration::get_value;
+  use dprint_core::configuration::ConfigKeyMap;
+  use dprint_core::configuration::GlobalConfiguration;
+  use dprint_core::configuration::WhitespaceOptions;
+  use dprint_core::formatting::PrintItems;
+  use dprint_core::formatting::PrintOptions;
+  use dprint_core::formatting::Signal;
+  use dprint_core::plugins::AsyncPluginHandler;
+  use dprint_core::plugins::CheckConfigUpdatesMessage;
+  use dprint_core::plugins::ConfigChange;
+  use dprint_core::plugins::FileMatchingInfo;
+  use dprint_core::plugins::FormatRequest;
+  use dprint_core::plugins::FormatResult;
+  use dprint_core::plugins::HostFormatRequest;
+  use dprint_core::plugins::PluginInfo;
+  use dprint_core::plugins::PluginResolveConfigurationResult;
+  use dprint_core::plugins::SyncFormatRequest;
+  use dprint_core::plugins::SyncHostFormatRequest;
+  use dprint_core::plugins::SyncPluginHandler;
+  use pretty_assertions::assert_eq;
+  use serde::Serialize;
+
+  use super::NativePlugin;
+  use crate::arg_parser::parse_args;
+  use crate::environment::Environment;
+  use crate::environment::TestEnvironment;
+  use crate::environment::TestEnvironmentBuilder;
+  use crate::plugins::Plugin;
+  use crate::plugins::PluginCache;
+  use crate::plugins::PluginResolver;
+  use crate::run_cli::run_cli;
+  use crate::test_helpers::get_plural_formatted_text;
+  use crate::utils::TestStdInReader;
+
+  #[derive(Clone, Serialize)]
+  struct Configuration {
+    ending: String,
+  }
+
+  fn plugin_info(name: &str, config_key: &str) -> PluginInfo {
+    PluginInfo {
+      name: name.to_string(),
+      version: "0.1.0".to_string(),
+      config_key: config_key.to_string(),
+      help_url: "https://dprint.dev/plugins/test".to_string(),
+      config_schema_url: String::new(),
+      update_url: None,
+    }
+  }
+
+  fn resolve_config(mut config: ConfigKeyMap, file_extension: &str, default_ending: &str) -> PluginResolveConfigurationResult<Configuration> {
+    let mut diagnostics = Vec::new();
+    let ending = get_value(&mut config, "ending", default_ending.to_string(), &mut diagnostics);
+    diagnostics.extend(get_unknown_property_diagnostics(config));
+    PluginResolveConfigurationResult {
+      file_matching: FileMatchingInfo {
+        file_extensions: vec![file_extension.to_string()],
+        file_names: vec![],
+      },
+      diagnostics,
+      config: Configuration { ending },
+    }
+  }
+
+  fn append_ending(text: &str, ending: &str) -> Option<Vec<u8>> {
+    let ending = format!("_{}", ending);
+    if text.ends_with(&ending) {
+      None
+    } else {
+      Some(format!("{}{}", text, ending).into_bytes())
+    }
+  }
+
+  /// Prints the words of each line with spaces between them so
+  /// the printer enforces the global whitespace policies.
+  fn print_words(text: &str, whitespace: WhitespaceOptions) -> Option<Vec<u8>> {
+    let printed_text = dprint_core::formatting::format(
+      || {
+        let mut items = PrintItems::new();
+        for (i, line) in text.split('\n').enumerate() {
+          if i > 0 {
+            items.push_signal(Signal::NewLine);
+          }
+          for (j, word) in line.split(' ').enumerate() {
+            if j > 0 {
+              items.push_space();
+            }
+            if !word.is_empty() {
+              items.push_string(word.to_string());
+            }
+          }
+        }
+        items
+      },
+      PrintOptions::new(120, 2, false, "\n").with_whitespace(whitespace),
+    );
+    if printed_text == text {
+      None
+    } else {
+      Some(printed_text.into_bytes())
+    }
+  }
+
+  /// Appends the configured ending, formats the rest of the text with
+  /// the host when it starts with "plugin: " or prints it with the
+  /// whitespace policies when it starts with "print:".
+  struct AsyncTestHandler;
+
+  #[async_trait(?Send)]
+  impl AsyncPluginHandler for AsyncTestHandler {
+    type Configuration = Configuration;
+
+    fn plugin_info(&self) -> PluginInfo {
+      plugin_info("test-native-async-plugin", "nativeAsync")
+    }
+
+    fn license_text(&self) -> String {
+      "License".to_string()
+    }
+
+    async fn resolve_config(&self, config: ConfigKeyMap, _global_config: GlobalConfiguration) -> PluginResolveConfigurationResult<Configuration> {
+      resolve_config(config, "txt_na", "async")
+    }
+
+    async fn check_config_updates(&self, _message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>> {
+      Ok(Vec::new())
+    }
+
+    async fn format(
+      &self,
+      request: FormatRequest<Configuration>,
+      mut format_with_host: impl FnMut(HostFormatRequest) -> LocalBoxFuture<'static, FormatResult> + 'static,
+    ) -> FormatResult {
+      let text = String::from_utf8(request.file_bytes)?;
+      if let Some(inner_text) = text.strip_prefix("plugin: ") {
+        let result = format_with_host(HostFormatRequest {
+          file_path: PathBuf::from("file.txt_ns"),
+          file_bytes: inner_text.as_bytes().to_vec(),
+          range: None,
+          override_config: Default::default(),
+          hints: Default::default(),
+          token: request.token.clone(),
+        })
+        .await?;
+        return Ok(Some(result.unwrap_or_else(|| inner_text.as_bytes().to_vec())));
+      }
+      if text.starts_with("print:") {
+        return Ok(print_words(&text, request.whitespace));
+      }
+      Ok(append_ending(&text, &request.config.ending))
+    }
+  }
+
+  struct SyncTestHandler;
+
+  impl SyncPluginHandler<Configuration> for SyncTestHandler {
+    fn resolve_config(&mut self, config: ConfigKeyMap, _global_config: &GlobalConfiguration) -> PluginResolveConfigurationResult<Configuration> {
+      resolve_config(config, "txt_ns", "sync")
+    }
+
+    fn plugin_info(&mut self) -> PluginInfo {
+      plugin_info("test-native-sync-plugin", "nativeSync")
+    }
+
+    fn license_text(&mut self) -> String {
+      "License".to_string()
+    }
+
+    fn check_config_updates(&self, _message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>> {
+      Ok(Vec::new())
+    }
+
+    fn format(&mut self, request: SyncFormatRequest<Configuration>, mut format_with_host: impl FnMut(SyncHostFormatRequest) -> FormatResult) -> FormatResult {
+      let text = String::from_utf8(request.file_bytes)?;
+      if let Some(inner_text) = text.strip_prefix("plugin: ") {
+        let result = format_with_host(SyncHostFormatRequest {
+          file_path: &PathBuf::from("file.txt"),
+          file_bytes: inner_text.as_bytes(),
+          range: None,
+          override_config: &Default::default(),
+          hints: Default::default(),
+        })?;
+        return Ok(Some(result.unwrap_or_else(|| inner_text.as_bytes().to_vec())));
+      }
+      if text.starts_with("print:") {
+        return Ok(print_words(&text, request.whitespace));
+      }
+      Ok(append_ending(&text, &request.config.ending))
+    }
+  }
+
+  fn native_plugins() -> Vec<Box<dyn Plugin>> {
+    vec![
+      Box::new(NativePlugin::from_async(|| AsyncTestHandler)),
+      Box::new(NativePlugin::from_sync(|| SyncTestHandler)),
+    ]
+  }
+
+  fn run_test_cli_with_native_plugins(args: Vec<&str>, environment: &TestEnvironment) -> Result<()> {
+    let args = parse_args(std::iter::once("").chain(args).map(String::from).collect(), TestStdInReader::default())?;
+    environment.set_stdout_machine_readable(args.is_stdout_machine_readable());
+    let plugin_resolver = Rc::new(PluginResolver::new(environment.clone(), PluginCache::new(environment.clone())).with_native_plugins(native_plugins()));
+    environment.run_in_runtime({
+      let environment = environment.clone();
+      async move {
+        let result = run_cli(&args, &environment, &plugin_resolver).await;
+        plugin_resolver.clear_and_shutdown_initialized().await;
+        result
+      }
+    })
+  }
+
+  #[test]
+  fn should_format_with_native_plugins() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|config_file| {
+        config_file.add_config_section("nativeSync", r#"{ "ending": "custom" }"#);
+      })
+      .write_file("/file.txt_na", "text")
+      .write_file("/file.txt_ns", "text")
+      .write_file("/file2.txt_na", "plugin: text")
+      .write_file("/file3.txt_ns", "plugin: text")
+      .write_file("/file.txt", "text")
+      .build();
+    run_test_cli_with_native_plugins(vec!["fmt"], &environment).unwrap();
+    assert_eq!(environment.take_stdout_messages(), vec![get_plural_formatted_text(5)]);
+    assert_eq!(environment.read_file("/file.txt_na").unwrap(), "text_async");
+    assert_eq!(environment.read_file("/file.txt_ns").unwrap(), "text_custom");
+    // host formatting between native plugins and with the Wasm plugin, then
+    // the ending is added when formatting again to check for stable output
+    assert_eq!(environment.read_file("/file2.txt_na").unwrap(), "text_custom_async");
+    assert_eq!(environment.read_file("/file3.txt_ns").unwrap(), "text_formatted_custom");
+    assert_eq!(environment.read_file("/file.txt").unwrap(), "text_formatted");
+  }
+
+  #[test]
+  fn should_output_native_plugin_config_diagnostics() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|config_file| {
+        config_file.add_config_section("nativeAsync", r#"{ "unknown": 1 }"#);
+      })
+      .write_file("/file.txt_na", "text")
+      .build();
+    let err = run_test_cli_with_native_plugins(vec!["fmt"], &environment).unwrap_err();
+    assert_eq!(err.to_string(), "Had 1 error formatting.");
+    assert_eq!(
+      environment.take_stderr_messages(),
+      vec![
+        "[test-native-async-plugin]: Unknown property in configuration (unknown)",
+        "[test-native-async-plugin]: Error initializing from configuration file. Had 1 diagnostic(s)."
+      ]
+    );
+  }
+
+  #[test]
+  fn should_pass_global_whitespace_policies_to_native_plugins() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_wasm_plugin()
+      .with_default_config(|config_file| {
+        config_file
+          .add_config_section("trimTrailingWhitespace", "true")
+          .add_config_section("insertFinalNewLine", "true")
+          .add_config_section("maxConsecutiveBlankLines", "1");
+      })
+      .write_file("/file.txt_na", "print: a \n\n\n\nb  c ")
+      .write_file("/file.txt_ns", "print: a \n\n\n\nb  c ")
+      .build();
+    run_test_cli_with_native_plugins(vec!["fmt"], &environment).unwrap();
+    assert_eq!(environment.take_stdout_messages(), vec![get_plural_formatted_text(2)]);
+    assert_eq!(environment.read_file("/file.txt_na").unwrap(), "print: a\n\nb  c\n");
+    assert_eq!(environment.read_file("/file.txt_ns").unwrap(), "print: a\n\nb  c\n");
+  }
+}
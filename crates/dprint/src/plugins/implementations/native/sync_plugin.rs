@@ -0,0 +1,297 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use dprint_core::async_runtime::async_trait;
+use dprint_core::configuration::ConfigKeyMap;
+use dprint_core::configuration::ConfigurationDiagnostic;
+use dprint_core::configuration::WhitespaceOptions;
+use dprint_core::plugins::process::HostFormatCallback;
+use dprint_core::plugins::CancellationToken;
+use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
+use dprint_core::plugins::ConfigChange;
+use dprint_core::plugins::FileMatchingInfo;
+use dprint_core::plugins::FormatConfigId;
+use dprint_core::plugins::FormatRange;
+use dprint_core::plugins::FormatResult;
+use dprint_core::plugins::HostFormatRequest;
+use dprint_core::plugins::PluginResolveConfigurationResult;
+use dprint_core::plugins::SyncCheckRequest;
+use dprint_core::plugins::SyncFormatRequest;
+use dprint_core::plugins::SyncHostFormatRequest;
+use dprint_core::plugins::SyncPluginHandler;
+
+use super::get_plugin_config_with_override;
+use crate::plugins::FormatConfig;
+use crate::plugins::InitializedPlugin;
+use crate::plugins::InitializedPluginCheckRequest;
+use crate::plugins::InitializedPluginFormatRequest;
+
+struct SyncPluginFormatMessage {
+  file_path: PathBuf,
+  file_bytes: Vec<u8>,
+  range: FormatRange,
+  config: Arc<FormatConfig>,
+  override_config: ConfigKeyMap,
+  token: Arc<dyn CancellationToken>,
+}
+
+struct SyncPluginCheckMessage {
+  file_path: PathBuf,
+  file_bytes: Vec<u8>,
+  config: Arc<FormatConfig>,
+  token: Arc<dyn CancellationToken>,
+}
+
+type ResponseSender<T> = tokio::sync::oneshot::Sender<T>;
+type HostFormatSender = tokio::sync::mpsc::UnboundedSender<(HostFormatRequest, std::sync::mpsc::Sender<FormatResult>)>;
+
+enum SyncPluginMessage {
+  LicenseText(ResponseSender<String>),
+  ResolvedConfig(Arc<FormatConfig>, ResponseSender<Result<String>>),
+  CheckConfigUpdates(CheckConfigUpdatesMessage, ResponseSender<Result<Vec<ConfigChange>>>),
+  FileMatchingInfo(Arc<FormatConfig>, ResponseSender<FileMatchingInfo>),
+  ConfigDiagnostics(Arc<FormatConfig>, ResponseSender<Vec<ConfigurationDiagnostic>>),
+  FormatRequest(SyncPluginFormatMessage, ResponseSender<FormatResult>),
+  CheckRequest(SyncPluginCheckMessage, ResponseSender<CheckResult>),
+}
+
+type SyncPluginSender = std::sync::mpsc::Sender<SyncPluginMessage>;
+
+struct SyncPluginSenderWithState {
+  sender: SyncPluginSender,
+  host_format_callback_cell: Rc<RefCell<Option<HostFormatCallback>>>,
+}
+
+type SpawnInstanceFn = dyn Fn(std::sync::mpsc::Receiver<SyncPluginMessage>, HostFormatSender);
+
+/// A sync plugin handler that runs on dedicated threads.
+///
+/// Like Wasm plugins, a new instance is created when all the others are busy.
+pub struct InitializedSyncNativePlugin {
+  pending_instances: RefCell<Vec<SyncPluginSenderWithState>>,
+  spawn_instance: Box<SpawnInstanceFn>,
+}
+
+impl InitializedSyncNativePlugin {
+  pub fn new<TConfiguration, THandler>(create_handler: impl Fn() -> THandler + Send + Sync + Clone + 'static) -> Self
+  where
+    TConfiguration: Clone + serde::Serialize + 'static,
+    THandler: SyncPluginHandler<TConfiguration> + 'static,
+  {
+    Self {
+      pending_instances: Default::default(),
+      spawn_instance: Box::new(move |receiver, host_format_sender| {
+        let create_handler = create_handler.clone();
+        dprint_core::async_runtime::spawn_blocking(move || {
+          run_handler(create_handler(), receiver, host_format_sender);
+        });
+      }),
+    }
+  }
+
+  async fn send<T>(&self, host_format_callback: Option<HostFormatCallback>, create_message: impl FnOnce(ResponseSender<T>) -> SyncPluginMessage) -> Result<T> {
+    let instance = self.get_or_create_instance();
+    *instance.host_format_callback_cell.borrow_mut() = host_format_callback;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let result = match instance.sender.send(create_message(tx)) {
+      Ok(()) => rx.await.map_err(|_| anyhow!("The native plugin's thread exited unexpectedly.")),
+      Err(_) => Err(anyhow!("The native plugin's thread exited unexpectedly.")),
+    };
+    *instance.host_format_callback_cell.borrow_mut() = None;
+    if result.is_ok() {
+      self.pending_instances.borrow_mut().push(instance);
+    }
+    result
+  }
+
+  fn get_or_create_instance(&self) -> SyncPluginSenderWithState {
+    let maybe_instance = self.pending_instances.borrow_mut().pop(); // needs to be on a separate line
+    match maybe_instance {
+      Some(instance) => instance,
+      None => self.create_instance(),
+    }
+  }
+
+  fn create_instance(&self) -> SyncPluginSenderWithState {
+    let (host_format_tx, mut host_format_rx) = tokio::sync::mpsc::unbounded_channel::<(HostFormatRequest, std::sync::mpsc::Sender<FormatResult>)>();
+    let host_format_callback_cell: Rc<RefCell<Option<HostFormatCallback>>> = Default::default();
+
+    dprint_core::async_runtime::spawn({
+      let host_format_callback_cell = host_format_callback_cell.clone();
+      async move {
+        while let Some((request, sender)) = host_format_rx.recv().await {
+          let host_format_callback = host_format_callback_cell.borrow().clone();
+          let result = match host_format_callback {
+            Some(host_format_callback) => (host_format_callback)(request).await,
+            None => Err(anyhow!("Host format callback was not set.")),
+          };
+          if sender.send(result).is_err() {
+            return; // disconnected
+          }
+        }
+      }
+    });
+
+    let (tx, rx) = std::sync::mpsc::channel::<SyncPluginMessage>();
+    (self.spawn_instance)(rx, host_format_tx);
+    SyncPluginSenderWithState {
+      sender: tx,
+      host_format_callback_cell,
+    }
+  }
+}
+
+fn run_handler<TConfiguration: Clone + serde::Serialize>(
+  mut handler: impl SyncPluginHandler<TConfiguration>,
+  receiver: std::sync::mpsc::Receiver<SyncPluginMessage>,
+  host_format_sender: HostFormatSender,
+) {
+  let mut configs: HashMap<FormatConfigId, PluginResolveConfigurationResult<TConfiguration>> = HashMap::new();
+  fn get_resolved_config<'a, TConfiguration: Clone + serde::Serialize>(
+    handler: &mut impl SyncPluginHandler<TConfiguration>,
+    configs: &'a mut HashMap<FormatConfigId, PluginResolveConfigurationResult<TConfiguration>>,
+    config: &FormatConfig,
+  ) -> &'a PluginResolveConfigurationResult<TConfiguration> {
+    configs
+      .entry(config.id)
+      .or_insert_with(|| handler.resolve_config(config.plugin.clone(), &config.global))
+  }
+
+  // the responses only fail to send when the CLI stopped waiting, so ignore those errors
+  while let Ok(message) = receiver.recv() {
+    match message {
+      SyncPluginMessage::LicenseText(response) => {
+        let _ = response.send(handler.license_text());
+      }
+      SyncPluginMessage::ResolvedConfig(config, response) => {
+        let result = get_resolved_config(&mut handler, &mut configs, &config);
+        let _ = response.send(serde_json::to_string(&result.config).map_err(|err| err.into()));
+      }
+      SyncPluginMessage::CheckConfigUpdates(message, response) => {
+        let _ = response.send(handler.check_config_updates(message));
+      }
+      SyncPluginMessage::FileMatchingInfo(config, response) => {
+        let result = get_resolved_config(&mut handler, &mut configs, &config);
+        let _ = response.send(result.file_matching.clone());
+      }
+      SyncPluginMessage::ConfigDiagnostics(config, response) => {
+        let result = get_resolved_config(&mut handler, &mut configs, &config);
+        let _ = response.send(result.diagnostics.clone());
+      }
+      SyncPluginMessage::FormatRequest(request, response) => {
+        let override_result;
+        let config = if request.override_config.is_empty() {
+          &get_resolved_config(&mut handler, &mut configs, &request.config).config
+        } else {
+          let plugin_config = get_plugin_config_with_override(&request.config, request.override_config);
+          override_result = handler.resolve_config(plugin_config, &request.config.global);
+          &override_result.config
+        };
+        let token = request.token.clone();
+        let format_with_host = |host_request: SyncHostFormatRequest| {
+          let (tx, rx) = std::sync::mpsc::channel();
+          let host_request = HostFormatRequest {
+            file_path: host_request.file_path.to_path_buf(),
+            file_bytes: host_request.file_bytes.to_vec(),
+            range: host_request.range,
+            override_config: host_request.override_config.clone(),
+            hints: host_request.hints,
+            token: token.clone(),
+          };
+          host_format_sender
+            .send((host_request, tx))
+            .map_err(|_| anyhow!("Could not send host format request."))?;
+          rx.recv()?
+        };
+        let result = handler.format(
+          SyncFormatRequest {
+            file_path: &request.file_path,
+            file_bytes: request.file_bytes,
+            config_id: request.config.id,
+            config,
+            range: request.range,
+            whitespace: WhitespaceOptions::from(&request.config.global),
+            token: request.token.as_ref(),
+          },
+          format_with_host,
+        );
+        let _ = response.send(result);
+      }
+      SyncPluginMessage::CheckRequest(request, response) => {
+        let config = &get_resolved_config(&mut handler, &mut configs, &request.config).config;
+        let result = handler.check(SyncCheckRequest {
+          file_path: &request.file_path,
+          file_bytes: request.file_bytes,
+          config_id: request.config.id,
+          config,
+          token: request.token.as_ref(),
+        });
+        let _ = response.send(result);
+      }
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl InitializedPlugin for InitializedSyncNativePlugin {
+  async fn license_text(&self) -> Result<String> {
+    self.send(None, SyncPluginMessage::LicenseText).await
+  }
+
+  async fn resolved_config(&self, config: Arc<FormatConfig>) -> Result<String> {
+    self.send(None, |tx| SyncPluginMessage::ResolvedConfig(config, tx)).await?
+  }
+
+  async fn file_matching_info(&self, config: Arc<FormatConfig>) -> Result<FileMatchingInfo> {
+    self.send(None, |tx| SyncPluginMessage::FileMatchingInfo(config, tx)).await
+  }
+
+  async fn config_diagnostics(&self, config: Arc<FormatConfig>) -> Result<Vec<ConfigurationDiagnostic>> {
+    self.send(None, |tx| SyncPluginMessage::ConfigDiagnostics(config, tx)).await
+  }
+
+  async fn check_config_updates(&self, message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>> {
+    self.send(None, |tx| SyncPluginMessage::CheckConfigUpdates(message, tx)).await?
+  }
+
+  async fn format_text(&self, request: InitializedPluginFormatRequest) -> FormatResult {
+    if request.token.is_cancelled() {
+      return Ok(None);
+    }
+    let message = SyncPluginFormatMessage {
+      file_path: request.file_path,
+      file_bytes: request.file_text,
+      range: request.range,
+      config: request.config,
+      override_config: request.override_config,
+      token: request.token,
+    };
+    self
+      .send(Some(request.on_host_format), |tx| SyncPluginMessage::FormatRequest(message, tx))
+      .await?
+  }
+
+  async fn check_text(&self, request: InitializedPluginCheckRequest) -> CheckResult {
+    if request.token.is_cancelled() {
+      return Ok(Vec::new());
+    }
+    let message = SyncPluginCheckMessage {
+      file_path: request.file_path,
+      file_bytes: request.file_text,
+      config: request.config,
+      token: request.token,
+    };
+    self.send(None, |tx| SyncPluginMessage::CheckRequest(message, tx)).await?
+  }
+
+  async fn shutdown(&self) {
+    // dropping the senders ends the threads
+    self.pending_instances.borrow_mut().clear();
+  }
+}
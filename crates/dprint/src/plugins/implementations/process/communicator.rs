@@ -1,12 +1,15 @@
 use crate::environment::Environment;
 use crate::plugins::FormatConfig;
+use crate::plugins::InitializedPluginCheckRequest;
 use crate::plugins::InitializedPluginFormatRequest;
 use crate::utils::AsyncMutex;
 use anyhow::Result;
 use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::plugins::process::ProcessPluginCommunicator;
+use dprint_core::plugins::process::ProcessPluginCommunicatorCheckRequest;
 use dprint_core::plugins::process::ProcessPluginCommunicatorFormatRequest;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::FileMatchingInfo;
 use dprint_core::plugins::FormatConfigId;
@@ -120,6 +123,19 @@ impl<TEnvironment: Environment> InitializedProcessPluginCommunicator<TEnvironmen
     }
   }
 
+  pub async fn check_text(&self, request: InitializedPluginCheckRequest) -> CheckResult {
+    self
+      .get_inner_ensure_config(&request.config)
+      .await?
+      .check_text(ProcessPluginCommunicatorCheckRequest {
+        file_path: request.file_path,
+        file_bytes: request.file_text,
+        config_id: request.config.id,
+        token: request.token,
+      })
+      .await
+  }
+
   pub async fn get_inner(&self) -> Rc<ProcessPluginCommunicator> {
     self.inner.lock().await.communicator.clone()
   }
@@ -260,6 +276,83 @@ mod test {
     })
   }
 
+  #[test]
+  fn should_format_large_files_in_chunks() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin().build();
+    environment.run_in_runtime({
+      let environment = environment.clone();
+      async move {
+        let communicator = InitializedProcessPluginCommunicator::new_test_plugin_communicator(environment.clone()).await;
+        let format_config = Arc::new(FormatConfig {
+          id: FormatConfigId::from_raw(1),
+          plugin: Default::default(),
+          global: Default::default(),
+        });
+        let format = |file_text: String| {
+          communicator.format_text(InitializedPluginFormatRequest {
+            file_path: PathBuf::from("test.txt"),
+            file_text: file_text.into_bytes(),
+            range: None,
+            config: format_config.clone(),
+            override_config: Default::default(),
+            on_host_format: Rc::new(|_| future::ready(Ok(None)).boxed_local()),
+            token: Arc::new(NullCancellationToken),
+          })
+        };
+
+        // larger than a few chunks
+        let large_text = "a".repeat(1024 * 1024 * 3 + 5);
+        let formatted_text = format(large_text.clone()).await.unwrap();
+        assert_eq!(
+          formatted_text.map(|t| String::from_utf8(t).unwrap()),
+          Some(format!("{}_formatted_process", large_text))
+        );
+
+        // plugin reports no change, so the text isn't sent back
+        let formatted_text = format(format!("unchanged{}", large_text)).await.unwrap();
+        assert_eq!(formatted_text, None);
+
+        assert_eq!(environment.take_stderr_messages(), Vec::<String>::new());
+
+        communicator.shutdown().await;
+      }
+    })
+  }
+
+  #[test]
+  fn should_check_large_files_in_chunks() {
+    let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin().build();
+    environment.run_in_runtime({
+      let environment = environment.clone();
+      async move {
+        let communicator = InitializedProcessPluginCommunicator::new_test_plugin_communicator(environment.clone()).await;
+        let format_config = Arc::new(FormatConfig {
+          id: FormatConfigId::from_raw(1),
+          plugin: Default::default(),
+          global: Default::default(),
+        });
+
+        // larger than a few chunks
+        let message = "a".repeat(1024 * 1024 * 3 + 5);
+        let diagnostics = communicator
+          .check_text(InitializedPluginCheckRequest {
+            file_path: PathBuf::from("test.txt"),
+            file_text: format!("warn: {}", message).into_bytes(),
+            config: format_config.clone(),
+            token: Arc::new(NullCancellationToken),
+          })
+          .await
+          .unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, message);
+
+        assert_eq!(environment.take_stderr_messages(), Vec::<String>::new());
+
+        communicator.shutdown().await;
+      }
+    })
+  }
+
   #[test]
   fn should_handle_cancellation() {
     let environment = TestEnvironmentBuilder::with_initialized_remote_process_plugin().build();
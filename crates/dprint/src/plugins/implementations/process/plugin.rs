@@ -2,6 +2,7 @@ use anyhow::Result;
 use dprint_core::async_runtime::async_trait;
 use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::FileMatchingInfo;
 use dprint_core::plugins::FormatResult;
@@ -14,6 +15,7 @@ use std::time::Instant;
 use crate::environment::Environment;
 use crate::plugins::FormatConfig;
 use crate::plugins::InitializedPlugin;
+use crate::plugins::InitializedPluginCheckRequest;
 use crate::plugins::InitializedPluginFormatRequest;
 use crate::plugins::Plugin;
 
@@ -144,6 +146,10 @@ impl<TEnvironment: Environment> InitializedPlugin for InitializedProcessPlugin<T
     self.communicator.format_text(request).await
   }
 
+  async fn check_text(&self, request: InitializedPluginCheckRequest) -> CheckResult {
+    self.communicator.check_text(request).await
+  }
+
   async fn shutdown(&self) -> () {
     self.communicator.shutdown().await
   }
@@ -8,6 +8,7 @@ use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::plugins::wasm::PLUGIN_SYSTEM_SCHEMA_VERSION;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::FileMatchingInfo;
 use dprint_core::plugins::FormatRange;
@@ -55,6 +56,7 @@ pub trait InitializedWasmPluginInstance {
     override_config: &ConfigKeyMap,
     token: Arc<dyn CancellationToken>,
   ) -> FormatResult;
+  fn check_text(&mut self, file_path: &Path, file_bytes: &[u8], config: &FormatConfig, token: Arc<dyn CancellationToken>) -> CheckResult;
 }
 
 pub fn create_wasm_plugin_instance(store: Store, instance: WasmInstance) -> Result<Box<dyn InitializedWasmPluginInstance>> {
@@ -10,6 +10,7 @@ use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::configuration::GlobalConfiguration;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::CriticalFormatError;
 use dprint_core::plugins::FileMatchingInfo;
@@ -203,6 +204,7 @@ pub fn create_pools_import_object(store: &mut Store, host_format_sender: WasmHos
       file_bytes,
       range: None,
       override_config,
+      hints: Default::default(),
       token: env.token.clone(),
     };
     // todo: worth it to use a oneshot channel library here?
@@ -459,6 +461,10 @@ impl InitializedWasmPluginInstance for InitializedWasmPluginInstanceV3 {
       Err(err) => Err(CriticalFormatError(err).into()),
     }
   }
+
+  fn check_text(&mut self, _file_path: &Path, _file_bytes: &[u8], _config: &FormatConfig, _token: Arc<dyn CancellationToken>) -> CheckResult {
+    Ok(Vec::new()) // not supported for v3
+  }
 }
 
 struct WasmFunctions {
@@ -9,13 +9,16 @@ use anyhow::Result;
 use dprint_core::configuration::ConfigKeyMap;
 use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::configuration::GlobalConfiguration;
+use dprint_core::plugins::wasm::HostFormatConfigWithHints;
 use dprint_core::plugins::wasm::JsonResponse;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::CriticalFormatError;
 use dprint_core::plugins::FileMatchingInfo;
 use dprint_core::plugins::FormatConfigId;
+use dprint_core::plugins::FormatDiagnostic;
 use dprint_core::plugins::FormatRange;
 use dprint_core::plugins::FormatResult;
 use dprint_core::plugins::HostFormatRequest;
@@ -86,11 +89,22 @@ pub fn create_pools_import_object<TEnvironment: Environment>(
     error_text_store: String,
     token: Arc<dyn CancellationToken>,
     host_format_sender: WasmHostFormatSender,
+    /// If the plugin sends its override configuration along with hints.
+    host_format_hints_enabled: bool,
+    /// If the plugin can receive host format errors as format diagnostics.
+    host_format_diagnostics_enabled: bool,
   }
 
   impl<TEnvironment: Environment> ImportObjectEnvironment for FunctionEnv<ImportObjectEnvironmentV4<TEnvironment>> {
     fn initialize(&self, store: &mut Store, instance: &Instance) -> Result<(), ExportError> {
       self.as_mut(store).memory = Some(instance.exports.get_memory("memory")?.clone());
+      // older plugins won't have this export
+      if let Ok(func) = instance.exports.get_typed_function::<(), ()>(store, "enable_host_format_hints") {
+        self.as_mut(store).host_format_hints_enabled = func.call(store).is_ok();
+      }
+      if let Ok(func) = instance.exports.get_typed_function::<(), ()>(store, "enable_host_format_diagnostics") {
+        self.as_mut(store).host_format_diagnostics_enabled = func.call(store).is_ok();
+      }
       Ok(())
     }
 
@@ -184,13 +198,18 @@ pub fn create_pools_import_object<TEnvironment: Environment>(
     let memory = env_data.memory.as_ref().unwrap();
     let store_ref = env.as_store_ref();
     let memory_view = memory.view(&store_ref);
-    let override_config = {
+    let (override_config, hints) = {
       if override_cfg_len == 0 {
         Default::default()
       } else {
         let mut buf = vec![0; override_cfg_len as usize];
         memory_view.read(override_cfg_ptr as u64, &mut buf).unwrap();
-        serde_json::from_slice::<ConfigKeyMap>(&buf).unwrap()
+        if env_data.host_format_hints_enabled {
+          let config = serde_json::from_slice::<HostFormatConfigWithHints>(&buf).unwrap();
+          (config.config, config.hints)
+        } else {
+          (serde_json::from_slice::<ConfigKeyMap>(&buf).unwrap(), Default::default())
+        }
       }
     };
     let file_path = {
@@ -214,6 +233,7 @@ pub fn create_pools_import_object<TEnvironment: Environment>(
       file_bytes,
       range,
       override_config,
+      hints,
       token: env.token.clone(),
     };
     // todo: worth it to use a oneshot channel library here?
@@ -239,10 +259,16 @@ pub fn create_pools_import_object<TEnvironment: Environment>(
         0 // no change
       }
       // ignore critical error as we can just continue formatting
-      Err(err) => {
-        env.error_text_store = err.to_string();
-        2 // error
-      }
+      Err(err) => match FormatDiagnostic::from_error(&err) {
+        Some(diagnostic) if env.host_format_diagnostics_enabled => {
+          env.error_text_store = serde_json::to_string(diagnostic).unwrap();
+          3 // error with diagnostic
+        }
+        _ => {
+          env.error_text_store = err.to_string();
+          2 // error
+        }
+      },
     }
   }
 
@@ -279,6 +305,8 @@ pub fn create_pools_import_object<TEnvironment: Environment>(
     error_text_store: Default::default(),
     token: Arc::new(NullCancellationToken),
     host_format_sender,
+    host_format_hints_enabled: false,
+    host_format_diagnostics_enabled: false,
   };
   let env = FunctionEnv::new(store, env);
 
@@ -345,6 +373,23 @@ impl InitializedWasmPluginInstanceV4 {
     self.inner_handle_response(response_code)
   }
 
+  fn inner_check_text(&mut self, file_path: &Path, file_bytes: &[u8], config: &FormatConfig) -> Result<CheckResult> {
+    self.inner_setup_formatting(file_path, file_bytes, None)?;
+    match self.wasm_functions.check(config.id)? {
+      0 => Ok(Ok(Vec::new())),
+      1 => {
+        let len = self.wasm_functions.get_check_diagnostics()?;
+        let bytes = self.receive_bytes(len)?;
+        Ok(Ok(serde_json::from_slice(&bytes)?))
+      }
+      _ => {
+        let len = self.wasm_functions.get_error_text()?;
+        let text = self.receive_string(len)?;
+        Ok(Err(anyhow!("{}", text)))
+      }
+    }
+  }
+
   fn inner_setup_formatting(&mut self, file_path: &Path, file_bytes: &[u8], override_config: Option<&str>) -> Result<()> {
     // send override config if necessary
     if let Some(override_config) = override_config {
@@ -371,6 +416,14 @@ impl InitializedWasmPluginInstanceV4 {
       WasmFormatResult::Error => {
         let len = self.wasm_functions.get_error_text()?;
         let text = self.receive_string(len)?;
+        // older plugins won't have a diagnostic
+        let len = self.wasm_functions.get_error_diagnostic()?.unwrap_or(0);
+        if len > 0 {
+          let bytes = self.receive_bytes(len)?;
+          if let Ok(diagnostic) = serde_json::from_slice::<FormatDiagnostic>(&bytes) {
+            return Ok(Err(diagnostic.into()));
+          }
+        }
         Ok(Err(anyhow!("{}", text)))
       }
     }
@@ -488,6 +541,21 @@ impl InitializedWasmPluginInstance for InitializedWasmPluginInstanceV4 {
       Err(err) => Err(CriticalFormatError(err).into()),
     }
   }
+
+  fn check_text(&mut self, file_path: &Path, file_bytes: &[u8], config: &FormatConfig, token: Arc<dyn CancellationToken>) -> CheckResult {
+    // older plugins won't have a check export, so don't send them the config or file
+    match self.wasm_functions.supports_check() {
+      Ok(true) => {}
+      Ok(false) => return Ok(Vec::new()),
+      Err(err) => return Err(CriticalFormatError(err).into()),
+    }
+    self.wasm_functions.instance.set_token(&mut self.wasm_functions.store, token);
+    self.ensure_config(config)?;
+    match self.inner_check_text(file_path, file_bytes, config) {
+      Ok(inner) => inner,
+      Err(err) => Err(CriticalFormatError(err).into()),
+    }
+  }
 }
 
 struct WasmFunctions {
@@ -594,6 +662,32 @@ impl WasmFunctions {
     Ok(func.call(&mut self.store).map(|value| value as usize)?)
   }
 
+  #[inline]
+  pub fn get_error_diagnostic(&mut self) -> Result<Option<usize>> {
+    let maybe_func = self.get_maybe_export::<(), u32>("get_error_diagnostic")?;
+    match maybe_func {
+      Some(func) => Ok(Some(func.call(&mut self.store).map(|value| value as usize)?)),
+      None => Ok(None),
+    }
+  }
+
+  #[inline]
+  pub fn supports_check(&mut self) -> Result<bool> {
+    Ok(self.get_maybe_export::<u32, u8>("check")?.is_some())
+  }
+
+  #[inline]
+  pub fn check(&mut self, config_id: FormatConfigId) -> Result<u8> {
+    let func = self.get_export::<u32, u8>("check")?;
+    Ok(func.call(&mut self.store, config_id.as_raw())?)
+  }
+
+  #[inline]
+  pub fn get_check_diagnostics(&mut self) -> Result<usize> {
+    let func = self.get_export::<(), u32>("get_check_diagnostics")?;
+    Ok(func.call(&mut self.store).map(|value| value as usize)?)
+  }
+
   #[inline]
   pub fn get_memory_view(&self) -> MemoryView {
     self.memory.view(&self.store)
@@ -6,6 +6,7 @@ use dprint_core::async_runtime::LocalBoxFuture;
 use dprint_core::plugins::process::HostFormatCallback;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::CriticalFormatError;
 use dprint_core::plugins::FileMatchingInfo;
@@ -32,6 +33,7 @@ use crate::environment::Environment;
 use crate::plugins::implementations::wasm::create_wasm_plugin_instance;
 use crate::plugins::FormatConfig;
 use crate::plugins::InitializedPlugin;
+use crate::plugins::InitializedPluginCheckRequest;
 use crate::plugins::InitializedPluginFormatRequest;
 use crate::plugins::Plugin;
 
@@ -90,6 +92,13 @@ struct WasmPluginFormatMessage {
   token: Arc<dyn CancellationToken>,
 }
 
+struct WasmPluginCheckMessage {
+  file_path: PathBuf,
+  file_bytes: Vec<u8>,
+  config: Arc<FormatConfig>,
+  token: Arc<dyn CancellationToken>,
+}
+
 type WasmResponseSender<T> = tokio::sync::oneshot::Sender<T>;
 
 enum WasmPluginMessage {
@@ -99,6 +108,7 @@ enum WasmPluginMessage {
   FileMatchingInfo(Arc<FormatConfig>, WasmResponseSender<Result<FileMatchingInfo>>),
   ConfigDiagnostics(Arc<FormatConfig>, WasmResponseSender<Result<Vec<ConfigurationDiagnostic>>>),
   FormatRequest(Arc<WasmPluginFormatMessage>, WasmResponseSender<FormatResult>),
+  CheckRequest(Arc<WasmPluginCheckMessage>, WasmResponseSender<CheckResult>),
 }
 
 type WasmPluginSender = std::sync::mpsc::Sender<WasmPluginMessage>;
@@ -324,6 +334,12 @@ impl<TEnvironment: Environment> InitializedWasmPlugin<TEnvironment> {
                 break; // disconnected
               }
             }
+            WasmPluginMessage::CheckRequest(request, response) => {
+              let result = instance.check_text(&request.file_path, &request.file_bytes, &request.config, request.token.clone());
+              if response.send(result).is_err() {
+                break; // disconnected
+              }
+            }
           }
         }
       }
@@ -445,6 +461,29 @@ impl<TEnvironment: Environment> InitializedPlugin for InitializedWasmPlugin<TEnv
       .await
   }
 
+  async fn check_text(&self, request: InitializedPluginCheckRequest) -> CheckResult {
+    if request.token.is_cancelled() {
+      return Ok(Vec::new());
+    }
+    let message = Arc::new(WasmPluginCheckMessage {
+      file_path: request.file_path,
+      file_bytes: request.file_text,
+      config: request.config,
+      token: request.token,
+    });
+    self
+      .with_instance(None, move |plugin_sender| {
+        let message = message.clone();
+        async move {
+          let (tx, rx) = tokio::sync::oneshot::channel();
+          plugin_sender.send(WasmPluginMessage::CheckRequest(message, tx))?;
+          rx.await?
+        }
+        .boxed_local()
+      })
+      .await
+  }
+
   async fn shutdown(&self) {
     // do nothing
   }
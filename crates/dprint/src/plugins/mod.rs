@@ -18,4 +18,5 @@ pub use resolver::*;
 pub use types::*;
 
 pub use implementations::compile_wasm;
+pub use implementations::NativePlugin;
 pub use name_resolution::PluginNameResolutionMaps;
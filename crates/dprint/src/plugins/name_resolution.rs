@@ -87,6 +87,19 @@ impl PluginNameResolutionMaps {
     plugin_names
   }
 
+  /// Gets the plugin names to use for a language or media type hint (ex. `typescript`
+  /// or `application/json`) along with the file extension the hint resolved to. The
+  /// file path is given the extension before matching, so associations also apply.
+  pub fn get_plugin_names_from_language(&self, language: &str, file_path: &Path) -> Option<(String, Vec<String>)> {
+    for extension in get_language_extension_candidates(language) {
+      let plugin_names = self.get_plugin_names_from_file_path(&file_path.with_extension(&extension));
+      if !plugin_names.is_empty() {
+        return Some((extension, plugin_names));
+      }
+    }
+    None
+  }
+
   fn is_not_associations_excluded(&self, plugin_name: &str, file_path: &Path) -> bool {
     if let Some(matcher) = self.association_matchers_map.get(plugin_name) {
       matcher.has_only_excludes() && matcher.matches_detail(file_path) == GlobMatchesDetail::NotMatched
@@ -96,6 +109,40 @@ impl PluginNameResolutionMaps {
   }
 }
 
+fn get_language_extension_candidates(language: &str) -> Vec<String> {
+  let language = language.trim().to_lowercase();
+  // media types like "text/x-python" or "application/ld+json"
+  let language = match language.split_once('/') {
+    Some((_, sub_type)) => {
+      let sub_type = sub_type.split(';').next().unwrap_or(sub_type).trim();
+      let sub_type = sub_type.rsplit('+').next().unwrap_or(sub_type);
+      sub_type.strip_prefix("x-").unwrap_or(sub_type).to_string()
+    }
+    None => language,
+  };
+  let language = language.strip_prefix('.').unwrap_or(&language);
+  let alias = match language {
+    "javascript" | "ecmascript" | "node" => Some("js"),
+    "typescript" => Some("ts"),
+    "jsonc" => Some("json"),
+    "markdown" => Some("md"),
+    "yaml" => Some("yml"),
+    "yml" => Some("yaml"),
+    "python" => Some("py"),
+    "rust" => Some("rs"),
+    "shell" | "bash" => Some("sh"),
+    "protobuf" => Some("proto"),
+    _ => None,
+  };
+  let mut candidates = vec![language.to_string()];
+  if let Some(alias) = alias {
+    if alias != language {
+      candidates.push(alias.to_string());
+    }
+  }
+  candidates
+}
+
 fn get_plugin_association_glob_matcher(plugin: &PluginWithConfig, config_base_path: &CanonicalizedPathBuf) -> Result<Option<GlobMatcher>> {
   Ok(if let Some(associations) = plugin.associations.as_ref() {
     Some(get_patterns_as_glob_matcher(associations, config_base_path)?)
@@ -103,3 +150,51 @@ fn get_plugin_association_glob_matcher(plugin: &PluginWithConfig, config_base_pa
     None
   })
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn gets_plugin_names_from_language() {
+    let mut maps = PluginNameResolutionMaps::default();
+    maps.extension_to_plugin_names_map.insert("ts".to_string(), vec!["typescript".to_string()]);
+    maps
+      .extension_to_plugin_names_map
+      .insert("json".to_string(), vec!["json".to_string(), "other".to_string()]);
+    maps.extension_to_plugin_names_map.insert("yaml".to_string(), vec!["yaml".to_string()]);
+
+    let get = |language: &str| maps.get_plugin_names_from_language(language, Path::new("/dir/file.embedded"));
+    let expected = |ext: &str, name: &str| Some((ext.to_string(), vec![name.to_string()]));
+    assert_eq!(get("ts"), expected("ts", "typescript"));
+    assert_eq!(get("TypeScript"), expected("ts", "typescript"));
+    assert_eq!(get(".ts"), expected("ts", "typescript"));
+    assert_eq!(get("application/json"), expected("json", "json"));
+    assert_eq!(get("application/ld+json"), expected("json", "json"));
+    assert_eq!(get("application/json; charset=utf-8"), expected("json", "json"));
+    assert_eq!(get("jsonc"), expected("json", "json"));
+    assert_eq!(get("text/x-yaml"), expected("yaml", "yaml"));
+    assert_eq!(get("yml"), expected("yaml", "yaml"));
+    assert_eq!(get("python"), None);
+    assert_eq!(get(""), None);
+  }
+
+  #[test]
+  fn gets_plugin_names_from_language_with_associations() {
+    let base_path = CanonicalizedPathBuf::new_for_testing("/dir");
+    let mut maps = PluginNameResolutionMaps::default();
+    maps.extension_to_plugin_names_map.insert("json".to_string(), vec!["json".to_string()]);
+    let matcher = Rc::new(get_patterns_as_glob_matcher(&["**/*.sql".to_string()], &base_path).unwrap());
+    maps.association_matchers.push(("sql".to_string(), matcher.clone()));
+    maps.association_matchers_map.insert("sql".to_string(), matcher);
+    let matcher = Rc::new(get_patterns_as_glob_matcher(&["!**/*.json".to_string()], &base_path).unwrap());
+    maps.association_matchers.push(("json".to_string(), matcher.clone()));
+    maps.association_matchers_map.insert("json".to_string(), matcher);
+
+    let get = |language: &str| maps.get_plugin_names_from_language(language, Path::new("/dir/file.embedded"));
+    // only has an association
+    assert_eq!(get("sql"), Some(("sql".to_string(), vec!["sql".to_string()])));
+    // excluded by its associations
+    assert_eq!(get("json"), None);
+  }
+}
@@ -10,6 +10,7 @@ use dprint_core::configuration::GlobalConfiguration;
 use dprint_core::plugins::process::HostFormatCallback;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::FileMatchingInfo;
 use dprint_core::plugins::FormatConfigId;
@@ -44,6 +45,13 @@ pub struct InitializedPluginFormatRequest {
   pub token: Arc<dyn CancellationToken>,
 }
 
+pub struct InitializedPluginCheckRequest {
+  pub file_path: PathBuf,
+  pub file_text: Vec<u8>,
+  pub config: Arc<FormatConfig>,
+  pub token: Arc<dyn CancellationToken>,
+}
+
 #[async_trait(?Send)]
 pub trait InitializedPlugin {
   /// Gets the license text
@@ -58,6 +66,8 @@ pub trait InitializedPlugin {
   async fn check_config_updates(&self, message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>>;
   /// Formats the text in memory based on the file path and file text.
   async fn format_text(&self, format_request: InitializedPluginFormatRequest) -> FormatResult;
+  /// Checks the text for problems the plugin won't fix when formatting.
+  async fn check_text(&self, check_request: InitializedPluginCheckRequest) -> CheckResult;
   /// Shuts down the plugin. This is used for process plugins.
   async fn shutdown(&self) -> ();
 }
@@ -136,6 +146,10 @@ impl InitializedPlugin for InitializedTestPlugin {
     Ok(Some(format!("{}_formatted", String::from_utf8(format_request.file_text)?).into_bytes()))
   }
 
+  async fn check_text(&self, _check_request: InitializedPluginCheckRequest) -> CheckResult {
+    Ok(Vec::new())
+  }
+
   async fn shutdown(&self) -> () {
     // do nothing
   }
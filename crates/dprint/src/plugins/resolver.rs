@@ -43,6 +43,12 @@ impl PluginWrapper {
     self.initialized_plugin.get_or_try_init(|| self.plugin.initialize()).await.cloned()
   }
 
+  /// Creates a new instance of the plugin that isn't shared. The caller is responsible
+  /// for shutting it down.
+  pub async fn initialize_unshared(&self) -> Result<Rc<dyn InitializedPlugin>> {
+    self.plugin.initialize().await
+  }
+
   pub async fn shutdown(&self) {
     if let Some(plugin) = self.initialized_plugin.get() {
       plugin.shutdown().await;
@@ -56,6 +62,7 @@ pub struct PluginResolver<TEnvironment: Environment> {
   memory_cache: RefCell<HashMap<PluginSourceReference, Rc<tokio::sync::OnceCell<Rc<PluginWrapper>>>>>,
   wasm_module_creator: WasmModuleCreator,
   next_config_id: IdGenerator,
+  native_plugins: Vec<Rc<PluginWrapper>>,
 }
 
 impl<TEnvironment: Environment> PluginResolver<TEnvironment> {
@@ -66,12 +73,24 @@ impl<TEnvironment: Environment> PluginResolver<TEnvironment> {
       memory_cache: Default::default(),
       wasm_module_creator: Default::default(),
       next_config_id: Default::default(),
+      native_plugins: Vec::new(),
     }
   }
 
+  /// Sets the plugins compiled into the binary. These are included in
+  /// every plugins scope without being specified in the configuration.
+  pub fn with_native_plugins(mut self, plugins: Vec<Box<dyn Plugin>>) -> Self {
+    self.native_plugins = plugins.into_iter().map(|plugin| Rc::new(PluginWrapper::new(plugin))).collect();
+    self
+  }
+
+  pub fn native_plugins(&self) -> &[Rc<PluginWrapper>] {
+    &self.native_plugins
+  }
+
   pub async fn clear_and_shutdown_initialized(&self) {
     let plugins = self.memory_cache.borrow_mut().drain().collect::<Vec<_>>();
-    let futures = plugins.iter().filter_map(|p| p.1.get()).map(|p| p.shutdown());
+    let futures = plugins.iter().filter_map(|p| p.1.get()).chain(self.native_plugins.iter()).map(|p| p.shutdown());
     future::join_all(futures).await;
   }
 
@@ -11,9 +11,11 @@ use anyhow::Result;
 use dprint_core::async_runtime::FutureExt;
 use dprint_core::async_runtime::LocalBoxFuture;
 use dprint_core::configuration::ConfigKeyMap;
+use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::plugins::process::HostFormatCallback;
 use dprint_core::plugins::CancellationToken;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::CriticalFormatError;
 use dprint_core::plugins::FileMatchingInfo;
@@ -45,6 +47,7 @@ use crate::patterns::FileMatcher;
 use crate::plugins::output_plugin_config_diagnostics;
 use crate::plugins::FormatConfig;
 use crate::plugins::InitializedPlugin;
+use crate::plugins::InitializedPluginCheckRequest;
 use crate::plugins::InitializedPluginFormatRequest;
 use crate::plugins::OutputPluginConfigDiagnosticsError;
 use crate::plugins::PluginNameResolutionMaps;
@@ -181,10 +184,26 @@ impl InitializedPluginWithConfig {
     output_plugin_config_diagnostics(&self.info().name, &*self.instance, self.plugin.format_config.clone(), environment).await
   }
 
+  pub async fn config_diagnostics(&self) -> Result<Vec<ConfigurationDiagnostic>> {
+    self.instance.config_diagnostics(self.plugin.format_config.clone()).await
+  }
+
   pub async fn check_config_updates(&self, message: CheckConfigUpdatesMessage) -> Result<Vec<ConfigChange>> {
     self.instance.check_config_updates(message).await
   }
 
+  pub async fn check_text(&self, file_path: PathBuf, file_bytes: Vec<u8>, token: Arc<dyn CancellationToken>) -> CheckResult {
+    self
+      .instance
+      .check_text(InitializedPluginCheckRequest {
+        file_path,
+        file_text: file_bytes,
+        config: self.plugin.format_config.clone(),
+        token,
+      })
+      .await
+  }
+
   pub async fn format_text(&self, request: InitializedPluginWithConfigFormatRequest) -> FormatResult {
     self
       .instance
@@ -297,6 +316,10 @@ impl<TEnvironment: Environment> PluginsScope<TEnvironment> {
     Err(ResolveConfigError::Other(anyhow::anyhow!("{}", output_text)))
   }
 
+  pub fn global_config_diagnostics(&self) -> &[GlobalConfigDiagnostic] {
+    &self.global_config_diagnostics
+  }
+
   pub fn process_plugin_count(&self) -> usize {
     self.plugins.values().filter(|p| p.plugin.is_process_plugin()).count()
   }
@@ -344,14 +367,26 @@ impl<TEnvironment: Environment> PluginsScope<TEnvironment> {
   }
 
   pub fn format(self: &Rc<Self>, request: HostFormatRequest) -> LocalBoxFuture<'static, FormatResult> {
-    let plugin_names = self.plugin_name_maps.get_plugin_names_from_file_path(&request.file_path);
+    let language_plugin_names = request
+      .hints
+      .language
+      .as_ref()
+      .and_then(|language| self.plugin_name_maps.get_plugin_names_from_language(language, &request.file_path));
+    let (file_path, plugin_names) = match language_plugin_names {
+      Some((extension, plugin_names)) => (request.file_path.with_extension(extension), plugin_names),
+      None => {
+        let plugin_names = self.plugin_name_maps.get_plugin_names_from_file_path(&request.file_path);
+        (request.file_path, plugin_names)
+      }
+    };
     log_debug!(
       self.environment,
-      "Host formatting {} - File length: {} - Plugins: [{}] - Range: {:?}",
-      request.file_path.display(),
+      "Host formatting {} - File length: {} - Plugins: [{}] - Range: {:?} - Hints: {:?}",
+      file_path.display(),
       request.file_bytes.len(),
       plugin_names.join(", "),
       request.range,
+      request.hints,
     );
     let scope = self.clone();
     async move {
@@ -359,14 +394,18 @@ impl<TEnvironment: Environment> PluginsScope<TEnvironment> {
       let mut had_change = false;
       for plugin_name in plugin_names {
         let plugin = scope.get_plugin(&plugin_name);
+        let mut override_config = request.override_config.clone();
+        request
+          .hints
+          .add_inherited_to_override_config(&mut override_config, &plugin.format_config.plugin);
         match plugin.get_or_create_checking_config_diagnostics(&scope.environment).await {
           Ok(GetPluginResult::Success(initialized_plugin)) => {
             let result = initialized_plugin
               .format_text(InitializedPluginWithConfigFormatRequest {
-                file_path: request.file_path.clone(),
+                file_path: file_path.clone(),
                 file_bytes: file_text.clone(),
                 range: request.range.clone(),
-                override_config: request.override_config.clone(),
+                override_config,
                 on_host_format: scope.create_host_format_callback(),
                 token: request.token.clone(),
               })
@@ -385,6 +424,26 @@ impl<TEnvironment: Environment> PluginsScope<TEnvironment> {
     }
     .boxed_local()
   }
+
+  /// Checks the file with the plugins that format it.
+  pub async fn check(&self, file_path: &Path, file_bytes: &[u8], token: Arc<dyn CancellationToken>) -> CheckResult {
+    let plugin_names = self.plugin_name_maps.get_plugin_names_from_file_path(file_path);
+    let mut diagnostics = Vec::new();
+    for plugin_name in plugin_names {
+      let plugin = self.get_plugin(&plugin_name);
+      match plugin.get_or_create_checking_config_diagnostics(&self.environment).await? {
+        GetPluginResult::Success(initialized_plugin) => {
+          diagnostics.extend(
+            initialized_plugin
+              .check_text(file_path.to_path_buf(), file_bytes.to_vec(), token.clone())
+              .await?,
+          );
+        }
+        GetPluginResult::HadDiagnostics(count) => bail!("Had {} configuration errors.", count),
+      }
+    }
+    Ok(diagnostics)
+  }
 }
 
 pub struct PluginsScopeAndPathsCollection<TEnvironment: Environment> {
@@ -581,7 +640,11 @@ pub async fn resolve_plugins_scope<TEnvironment: Environment>(
   plugin_resolver: &Rc<PluginResolver<TEnvironment>>,
 ) -> Result<PluginsScope<TEnvironment>, ResolvePluginsError> {
   // resolve the plugins
-  let plugins = plugin_resolver.resolve_plugins(config.plugins.clone()).await?;
+  let mut plugins = plugin_resolver.resolve_plugins(config.plugins.clone()).await?;
+  // a native plugin takes the place of the same plugin in the configuration
+  let native_plugins = plugin_resolver.native_plugins();
+  plugins.retain(|plugin| !native_plugins.iter().any(|native| native.info().name == plugin.info().name));
+  plugins.extend(native_plugins.iter().cloned());
   let mut config_map = config.config_map.clone();
 
   // resolve each plugin's configuration
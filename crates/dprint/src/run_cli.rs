@@ -114,6 +114,7 @@ pub async fn run_cli<TEnvironment: Environment>(args: &CliArgs, environment: &TE
     SubCommand::EditorInfo => commands::output_editor_info(args, environment, plugin_resolver).await,
     SubCommand::EditorService(cmd) => commands::run_editor_service(args, environment, plugin_resolver, cmd).await,
     SubCommand::Lsp => commands::run_language_server(args, environment, plugin_resolver).await,
+    SubCommand::Daemon(cmd) => commands::run_daemon(cmd, environment).await,
     SubCommand::ClearCache => commands::clear_cache(environment),
     SubCommand::Config(cmd) => match cmd {
       ConfigSubCommand::Init => commands::init_config_file(environment, &args.config).await,
@@ -122,10 +123,15 @@ pub async fn run_cli<TEnvironment: Environment>(args: &CliArgs, environment: &TE
     },
     SubCommand::Version => commands::output_version(environment),
     SubCommand::StdInFmt(cmd) => commands::stdin_fmt(cmd, args, environment, plugin_resolver).await,
+    SubCommand::StdInFmtFiles(cmd) => commands::stdin_fmt_files(cmd, args, environment, plugin_resolver).await,
     SubCommand::OutputResolvedConfig => commands::output_resolved_config(args, environment, plugin_resolver).await,
     SubCommand::OutputFilePaths(cmd) => commands::output_file_paths(cmd, args, environment, plugin_resolver).await,
     SubCommand::OutputFormatTimes(cmd) => commands::output_format_times(cmd, args, environment, plugin_resolver).await,
-    SubCommand::Check(cmd) => commands::check(cmd, args, environment, plugin_resolver).await,
+    SubCommand::Bench(cmd) => commands::bench(cmd, args, environment, plugin_resolver).await,
+    SubCommand::Check(cmd) => match &cmd.git_ref {
+      Some(git_ref) => commands::check_git_ref(git_ref, cmd, args, environment).await,
+      None => commands::check(cmd, args, environment, plugin_resolver).await,
+    },
     SubCommand::Fmt(cmd) => commands::format(cmd, args, environment, plugin_resolver).await,
     SubCommand::Completions(shell) => commands::completions(shell.to_owned(), environment),
     SubCommand::Upgrade => commands::upgrade(environment).await,
@@ -1,5 +1,4 @@
 use std::cell::RefCell;
-use std::io::Write;
 use std::path::PathBuf;
 use std::rc::Rc;
 
@@ -10,6 +9,7 @@ use thiserror::Error;
 
 use crate::arg_parser::parse_args;
 use crate::environment::TestEnvironment;
+use crate::plugin_spec_harness::create_process_plugin_zip;
 use crate::plugins::PluginCache;
 use crate::plugins::PluginResolver;
 use crate::run_cli::run_cli;
@@ -54,23 +54,8 @@ pub static WASM_PLUGIN_BYTES: &'static [u8] = include_bytes!("../../test-plugin/
 pub static WASM_PLUGIN_0_1_0_BYTES: &'static [u8] = include_bytes!("../../test-plugin/test_plugin_0_1_0.wasm");
 // cache these so it only has to be done once across all tests
 pub static PROCESS_PLUGIN_ZIP_BYTES: Lazy<Vec<u8>> = Lazy::new(|| {
-  let buf: Vec<u8> = Vec::new();
-  let w = std::io::Cursor::new(buf);
-  let mut zip = zip::ZipWriter::new(w);
-  let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
-  zip
-    .start_file(
-      if cfg!(target_os = "windows") {
-        "test-process-plugin.exe"
-      } else {
-        "test-process-plugin"
-      },
-      options,
-    )
-    .unwrap();
   let file_bytes = std::fs::read(&*TEST_PROCESS_PLUGIN_PATH).unwrap();
-  zip.write(&file_bytes).unwrap();
-  zip.finish().unwrap().into_inner()
+  create_process_plugin_zip("test-process-plugin", &file_bytes).unwrap()
 });
 pub static PROCESS_PLUGIN_ZIP_CHECKSUM: Lazy<String> = Lazy::new(|| crate::utils::get_sha256_checksum(&PROCESS_PLUGIN_ZIP_BYTES));
 
@@ -269,11 +254,13 @@ SUBCOMMANDS:
   output-file-paths       Prints the resolved file paths for the plugins based on the args and configuration.
   output-resolved-config  Prints the resolved configuration for the plugins based on the args and configuration.
   output-format-times     Prints the amount of time it takes to format each file. Use this for debugging.
+  bench                   Benchmarks the plugins on each file and outputs the results as JSON.
   clear-cache             Deletes the plugin cache directory.
   upgrade                 Upgrades the dprint executable.
   completions             Generate shell completions script for dprint
   license                 Outputs the software license.
   lsp                     Starts up a language server for formatting files.
+  daemon                  Runs a background process that keeps plugins loaded between `fmt` and `check` runs.
 
 More details at `dprint help <SUBCOMMAND>`
 
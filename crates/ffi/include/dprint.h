@@ -0,0 +1,45 @@
+#ifndef DPRINT_H
+#define DPRINT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DprintSession DprintSession;
+
+typedef enum DprintFormatStatus {
+  DPRINT_FORMAT_STATUS_UNCHANGED = 0,
+  DPRINT_FORMAT_STATUS_CHANGED = 1,
+  DPRINT_FORMAT_STATUS_SKIPPED = 2,
+  DPRINT_FORMAT_STATUS_ERROR = 3,
+} DprintFormatStatus;
+
+typedef struct DprintFormatResult {
+  DprintFormatStatus status;
+  /* Changed: formatted text. Error: UTF-8 message. Otherwise null. */
+  uint8_t *data;
+  size_t len;
+} DprintFormatResult;
+
+/* Returns null on failure and sets `error_out` (when not null) to a message
+   that must be released with `dprint_string_free`. */
+DprintSession *dprint_session_new(const char *config_path, char **error_out);
+
+/* Must be called on the thread that created the session, otherwise the
+   status is an error. */
+DprintFormatResult dprint_session_format(DprintSession *session, const char *file_name, const uint8_t *data, size_t len);
+
+void dprint_session_free(DprintSession *session);
+
+void dprint_format_result_free(DprintFormatResult *result);
+
+void dprint_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,302 @@
+//! C ABI for formatting with dprint from other languages.
+//!
+//! A session resolves the configuration and loads the plugins once, then
+//! stays warm for any number of format calls until it's freed. See
+//! `include/dprint.h` for the C declarations.
+//!
+//! A session must only be used from the thread that created it. Formatting
+//! on another thread returns an error.
+
+use std::ffi::c_char;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::ptr;
+use std::thread::ThreadId;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use dprint::ConfigSource;
+use dprint::FormatOutput;
+use dprint::Formatter;
+use dprint::FormatterOptions;
+
+/// Opaque handle to a formatter with its plugins loaded.
+pub struct DprintSession {
+  formatter: Formatter,
+  thread_id: ThreadId,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DprintFormatStatus {
+  /// The text was already formatted. `data` is null.
+  Unchanged = 0,
+  /// `data` contains the formatted text.
+  Changed = 1,
+  /// No plugin formats the file or it's excluded. `data` is null.
+  Skipped = 2,
+  /// `data` contains a UTF-8 error message.
+  Error = 3,
+}
+
+/// The result of [`dprint_session_format`], which must be released with
+/// [`dprint_format_result_free`].
+#[repr(C)]
+pub struct DprintFormatResult {
+  pub status: DprintFormatStatus,
+  pub data: *mut u8,
+  pub len: usize,
+}
+
+impl DprintFormatResult {
+  fn new(status: DprintFormatStatus, data: Option<Vec<u8>>) -> Self {
+    match data {
+      Some(data) => {
+        let len = data.len();
+        let data = Box::into_raw(data.into_boxed_slice()) as *mut u8;
+        Self { status, data, len }
+      }
+      None => Self {
+        status,
+        data: ptr::null_mut(),
+        len: 0,
+      },
+    }
+  }
+
+  fn error(err: anyhow::Error) -> Self {
+    Self::new(DprintFormatStatus::Error, Some(format!("{:#}", err).into_bytes()))
+  }
+}
+
+/// Creates a session from the configuration file at `config_path`.
+///
+/// Returns null on failure and, when `error_out` is not null, sets it to an
+/// error message that must be released with [`dprint_string_free`].
+///
+/// # Safety
+///
+/// `config_path` must be a valid null terminated string and `error_out`
+/// must be null or point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn dprint_session_new(config_path: *const c_char, error_out: *mut *mut c_char) -> *mut DprintSession {
+  let result = catch_unwind(|| {
+    let config_path = read_str(config_path, "config_path")?;
+    let formatter = Formatter::new(FormatterOptions::new(ConfigSource::Path(PathBuf::from(config_path))))?;
+    Ok(DprintSession {
+      formatter,
+      thread_id: std::thread::current().id(),
+    })
+  })
+  .unwrap_or_else(|_| Err(anyhow!("Panicked creating the session.")));
+  match result {
+    Ok(session) => Box::into_raw(Box::new(session)),
+    Err(err) => {
+      if !error_out.is_null() {
+        *error_out = to_c_string(format!("{:#}", err));
+      }
+      ptr::null_mut()
+    }
+  }
+}
+
+/// Formats `len` bytes of text as if it were the file at `file_name`.
+///
+/// Relative file names are resolved against the configuration file's directory
+/// before its "includes" and "excludes" are applied. Returns an error status
+/// when called on a thread other than the one that created the session.
+///
+/// # Safety
+///
+/// `session` must be a live session from [`dprint_session_new`], `file_name` a valid
+/// null terminated string and `data` must point to `len` readable bytes (or be null
+/// when `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn dprint_session_format(session: *mut DprintSession, file_name: *const c_char, data: *const u8, len: usize) -> DprintFormatResult {
+  let result = catch_unwind(AssertUnwindSafe(|| {
+    let Some(session) = session.as_ref() else {
+      return Err(anyhow!("The session was null."));
+    };
+    if session.thread_id != std::thread::current().id() {
+      return Err(anyhow!("The session was used on a thread other than the one that created it."));
+    }
+    let file_name = read_str(file_name, "file_name")?;
+    let file_text = if len == 0 {
+      &[]
+    } else if data.is_null() {
+      return Err(anyhow!("The data was null."));
+    } else {
+      std::slice::from_raw_parts(data, len)
+    };
+    session.formatter.format_text(file_name, file_text)
+  }))
+  .unwrap_or_else(|_| Err(anyhow!("Panicked formatting the text.")));
+  match result {
+    Ok(FormatOutput::Changed(text)) => DprintFormatResult::new(DprintFormatStatus::Changed, Some(text)),
+    Ok(FormatOutput::Unchanged) => DprintFormatResult::new(DprintFormatStatus::Unchanged, None),
+    Ok(FormatOutput::Skipped) => DprintFormatResult::new(DprintFormatStatus::Skipped, None),
+    Err(err) => DprintFormatResult::error(err),
+  }
+}
+
+/// Shuts down the session's plugins and releases it.
+///
+/// # Safety
+///
+/// `session` must be null or a session from [`dprint_session_new`] that wasn't
+/// already freed. It must be freed on the thread that created it.
+#[no_mangle]
+pub unsafe extern "C" fn dprint_session_free(session: *mut DprintSession) {
+  if !session.is_null() {
+    let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(session))));
+  }
+}
+
+/// Releases the data of a format result.
+///
+/// # Safety
+///
+/// `result` must be null or point to a result from [`dprint_session_format`]
+/// whose data wasn't already released.
+#[no_mangle]
+pub unsafe extern "C" fn dprint_format_result_free(result: *mut DprintFormatResult) {
+  let Some(result) = result.as_mut() else {
+    return;
+  };
+  if !result.data.is_null() {
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(result.data, result.len)));
+    result.data = ptr::null_mut();
+    result.len = 0;
+  }
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+///
+/// `value` must be null or a string from this library that wasn't already released.
+#[no_mangle]
+pub unsafe extern "C" fn dprint_string_free(value: *mut c_char) {
+  if !value.is_null() {
+    drop(CString::from_raw(value));
+  }
+}
+
+unsafe fn read_str<'a>(value: *const c_char, name: &str) -> Result<&'a str> {
+  if value.is_null() {
+    return Err(anyhow!("The {} was null.", name));
+  }
+  CStr::from_ptr(value)
+    .to_str()
+    .map_err(|err| anyhow!("The {} was not valid UTF-8: {}", name, err))
+}
+
+fn to_c_string(text: String) -> *mut c_char {
+  // interior nulls would truncate the message, so replace them
+  CString::new(text.replace('\0', " ")).unwrap().into_raw()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn take_error(error: *mut c_char) -> String {
+    assert!(!error.is_null());
+    let text = unsafe { CStr::from_ptr(error) }.to_string_lossy().to_string();
+    unsafe { dprint_string_free(error) };
+    text
+  }
+
+  fn format(session: *mut DprintSession, file_name: &str, text: &str) -> (DprintFormatStatus, String) {
+    let file_name = CString::new(file_name).unwrap();
+    unsafe {
+      let mut result = dprint_session_format(session, file_name.as_ptr(), text.as_ptr(), text.len());
+      let data = if result.data.is_null() {
+        String::new()
+      } else {
+        String::from_utf8(std::slice::from_raw_parts(result.data, result.len).to_vec()).unwrap()
+      };
+      let status = result.status;
+      dprint_format_result_free(&mut result);
+      assert!(result.data.is_null());
+      (status, data)
+    }
+  }
+
+  #[test]
+  fn should_format_with_session() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let plugin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../test-plugin/test_plugin.wasm");
+    let config_path = temp_dir.path().join("dprint.json");
+    std::fs::write(
+      &config_path,
+      format!(r#"{{ "excludes": ["excluded.txt"], "plugins": [{}] }}"#, json_string(&plugin_path)),
+    )
+    .unwrap();
+
+    let config_path = CString::new(config_path.to_string_lossy().to_string()).unwrap();
+    let mut error = ptr::null_mut();
+    let session = unsafe { dprint_session_new(config_path.as_ptr(), &mut error) };
+    assert!(error.is_null());
+    assert!(!session.is_null());
+
+    assert_eq!(format(session, "file.txt", "text"), (DprintFormatStatus::Changed, "text_formatted".to_string()));
+    assert_eq!(format(session, "file.txt", "text_formatted"), (DprintFormatStatus::Unchanged, String::new()));
+    assert_eq!(format(session, "file.other", "text"), (DprintFormatStatus::Skipped, String::new()));
+    assert_eq!(format(session, "excluded.txt", "text"), (DprintFormatStatus::Skipped, String::new()));
+    let excluded_path = temp_dir.path().join("excluded.txt");
+    assert_eq!(
+      format(session, &excluded_path.to_string_lossy(), "text"),
+      (DprintFormatStatus::Skipped, String::new())
+    );
+    let (status, message) = format(session, "file.txt", "should_error");
+    assert_eq!(status, DprintFormatStatus::Error);
+    assert!(message.contains("Did error."), "{}", message);
+
+    // raw pointers aren't Send, so pass the address to the other thread
+    let session_address = session as usize;
+    let other_thread_result = std::thread::spawn(move || format(session_address as *mut DprintSession, "file.txt", "text"))
+      .join()
+      .unwrap();
+    assert_eq!(
+      other_thread_result,
+      (
+        DprintFormatStatus::Error,
+        "The session was used on a thread other than the one that created it.".to_string()
+      )
+    );
+
+    unsafe { dprint_session_free(session) };
+  }
+
+  #[test]
+  fn should_error_creating_session_for_missing_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = CString::new(temp_dir.path().join("dprint.json").to_string_lossy().to_string()).unwrap();
+    let mut error = ptr::null_mut();
+    let session = unsafe { dprint_session_new(config_path.as_ptr(), &mut error) };
+    assert!(session.is_null());
+    assert!(!take_error(error).is_empty());
+
+    // null arguments
+    let session = unsafe { dprint_session_new(ptr::null(), &mut error) };
+    assert!(session.is_null());
+    assert_eq!(take_error(error), "The config_path was null.");
+    assert!(unsafe { dprint_session_new(ptr::null(), ptr::null_mut()) }.is_null());
+  }
+
+  #[test]
+  fn should_error_formatting_with_null_session() {
+    assert_eq!(
+      format(ptr::null_mut(), "file.txt", "text"),
+      (DprintFormatStatus::Error, "The session was null.".to_string())
+    );
+  }
+
+  fn json_string(path: &std::path::Path) -> String {
+    format!("\"{}\"", path.to_string_lossy().replace('\\', "\\\\"))
+  }
+}
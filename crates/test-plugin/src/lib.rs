@@ -9,12 +9,17 @@ use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::configuration::GlobalConfiguration;
 use dprint_core::generate_plugin_code;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::ConfigChangeKind;
 use dprint_core::plugins::FileMatchingInfo;
+use dprint_core::plugins::FormatDiagnostic;
+use dprint_core::plugins::FormatDiagnosticSeverity;
 use dprint_core::plugins::FormatResult;
+use dprint_core::plugins::HostFormatHints;
 use dprint_core::plugins::PluginInfo;
 use dprint_core::plugins::PluginResolveConfigurationResult;
+use dprint_core::plugins::SyncCheckRequest;
 use dprint_core::plugins::SyncFormatRequest;
 use dprint_core::plugins::SyncHostFormatRequest;
 use dprint_core::plugins::SyncPluginHandler;
@@ -178,6 +183,7 @@ impl SyncPluginHandler<Configuration> for TestWasmPlugin {
               file_bytes: new_text.as_bytes(),
               range: Some(range),
               override_config: &Default::default(),
+              hints: Default::default(),
             }),
             new_text
           )?
@@ -194,7 +200,9 @@ impl SyncPluginHandler<Configuration> for TestWasmPlugin {
             file_bytes: new_text.as_bytes(),
             range: None,
             override_config: &ConfigKeyMap::new(),
-          }),
+            hints: Default::default(),
+          })
+          .map_err(|err| offset_diagnostic(err, "plugin: ".len())),
           new_text,
         )?,
       )
@@ -209,12 +217,42 @@ impl SyncPluginHandler<Configuration> for TestWasmPlugin {
             file_bytes: new_text.as_bytes(),
             range: None,
             override_config: &config_map,
+            hints: Default::default(),
+          }),
+          new_text
+        )?
+      )
+    } else if let Some((language, new_text)) = file_text.strip_prefix("plugin-language: ").and_then(|text| text.split_once(": ")) {
+      format!(
+        "plugin-language: {}: {}",
+        language,
+        handle_host_response(
+          format_with_host(SyncHostFormatRequest {
+            file_path: &PathBuf::from("./test.embedded"),
+            file_bytes: new_text.as_bytes(),
+            range: None,
+            override_config: &ConfigKeyMap::new(),
+            hints: HostFormatHints {
+              language: Some(language.to_string()),
+              ..Default::default()
+            },
           }),
           new_text
         )?
       )
     } else if file_text == "should_error" {
       bail!("Did error.")
+    } else if let Some(message) = file_text.strip_prefix("diagnostic: ") {
+      let start = file_text.len() - message.len();
+      return Err(
+        FormatDiagnostic {
+          message: message.to_string(),
+          range: Some(start..file_text.len()),
+          severity: FormatDiagnosticSeverity::Error,
+          code: Some("test".to_string()),
+        }
+        .into(),
+      );
     } else if file_text == "should_panic" {
       self.has_panicked = true;
       panic!("Test panic")
@@ -236,6 +274,33 @@ impl SyncPluginHandler<Configuration> for TestWasmPlugin {
       Ok(Some(format!("{}_{}", inner_format_text, request.config.ending).into_bytes()))
     }
   }
+
+  fn check(&mut self, request: SyncCheckRequest<Configuration>) -> CheckResult {
+    let file_text = String::from_utf8(request.file_bytes)?;
+    let mut diagnostics = Vec::new();
+    if let Some(message) = file_text.strip_prefix("warn: ") {
+      let message = message.strip_suffix(&format!("_{}", request.config.ending)).unwrap_or(message);
+      let start = "warn: ".len();
+      diagnostics.push(FormatDiagnostic {
+        message: message.to_string(),
+        range: Some(start..start + message.len()),
+        severity: FormatDiagnosticSeverity::Warning,
+        code: Some("test".to_string()),
+      });
+    }
+    Ok(diagnostics)
+  }
+}
+
+/// Moves the range of a diagnostic from formatting embedded text to be within the file text.
+fn offset_diagnostic(err: anyhow::Error, offset: usize) -> anyhow::Error {
+  match err.downcast::<FormatDiagnostic>() {
+    Ok(mut diagnostic) => {
+      diagnostic.range = diagnostic.range.map(|range| range.start + offset..range.end + offset);
+      diagnostic.into()
+    }
+    Err(err) => err,
+  }
 }
 
 generate_plugin_code!(TestWasmPlugin, TestWasmPlugin::new());
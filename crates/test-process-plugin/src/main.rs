@@ -11,16 +11,25 @@ use dprint_core::configuration::ConfigKeyMap;
 use dprint_core::configuration::ConfigKeyValue;
 use dprint_core::configuration::ConfigurationDiagnostic;
 use dprint_core::configuration::GlobalConfiguration;
+use dprint_core::configuration::WhitespaceOptions;
+use dprint_core::formatting::PrintItems;
+use dprint_core::formatting::PrintOptions;
+use dprint_core::formatting::Signal;
 use dprint_core::plugins::process::get_parent_process_id_from_cli_args;
 use dprint_core::plugins::process::handle_process_stdio_messages;
 use dprint_core::plugins::process::start_parent_process_checker_task;
 use dprint_core::plugins::AsyncPluginHandler;
 use dprint_core::plugins::CheckConfigUpdatesMessage;
+use dprint_core::plugins::CheckRequest;
+use dprint_core::plugins::CheckResult;
 use dprint_core::plugins::ConfigChange;
 use dprint_core::plugins::ConfigChangeKind;
 use dprint_core::plugins::FileMatchingInfo;
+use dprint_core::plugins::FormatDiagnostic;
+use dprint_core::plugins::FormatDiagnosticSeverity;
 use dprint_core::plugins::FormatRequest;
 use dprint_core::plugins::FormatResult;
+use dprint_core::plugins::HostFormatHints;
 use dprint_core::plugins::HostFormatRequest;
 use dprint_core::plugins::PluginInfo;
 use dprint_core::plugins::PluginResolveConfigurationResult;
@@ -163,6 +172,13 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
     mut format_with_host: impl FnMut(HostFormatRequest) -> LocalBoxFuture<'static, FormatResult> + 'static,
   ) -> FormatResult {
     let file_text = String::from_utf8(request.file_bytes)?;
+    if file_text.starts_with("unchanged") {
+      return Ok(None);
+    }
+    if file_text.starts_with("print:") {
+      let printed_text = print_words(&file_text, request.whitespace);
+      return Ok(if printed_text == file_text { None } else { Some(printed_text.into_bytes()) });
+    }
     let (had_suffix, file_text) = if let Some(text) = file_text.strip_suffix(&format!("_{}", request.config.ending)) {
       (true, text.to_string())
     } else {
@@ -176,6 +192,7 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
           file_bytes: new_text.to_string().into_bytes(),
           range: Some(range),
           override_config: Default::default(),
+          hints: Default::default(),
           token: request.token.clone(),
         })
         .await?;
@@ -195,6 +212,7 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
         file_bytes: new_text.to_string().into_bytes(),
         range: None,
         override_config: Default::default(),
+        hints: Default::default(),
         token: request.token.clone(),
       })
       .await?;
@@ -210,6 +228,7 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
         file_bytes: new_text.to_string().into_bytes(),
         range: None,
         override_config: config_map,
+        hints: Default::default(),
         token: request.token.clone(),
       })
       .await?;
@@ -217,8 +236,37 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
         "plugin-config: {}",
         result.map(|r| String::from_utf8(r).unwrap()).unwrap_or_else(|| new_text.to_string())
       )
+    } else if let Some((language, new_text)) = file_text.strip_prefix("plugin-language: ").and_then(|text| text.split_once(": ")) {
+      let result = (format_with_host)(HostFormatRequest {
+        file_path: PathBuf::from("./test.embedded"),
+        file_bytes: new_text.to_string().into_bytes(),
+        range: None,
+        override_config: Default::default(),
+        hints: HostFormatHints {
+          language: Some(language.to_string()),
+          ..Default::default()
+        },
+        token: request.token.clone(),
+      })
+      .await?;
+      format!(
+        "plugin-language: {}: {}",
+        language,
+        result.map(|r| String::from_utf8(r).unwrap()).unwrap_or_else(|| new_text.to_string())
+      )
     } else if file_text == "should_error" {
       bail!("Did error.")
+    } else if let Some(message) = file_text.strip_prefix("diagnostic: ") {
+      let start = file_text.len() - message.len();
+      return Err(
+        FormatDiagnostic {
+          message: message.to_string(),
+          range: Some(start..file_text.len()),
+          severity: FormatDiagnosticSeverity::Error,
+          code: Some("test".to_string()),
+        }
+        .into(),
+      );
     } else {
       file_text.to_string()
     };
@@ -229,4 +277,44 @@ impl AsyncPluginHandler for TestProcessPluginHandler {
       Ok(Some(format!("{}_{}", inner_format_text, request.config.ending).into_bytes()))
     }
   }
+  async fn check(&self, request: CheckRequest<Self::Configuration>) -> CheckResult {
+    let file_text = String::from_utf8(request.file_bytes)?;
+    let mut diagnostics = Vec::new();
+    if let Some(message) = file_text.strip_prefix("warn: ") {
+      let message = message.strip_suffix(&format!("_{}", request.config.ending)).unwrap_or(message);
+      let start = "warn: ".len();
+      diagnostics.push(FormatDiagnostic {
+        message: message.to_string(),
+        range: Some(start..start + message.len()),
+        severity: FormatDiagnosticSeverity::Warning,
+        code: Some("test".to_string()),
+      });
+    }
+    Ok(diagnostics)
+  }
+}
+
+/// Prints the words of each line with spaces between them so
+/// the printer enforces the global whitespace policies.
+fn print_words(text: &str, whitespace: WhitespaceOptions) -> String {
+  dprint_core::formatting::format(
+    || {
+      let mut items = PrintItems::new();
+      for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+          items.push_signal(Signal::NewLine);
+        }
+        for (j, word) in line.split(' ').enumerate() {
+          if j > 0 {
+            items.push_space();
+          }
+          if !word.is_empty() {
+            items.push_string(word.to_string());
+          }
+        }
+      }
+      items
+    },
+    PrintOptions::new(120, 2, false, "\n").with_whitespace(whitespace),
+  )
 }